@@ -0,0 +1,282 @@
+use crate::{Keyed, NoTeardown, Owned, Teardown, WithArgs};
+use alloc::vec::Vec;
+use core::mem;
+
+/// Key of an [`ArenaComponentMap`] entry: a slot index plus the generation of
+/// that slot, so an id kept after its entry was removed never reaches the
+/// entry that later reuses the slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComponentId {
+    index: u32,
+    generation: u32,
+}
+
+impl ComponentId {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Debug)]
+struct Slot<Args, Comp> {
+    generation: u32,
+    entry: Option<WithArgs<Args, Comp>>,
+}
+
+/// A component map for dense ids, storing entries in a slab indexed by
+/// [`ComponentId`] for O(1) access without hashing.
+///
+/// Ids are handed out by [`insert`](Self::insert), and slots freed by
+/// [`remove`](Self::remove) are reused under a new generation.
+///
+/// The arena has no change events, so it takes no listeners or `Instrument`;
+/// time its builds inside the init function instead.
+#[derive(Debug)]
+pub struct ArenaComponentMap<Args, Comp, FnInit, FnDrop = NoTeardown>
+where
+    FnDrop: Teardown<ComponentId, Comp>,
+{
+    slots: Vec<Slot<Args, Comp>>,
+    free: Vec<u32>,
+    len: usize,
+    init: Owned<FnInit>,
+    teardown: Owned<FnDrop>,
+}
+
+impl<Args, Comp, FnInit> ArenaComponentMap<Args, Comp, FnInit> {
+    pub fn new(init: FnInit) -> Self {
+        Self::with_capacity(init, 0)
+    }
+
+    pub fn with_capacity(init: FnInit, capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            len: 0,
+            init: Owned::new(init),
+            teardown: Owned::new(NoTeardown),
+        }
+    }
+
+    pub fn with_teardown<FnDropNext>(
+        mut self,
+        teardown: FnDropNext,
+    ) -> ArenaComponentMap<Args, Comp, FnInit, FnDropNext>
+    where
+        FnDropNext: Fn(&ComponentId, &mut Comp),
+    {
+        ArenaComponentMap {
+            slots: mem::take(&mut self.slots),
+            free: mem::take(&mut self.free),
+            len: self.len,
+            init: Owned::new(self.init.take()),
+            teardown: Owned::new(teardown),
+        }
+    }
+}
+
+impl<Args, Comp, FnInit, FnDrop> ArenaComponentMap<Args, Comp, FnInit, FnDrop>
+where
+    FnDrop: Teardown<ComponentId, Comp>,
+{
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Initialises a component from `args` in a free slot, returning its id.
+    pub fn insert(&mut self, args: Args) -> ComponentId
+    where
+        FnInit: Fn(&ComponentId, &Args) -> Comp,
+    {
+        let index = self.free.pop().unwrap_or_else(|| {
+            let index = u32::try_from(self.slots.len()).expect("arena exceeds u32::MAX slots");
+            self.slots.push(Slot {
+                generation: 0,
+                entry: None,
+            });
+            index
+        });
+        let slot = &mut self.slots[index as usize];
+        let id = ComponentId {
+            index,
+            generation: slot.generation,
+        };
+
+        let component = (self.init)(&id, &args);
+        slot.entry = Some(WithArgs::new(component, args));
+        self.len += 1;
+        id
+    }
+
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.entry(id).is_some()
+    }
+
+    pub fn get(&self, id: ComponentId) -> Option<&Comp> {
+        self.entry(id).map(|entry| {
+            entry.touch();
+            &entry.component
+        })
+    }
+
+    pub fn get_mut(&mut self, id: ComponentId) -> Option<&mut Comp> {
+        self.entry_mut(id).map(|entry| {
+            entry.touch();
+            &mut entry.component
+        })
+    }
+
+    pub fn get_args(&self, id: ComponentId) -> Option<&Args> {
+        self.entry(id).map(|entry| &entry.args)
+    }
+
+    /// Removes the entry through the teardown and frees its slot; `id` and any
+    /// copies of it go stale.
+    pub fn remove(&mut self, id: ComponentId) -> Option<WithArgs<Args, Comp>> {
+        let slot = self.slots.get_mut(id.index())?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let mut entry = slot.entry.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.len -= 1;
+
+        self.teardown.teardown(&id, &mut entry.component);
+        Some(entry)
+    }
+
+    /// Rebuilds the component from its args, returning the previous one.
+    pub fn reinit(&mut self, id: ComponentId) -> Option<Comp>
+    where
+        FnInit: Fn(&ComponentId, &Args) -> Comp,
+    {
+        let slot = self.slots.get_mut(id.index())?;
+        let entry = slot
+            .entry
+            .as_mut()
+            .filter(|_| slot.generation == id.generation)?;
+
+        let next = (self.init)(&id, &entry.args);
+        let mut prev = entry.replace_component(next);
+        self.teardown.teardown(&id, &mut prev);
+        Some(prev)
+    }
+
+    pub fn reinit_all(&mut self) -> impl Iterator<Item = Keyed<ComponentId, Comp>>
+    where
+        FnInit: Fn(&ComponentId, &Args) -> Comp,
+    {
+        let ids: Vec<_> = self.ids().collect();
+        ids.into_iter()
+            .filter_map(|id| self.reinit(id).map(|prev| Keyed::new(id, prev)))
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = ComponentId> {
+        self.iter().map(|(id, ..)| id)
+    }
+
+    /// Iterates over the entries in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (ComponentId, &Comp, &Args)> {
+        self.slots.iter().zip(0..).filter_map(|(slot, index)| {
+            let entry = slot.entry.as_ref()?;
+            let id = ComponentId {
+                index,
+                generation: slot.generation,
+            };
+            Some((id, &entry.component, &entry.args))
+        })
+    }
+
+    fn entry(&self, id: ComponentId) -> Option<&WithArgs<Args, Comp>> {
+        self.slots
+            .get(id.index())
+            .filter(|slot| slot.generation == id.generation)?
+            .entry
+            .as_ref()
+    }
+
+    fn entry_mut(&mut self, id: ComponentId) -> Option<&mut WithArgs<Args, Comp>> {
+        self.slots
+            .get_mut(id.index())
+            .filter(|slot| slot.generation == id.generation)?
+            .entry
+            .as_mut()
+    }
+}
+
+impl<Args, Comp, FnInit, FnDrop> Drop for ArenaComponentMap<Args, Comp, FnInit, FnDrop>
+where
+    FnDrop: Teardown<ComponentId, Comp>,
+{
+    fn drop(&mut self) {
+        for (slot, index) in self.slots.iter_mut().zip(0..) {
+            if let Some(entry) = slot.entry.as_mut() {
+                let id = ComponentId {
+                    index,
+                    generation: slot.generation,
+                };
+                self.teardown.teardown(&id, &mut entry.component);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_stale_ids_miss_reused_slots() {
+        let init = |_id: &ComponentId, args: &usize| Counter(*args);
+        let mut arena = ArenaComponentMap::new(init);
+
+        let first = arena.insert(1);
+        let second = arena.insert(2);
+        assert_eq!(arena.len(), 2);
+        assert_eq!(arena.get(first), Some(&Counter(1)));
+
+        assert!(arena.remove(first).is_some());
+        assert!(arena.remove(first).is_none());
+
+        let third = arena.insert(3);
+        assert_eq!(third.index(), first.index());
+        assert_ne!(third.generation(), first.generation());
+        assert_eq!(arena.get(first), None);
+        assert_eq!(arena.get(third), Some(&Counter(3)));
+        assert_eq!(arena.ids().collect::<Vec<_>>(), vec![third, second]);
+    }
+
+    #[test]
+    fn test_reinit_and_teardown() {
+        let torn_down = Arc::new(Mutex::new(Vec::new()));
+        let torn_down_clone = torn_down.clone();
+        let init = |_id: &ComponentId, args: &usize| Counter(*args);
+        let mut arena = ArenaComponentMap::new(init).with_teardown(
+            move |_id: &ComponentId, component: &mut Counter| {
+                torn_down_clone.lock().unwrap().push(component.0);
+            },
+        );
+
+        let id = arena.insert(1);
+        *arena.get_mut(id).unwrap() = Counter(10);
+        assert_eq!(arena.reinit(id), Some(Counter(10)));
+        assert_eq!(arena.get(id), Some(&Counter(1)));
+        assert_eq!(arena.reinit_all().count(), 1);
+
+        drop(arena);
+        assert_eq!(*torn_down.lock().unwrap(), vec![10, 1, 1]);
+    }
+}
@@ -1,129 +1,436 @@
-use crate::{ComponentMap, Keyed, WithArgs};
-use futures::future::join_all;
+use crate::{
+    AsyncTeardown, BatchOptions, ChangeKind, ComponentMap, Keyed, KeyedError, KeyedStorage, Lookup,
+    NoTeardown, ReinitOutcome, Teardown, WithArgs,
+    batch::{join_bounded, try_join_bounded},
+    priority::prioritized,
+    retry::retry_async,
+    teardown::teardown_all,
+};
+use std::borrow::Borrow;
 
 impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
     pub async fn try_init_async<Error>(
         entries: impl IntoIterator<Item = (Key, Args)>,
         init: FnInit,
-    ) -> Result<Self, Error>
+    ) -> Result<Self, KeyedError<Key, Error>>
     where
         Key: Eq + std::hash::Hash,
         FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
     {
+        Self::try_init_async_with(entries, init, BatchOptions::default()).await
+    }
+
+    /// Like [`try_init_async`](Self::try_init_async), with per-call [`BatchOptions`].
+    pub async fn try_init_async_with<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        options: BatchOptions<Key>,
+    ) -> Result<Self, KeyedError<Key, Error>>
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let progress = options.progress_for(entries.len());
+
         let components_fut = entries.into_iter().map(|(key, args)| {
             let init = init.clone();
+            let progress = &progress;
             async move {
-                let result = (init)(&key, &args)
-                    .await
-                    .map(|component| WithArgs { component, args });
-
-                (key, result)
+                let result = retry_async(options.retry, || (init)(&key, &args)).await;
+                progress.report(&key);
+                match result {
+                    Ok(component) => Ok((key, WithArgs::new(component, args))),
+                    Err(error) => Err(KeyedError::new(key, error)),
+                }
             }
         });
 
-        let map = join_all(components_fut)
+        let map = join_bounded(components_fut, options.concurrency_limit)
             .await
             .into_iter()
-            .map(|(key, result)| result.map(|component| (key, component)))
             .collect::<Result<_, _>>()?;
 
-        Ok(Self { map: map, init })
+        Ok(Self::new(map, init, NoTeardown))
     }
 
+    /// Like [`try_init_async`](Self::try_init_async), but returns as soon as
+    /// any init fails, dropping the initialisations still in flight.
+    pub async fn try_init_async_fail_fast<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+    ) -> Result<Self, KeyedError<Key, Error>>
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+    {
+        Self::try_init_async_fail_fast_with(entries, init, BatchOptions::default()).await
+    }
+
+    /// Like [`try_init_async_fail_fast`](Self::try_init_async_fail_fast), with
+    /// per-call [`BatchOptions`].
+    pub async fn try_init_async_fail_fast_with<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        options: BatchOptions<Key>,
+    ) -> Result<Self, KeyedError<Key, Error>>
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let progress = options.progress_for(entries.len());
+
+        let components_fut = entries.into_iter().map(|(key, args)| {
+            let init = init.clone();
+            let progress = &progress;
+            async move {
+                let result = retry_async(options.retry, || (init)(&key, &args)).await;
+                progress.report(&key);
+                match result {
+                    Ok(component) => Ok((key, WithArgs::new(component, args))),
+                    Err(error) => Err(KeyedError::new(key, error)),
+                }
+            }
+        });
+
+        let map = try_join_bounded(components_fut, options.concurrency_limit)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(Self::new(map, init, NoTeardown))
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
     pub async fn try_reinit_all_async<Error>(
         &mut self,
     ) -> impl Iterator<Item = Keyed<&Key, Result<Comp, Error>>>
     where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        self.try_reinit_all_async_with(BatchOptions::default())
+            .await
+    }
+
+    /// Like [`try_reinit_all_async`](Self::try_reinit_all_async), with per-call [`BatchOptions`].
+    pub async fn try_reinit_all_async_with<Error>(
+        &mut self,
+        options: BatchOptions<Key>,
+    ) -> impl Iterator<Item = Keyed<&Key, Result<Comp, Error>>>
+    where
+        Key: Eq + std::hash::Hash,
         FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
     {
-        let next_components_fut = self
-            .map
-            .iter()
-            .map(|(key, component)| (self.init)(key, &component.args));
+        let options = options.or_config(&self.config);
+        let progress = options.progress_for(self.map.len());
+        let entries = prioritized(self.map.iter_mut(), &self.dependencies, &self.priorities);
+
+        let next_components_fut = entries.iter().map(|(key, component)| async {
+            let span = self.events.instrument.start(key, &component.args);
+            let result = retry_async(options.retry, || (self.init)(key, &component.args)).await;
+            span.finish(key);
+            progress.report(key);
+            result
+        });
 
-        let next_components = join_all(next_components_fut).await;
+        let next_components = join_bounded(next_components_fut, options.concurrency_limit).await;
 
-        self.map
-            .iter_mut()
+        let mut prev_components = entries
+            .into_iter()
             .zip(next_components)
             .map(|((key, prev), result)| {
-                let result = result.map(|next| std::mem::replace(&mut prev.component, next));
+                let result = result
+                    .map(|next| prev.replace_component(next))
+                    .inspect_err(|_| prev.record_failure());
+                self.events
+                    .emit_result(key, &result, ChangeKind::Reinitialized, &*prev);
 
                 Keyed::new(key, result)
             })
+            .collect::<Vec<_>>();
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .filter_map(|Keyed { key, value }| value.as_mut().ok().map(|prev| (&**key, prev))),
+        )
+        .await;
+
+        prev_components.into_iter()
     }
 
-    pub async fn try_reinit_async<Error>(
+    pub async fn try_reinit_async<'q, Q, Error>(
         &mut self,
-        keys: impl IntoIterator<Item = Key>,
-    ) -> impl Iterator<Item = Keyed<Key, Option<Result<Comp, Error>>>>
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, ReinitOutcome<Comp, Error>>>
     where
-        Key: Eq + std::hash::Hash + Clone,
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
         FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
     {
-        let next_components_fut = keys.into_iter().map(|key| {
-            let init = self.init.clone();
+        self.try_reinit_async_with(keys, BatchOptions::default())
+            .await
+    }
+
+    /// Like [`try_reinit_async`](Self::try_reinit_async), with per-call [`BatchOptions`].
+    pub async fn try_reinit_async_with<'q, Q, Error>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'q Q>,
+        options: BatchOptions<Key>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, ReinitOutcome<Comp, Error>>>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let options = options.or_config(&self.config);
+        let (entries, throttled): (Vec<_>, Vec<_>) = keys
+            .into_iter()
+            .map(|key| {
+                let throttled = self.is_throttled(key);
+                let entry = self.map.get_key_value(key).filter(|_| !throttled);
+                ((key, entry), throttled)
+            })
+            .unzip();
+        let progress =
+            options.progress_for(entries.iter().filter(|(_, entry)| entry.is_some()).count());
 
-            let args = self.map.get(&key).map(|component| &component.args);
+        let next_components_fut = entries.into_iter().map(|(key, entry)| {
+            let init = self.init.clone();
+            let instrument = &self.events.instrument;
+            let progress = &progress;
+            let options = &options;
 
             async move {
-                let result = match args {
-                    Some(args) => Some((init)(&key, args).await),
+                let result = match entry {
+                    Some((key, component)) => {
+                        let span = instrument.start(key, &component.args);
+                        let result = options.run(|| (init)(key, &component.args)).await;
+                        span.finish(key);
+                        progress.report(key);
+                        Some(result)
+                    }
                     None => None,
                 };
                 Keyed::new(key, result)
             }
         });
 
-        let results = join_all(next_components_fut).await;
+        let results = join_bounded(next_components_fut, options.concurrency_limit).await;
 
-        results.into_iter().map(|Keyed { key, value: result }| {
-            let prev = result
-                .map(|result| {
-                    result.map(|next| {
-                        self.map
-                            .get_mut(&key)
-                            .map(|component| std::mem::replace(&mut component.component, next))
+        let mut timed_out = Vec::with_capacity(results.len());
+        let mut prev_components = results
+            .into_iter()
+            .map(|Keyed { key, value: result }| {
+                // A timed out init counts as a failure, but leaves nothing to report
+                let result = match result {
+                    Some(None) => {
+                        if let Some(component) = self.map.get_mut(key) {
+                            component.record_failure();
+                        }
+                        timed_out.push(true);
+                        None
+                    }
+                    result => {
+                        timed_out.push(false);
+                        result.flatten()
+                    }
+                };
+                let prev = result
+                    .map(|result| {
+                        result
+                            .map(|next| {
+                                self.map
+                                    .get_mut(key)
+                                    .map(|component| component.replace_component(next))
+                            })
+                            .inspect_err(|_| {
+                                if let Some(component) = self.map.get_mut(key) {
+                                    component.record_failure();
+                                }
+                            })
                     })
-                })
-                .transpose()
-                .map(Option::flatten);
+                    .transpose()
+                    .map(Option::flatten);
+
+                Keyed::new(key, prev.transpose())
+            })
+            .collect::<Vec<_>>();
+
+        for Keyed { key, value } in &prev_components {
+            if let (Some((key, entry)), Some(result)) = (self.map.get_key_value(*key), value) {
+                self.events
+                    .emit_result(key, result, ChangeKind::Reinitialized, entry);
+            }
+        }
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .filter_map(|Keyed { key, value }| {
+                    let (key, _) = self.map.get_key_value(*key)?;
+                    value
+                        .as_mut()
+                        .and_then(|result| result.as_mut().ok())
+                        .map(|prev| (key, prev))
+                }),
+        )
+        .await;
 
-            Keyed::new(key, prev.transpose())
-        })
+        prev_components
+            .into_iter()
+            .zip(throttled)
+            .zip(timed_out)
+            .map(|((Keyed { key, value }, throttled), timed_out)| {
+                let outcome = match (throttled, timed_out) {
+                    (true, _) => ReinitOutcome::Throttled,
+                    (_, true) => ReinitOutcome::TimedOut,
+                    _ => ReinitOutcome::from_reinit(value),
+                };
+                Keyed::new(key, outcome)
+            })
     }
 
     pub async fn try_update_async<Error>(
         &mut self,
         updates: impl IntoIterator<Item = (Key, Args)>,
-    ) -> impl Iterator<Item = Keyed<Key, Option<Result<WithArgs<Args, Comp>, Error>>>>
+    ) -> impl Iterator<Item = Keyed<Key, ReinitOutcome<WithArgs<Args, Comp>, Error>>>
     where
         Key: Clone + Eq + std::hash::Hash,
         FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
     {
+        self.try_update_async_with(updates, BatchOptions::default())
+            .await
+    }
+
+    /// Like [`try_update_async`](Self::try_update_async), with per-call [`BatchOptions`].
+    pub async fn try_update_async_with<Error>(
+        &mut self,
+        updates: impl IntoIterator<Item = (Key, Args)>,
+        options: BatchOptions<Key>,
+    ) -> impl Iterator<Item = Keyed<Key, ReinitOutcome<WithArgs<Args, Comp>, Error>>>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let options = options.or_config(&self.config);
+        let mut updates: Vec<_> = updates.into_iter().collect();
+        if let Some(dedup) = options.dedup {
+            updates = dedup.apply(updates);
+        }
+        let progress = options.progress_for(updates.len());
+
         let updated_components_fut = updates.into_iter().map(|(key, args)| {
             let init = self.init.clone();
+            let instrument = &self.events.instrument;
+            let progress = &progress;
+            let options = &options;
             async move {
-                let result = (init)(&key, &args)
-                    .await
-                    .map(|component| WithArgs { component, args });
+                let span = instrument.start(&key, &args);
+                let result = options.run(|| (init)(&key, &args)).await;
+                span.finish(&key);
+                progress.report(&key);
 
-                (key, result)
+                (key, args, result)
             }
         });
 
-        join_all(updated_components_fut)
+        let mut prev_components = join_bounded(updated_components_fut, options.concurrency_limit)
             .await
             .into_iter()
-            .map(|(key, result)| {
-                let result = result.map(|component| self.map.insert(key.clone(), component));
+            .map(|(key, args, result)| {
+                let Some(result) = result else {
+                    if let Some(component) = self.map.get_mut(&key) {
+                        component.record_failure();
+                    }
+                    return Keyed::new(key, None);
+                };
+                let result = result
+                    .inspect_err(|error| self.events.emit_failed(&key, &args, error))
+                    .map(|component| self.map.insert(key.clone(), WithArgs::new(component, args)));
+                if let Ok(prev) = &result {
+                    self.events
+                        .emit(&key, ChangeKind::upsert(prev.is_some()), self.map.get(&key));
+                }
 
-                Keyed::new(key, result.transpose())
+                Keyed::new(key, Some(result))
+            })
+            .collect::<Vec<_>>();
+        self.enforce_capacity_async().await;
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .filter_map(|Keyed { key, value }| {
+                    value
+                        .as_mut()
+                        .and_then(|result| result.as_mut().ok())
+                        .and_then(Option::as_mut)
+                        .map(|prev| (&*key, &mut prev.component))
+                }),
+        )
+        .await;
+
+        prev_components
+            .into_iter()
+            .map(|Keyed { key, value }| match value {
+                Some(result) => Keyed::new(key, ReinitOutcome::from_update(result)),
+                None => Keyed::new(key, ReinitOutcome::TimedOut),
             })
     }
+
+    pub async fn get_or_try_init_async<Error>(
+        &mut self,
+        key: Key,
+        args: Args,
+    ) -> Result<&mut Comp, Error>
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        self.reserve_slot_async(&key).await;
+        if self.map.contains_key(&key) {
+            let entry = self.map.get_mut(&key).expect("checked above");
+            entry.touch();
+            return Ok(&mut entry.component);
+        }
+        let span = self.events.instrument.start(&key, &args);
+        let result = (self.init)(&key, &args).await;
+        span.finish(&key);
+        let component = result.inspect_err(|error| self.events.emit_failed(&key, &args, error))?;
+        let (_, entry) = self.insert_entry(key, WithArgs::new(component, args));
+        Ok(&mut entry.component)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_get_then_check)]
 mod tests {
     use super::*;
     use std::sync::{Arc, Mutex};
@@ -218,7 +525,10 @@ mod tests {
         .await;
 
         assert!(result.is_err());
-        assert_eq!(result.err().unwrap(), TestError("Failed".to_string()));
+        assert_eq!(
+            result.err().unwrap(),
+            KeyedError::new("key2", TestError("Failed".to_string()))
+        );
     }
 
     #[tokio::test]
@@ -235,7 +545,7 @@ mod tests {
             }
         };
 
-        let result: Result<ComponentMap<&str, FailArgs, Counter, _>, TestError> =
+        let result: Result<ComponentMap<&str, FailArgs, Counter, _>, KeyedError<&str, TestError>> =
             ComponentMap::try_init_async([], init).await;
 
         assert!(result.is_ok());
@@ -399,7 +709,7 @@ mod tests {
         let results: Vec<_> = manager.try_reinit_async(["key1"]).await.collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.as_ref().unwrap().is_ok());
+        assert!(matches!(results[0].value, ReinitOutcome::Replaced(_)));
         assert_eq!(manager.map.get("key1").unwrap().component, Counter(3));
         assert_eq!(manager.map.get("key2").unwrap().component, Counter(6));
     }
@@ -435,7 +745,43 @@ mod tests {
 
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].key, "nonexistent");
-        assert!(results[0].value.is_none());
+        assert!(matches!(results[0].value, ReinitOutcome::Missing));
+    }
+
+    #[tokio::test]
+    async fn test_try_reinit_async_borrowed_keys() {
+        let init = |key: &String, args: &FailArgs| {
+            let value = key.len() + args.value;
+            let should_fail = args.should_fail;
+            async move {
+                if should_fail {
+                    Err(TestError("Failed".to_string()))
+                } else {
+                    Ok(Counter(value))
+                }
+            }
+        };
+
+        let mut manager = ComponentMap::try_init_async(
+            [(
+                "key1".to_string(),
+                FailArgs {
+                    value: 1,
+                    should_fail: false,
+                },
+            )],
+            init,
+        )
+        .await
+        .unwrap();
+
+        manager.map.get_mut("key1").unwrap().args.value = 10;
+
+        let results: Vec<_> = manager.try_reinit_async(["key1"]).await.collect();
+
+        assert_eq!(results[0].key, "key1");
+        assert_eq!(results[0].value, ReinitOutcome::Replaced(Counter(5)));
+        assert_eq!(manager.map.get("key1").unwrap().component, Counter(14));
     }
 
     #[tokio::test]
@@ -477,7 +823,7 @@ mod tests {
             .collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.is_none());
+        assert!(matches!(results[0].value, ReinitOutcome::Inserted));
         assert_eq!(manager.map.len(), 2);
         assert_eq!(manager.map.get("key2").unwrap().component, Counter(20));
     }
@@ -521,8 +867,8 @@ mod tests {
             .collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.is_some());
-        assert!(results[0].value.as_ref().unwrap().is_err());
+        assert!(results[0].value.is_failed());
+        assert!(matches!(results[0].value, ReinitOutcome::Failed(_)));
 
         // Should not insert on error
         assert_eq!(manager.map.len(), 1);
@@ -591,4 +937,178 @@ mod tests {
         assert!(manager.map.get("key3").is_none());
         assert!(manager.map.get("key4").is_some());
     }
+
+    #[tokio::test]
+    async fn test_get_or_try_init_async() {
+        let init = |_key: &&str, args: &FailArgs| {
+            let value = args.value;
+            let should_fail = args.should_fail;
+            async move {
+                if should_fail {
+                    Err(TestError("Failed".to_string()))
+                } else {
+                    Ok(Counter(value))
+                }
+            }
+        };
+
+        let mut manager: ComponentMap<&str, FailArgs, Counter, _> =
+            ComponentMap::try_init_async([], init).await.unwrap();
+
+        let failed = manager
+            .get_or_try_init_async(
+                "key1",
+                FailArgs {
+                    value: 1,
+                    should_fail: true,
+                },
+            )
+            .await;
+        assert_eq!(failed, Err(TestError("Failed".to_string())));
+        assert!(manager.map.is_empty());
+
+        let inserted = manager
+            .get_or_try_init_async(
+                "key1",
+                FailArgs {
+                    value: 1,
+                    should_fail: false,
+                },
+            )
+            .await;
+        assert_eq!(inserted, Ok(&mut Counter(1)));
+        assert_eq!(manager.map.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_try_init_async_fail_fast() {
+        let init = |_key: &&str, args: &FailArgs| {
+            let value = args.value;
+            let should_fail = args.should_fail;
+            async move {
+                if should_fail {
+                    Err(TestError("Failed".to_string()))
+                } else {
+                    // Never completes; only returns because the batch is aborted
+                    futures::future::pending::<()>().await;
+                    Ok(Counter(value))
+                }
+            }
+        };
+
+        let result = ComponentMap::try_init_async_fail_fast(
+            [
+                (
+                    "key1",
+                    FailArgs {
+                        value: 1,
+                        should_fail: false,
+                    },
+                ),
+                (
+                    "key2",
+                    FailArgs {
+                        value: 2,
+                        should_fail: true,
+                    },
+                ),
+            ],
+            init,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap(),
+            KeyedError::new("key2", TestError("Failed".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_try_init_async_fail_fast_with_concurrency_limit() {
+        let running = Arc::new(Mutex::new((0, 0)));
+        let running_clone = running.clone();
+
+        let init = move |_key: &usize, args: &usize| {
+            let running = running_clone.clone();
+            let value = *args;
+            async move {
+                {
+                    let mut running = running.lock().unwrap();
+                    running.0 += 1;
+                    running.1 = running.1.max(running.0);
+                }
+                tokio::task::yield_now().await;
+                running.lock().unwrap().0 -= 1;
+                match value {
+                    7 => Err(TestError("Failed".to_string())),
+                    _ => Ok(Counter(value)),
+                }
+            }
+        };
+
+        let manager = ComponentMap::try_init_async_fail_fast_with(
+            (0..6).map(|key| (key, key)),
+            init.clone(),
+            BatchOptions::default().concurrency_limit(2),
+        )
+        .await
+        .unwrap();
+        assert_eq!(manager.len(), 6);
+        assert_eq!(manager.get(&5), Some(&Counter(5)));
+        assert_eq!(running.lock().unwrap().1, 2);
+
+        let result = ComponentMap::try_init_async_fail_fast_with(
+            (0..10).map(|key| (key, key)),
+            init,
+            BatchOptions::default().concurrency_limit(3),
+        )
+        .await;
+        assert_eq!(
+            result.err().unwrap(),
+            KeyedError::new(7, TestError("Failed".to_string()))
+        );
+        assert_eq!(running.lock().unwrap().1, 3);
+    }
+
+    #[tokio::test]
+    async fn test_try_init_async_receives_key() {
+        let init = |key: &&str, args: &FailArgs| {
+            let endpoint = format!("{key}:{}", args.value);
+            let should_fail = args.should_fail;
+            async move {
+                if should_fail {
+                    Err(TestError(endpoint))
+                } else {
+                    Ok(endpoint)
+                }
+            }
+        };
+
+        let mut manager = ComponentMap::try_init_async(
+            [(
+                "key1",
+                FailArgs {
+                    value: 1,
+                    should_fail: false,
+                },
+            )],
+            init,
+        )
+        .await
+        .unwrap();
+        assert_eq!(manager.get(&"key1"), Some(&"key1:1".to_string()));
+
+        let results: Vec<_> = manager
+            .try_update_async([(
+                "key2",
+                FailArgs {
+                    value: 2,
+                    should_fail: true,
+                },
+            )])
+            .await
+            .collect();
+        let error = results.into_iter().next().unwrap().value.failed();
+        assert_eq!(error, Some(TestError("key2:2".to_string())));
+    }
 }
@@ -1,5 +1,12 @@
-use crate::{ComponentMap, Keyed, WithArgs};
-use futures::future::join_all;
+use crate::{
+    AsyncTeardown, BatchOptions, ChangeKind, ComponentMap, Keyed, KeyedStorage, Lookup, NoTeardown,
+    Teardown, WithArgs, batch::join_bounded, priority::prioritized, teardown::teardown_all,
+};
+use futures::{
+    Stream, StreamExt,
+    stream::{self, FuturesUnordered},
+};
+use std::borrow::Borrow;
 
 impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
     pub async fn init_async(entries: impl IntoIterator<Item = (Key, Args)>, init: FnInit) -> Self
@@ -7,69 +14,239 @@ impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
         Key: Eq + std::hash::Hash,
         FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
     {
+        Self::init_async_with(entries, init, BatchOptions::default()).await
+    }
+
+    /// Like [`init_async`](Self::init_async), with per-call [`BatchOptions`].
+    pub async fn init_async_with(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        options: BatchOptions<Key>,
+    ) -> Self
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let progress = options.progress_for(entries.len());
+
         let components_fut = entries.into_iter().map(|(key, args)| {
             let init = init.clone();
+            let progress = &progress;
             async move {
                 let component = (init)(&key, &args).await;
-                (key, WithArgs { component, args })
+                progress.report(&key);
+                (key, WithArgs::new(component, args))
             }
         });
 
-        let map = join_all(components_fut).await.into_iter().collect();
+        let map = join_bounded(components_fut, options.concurrency_limit)
+            .await
+            .into_iter()
+            .collect();
 
-        Self { map: map, init }
+        Self::new(map, init, NoTeardown)
     }
+}
 
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
     pub async fn reinit_all_async(&mut self) -> impl Iterator<Item = Keyed<&Key, Comp>>
     where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        self.reinit_all_async_with(BatchOptions::default()).await
+    }
+
+    /// Like [`reinit_all_async`](Self::reinit_all_async), with per-call [`BatchOptions`].
+    pub async fn reinit_all_async_with(
+        &mut self,
+        options: BatchOptions<Key>,
+    ) -> impl Iterator<Item = Keyed<&Key, Comp>>
+    where
+        Key: Eq + std::hash::Hash,
         FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
     {
-        let next_components_fut = self
-            .map
-            .iter()
-            .map(|(key, component)| (self.init)(key, &component.args));
+        let options = options.or_config(&self.config);
+        let progress = options.progress_for(self.map.len());
+        let entries = prioritized(self.map.iter_mut(), &self.dependencies, &self.priorities);
+
+        let next_components_fut = entries.iter().map(|(key, component)| async {
+            let span = self.events.instrument.start(key, &component.args);
+            let next = (self.init)(key, &component.args).await;
+            span.finish(key);
+            progress.report(key);
+            next
+        });
 
-        let next_components = join_all(next_components_fut).await;
+        let next_components = join_bounded(next_components_fut, options.concurrency_limit).await;
 
-        self.map
-            .iter_mut()
+        let mut prev_components = entries
+            .into_iter()
             .zip(next_components)
-            .map(|((key, prev), next)| {
-                let prev = std::mem::replace(&mut prev.component, next);
+            .map(|((key, component), next)| {
+                let prev = component.replace_component(next);
+                self.events
+                    .emit(key, ChangeKind::Reinitialized, Some(&*component));
                 Keyed::new(key, prev)
             })
+            .collect::<Vec<_>>();
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .map(|Keyed { key, value }| (&**key, value)),
+        )
+        .await;
+
+        prev_components.into_iter()
     }
 
-    pub async fn reinit_async(
+    /// Reinitialises every component, yielding each previous component as soon
+    /// as its replacement is ready instead of waiting for the whole batch.
+    ///
+    /// Entries of a lower [priority](Self::set_priority) only start once every
+    /// higher one has been yielded. Args are cloned up front so components can
+    /// be swapped while the remaining inits are still running.
+    pub fn reinit_all_stream(&mut self) -> impl Stream<Item = Keyed<Key, Comp>> + '_
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        Args: Clone,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let mut tiers = Vec::<(i32, FuturesUnordered<_>)>::new();
+        for (key, component) in prioritized(self.map.iter(), &self.dependencies, &self.priorities) {
+            let priority = self.priorities.priority(key, component);
+            let init = self.init.clone();
+            let instrument = self.events.instrument.clone();
+            let key = key.clone();
+            let args = component.args.clone();
+            let pending = async move {
+                let span = instrument.start(&key, &args);
+                let next = (init)(&key, &args).await;
+                span.finish(&key);
+                (key, next)
+            };
+            match tiers.last_mut() {
+                Some((last, tier)) if *last == priority => tier.push(pending),
+                _ => tiers.push((priority, FuturesUnordered::from_iter([pending]))),
+            }
+        }
+        let pending = stream::iter(tiers.into_iter().map(|(_, tier)| tier)).flatten();
+
+        stream::unfold((self, pending), |(this, mut pending)| async move {
+            let (key, next) = pending.next().await?;
+            let component = this
+                .map
+                .get_mut(&key)
+                .expect("entries cannot be removed while the stream borrows the map");
+            let mut prev = component.replace_component(next);
+            this.events
+                .emit(&key, ChangeKind::Reinitialized, Some(&*component));
+            this.teardown.teardown_async(&key, &mut prev).await;
+
+            Some((Keyed::new(key, prev), (this, pending)))
+        })
+    }
+
+    /// Like [`reinit`](Self::reinit) for async inits, which likewise always
+    /// runs; use [`try_reinit_async`](Self::try_reinit_async) to have keys
+    /// reinitialised too soon reported as throttled instead.
+    pub async fn reinit_async<'q, Q>(
         &mut self,
-        keys: impl IntoIterator<Item = Key>,
-    ) -> impl Iterator<Item = Keyed<Key, Option<Comp>>>
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, Option<Comp>>>
     where
-        Key: Eq + std::hash::Hash + Clone,
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
         FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
     {
-        let next_components_fut = keys.into_iter().map(|key| {
+        self.reinit_async_with(keys, BatchOptions::default()).await
+    }
+
+    /// Like [`reinit_async`](Self::reinit_async), with per-call [`BatchOptions`].
+    pub async fn reinit_async_with<'q, Q>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'q Q>,
+        options: BatchOptions<Key>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, Option<Comp>>>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let options = options.or_config(&self.config);
+        let entries: Vec<_> = keys
+            .into_iter()
+            .map(|key| (key, self.map.get_key_value(key)))
+            .collect();
+        let progress =
+            options.progress_for(entries.iter().filter(|(_, entry)| entry.is_some()).count());
+
+        let next_components_fut = entries.into_iter().map(|(key, entry)| {
             let init = self.init.clone();
-            let args = self.map.get(&key).map(|component| &component.args);
+            let instrument = &self.events.instrument;
+            let progress = &progress;
             async move {
-                let next = match args {
-                    Some(args) => Some((init)(&key, args).await),
+                let next = match entry {
+                    Some((key, component)) => {
+                        let span = instrument.start(key, &component.args);
+                        let next = (init)(key, &component.args).await;
+                        span.finish(key);
+                        progress.report(key);
+                        Some(next)
+                    }
                     None => None,
                 };
                 Keyed::new(key, next)
             }
         });
 
-        let results = join_all(next_components_fut).await;
+        let results = join_bounded(next_components_fut, options.concurrency_limit).await;
 
-        results.into_iter().map(|Keyed { key, value: next }| {
-            let prev = next.and_then(|next| {
-                self.map
-                    .get_mut(&key)
-                    .map(|component| std::mem::replace(&mut component.component, next))
-            });
-            Keyed::new(key, prev)
-        })
+        let mut prev_components = results
+            .into_iter()
+            .map(|Keyed { key, value: next }| {
+                let prev = next.and_then(|next| {
+                    self.map
+                        .get_mut(key)
+                        .map(|component| component.replace_component(next))
+                });
+                Keyed::new(key, prev)
+            })
+            .collect::<Vec<_>>();
+
+        for Keyed { key, value } in &prev_components {
+            if let (Some((key, entry)), Some(_)) = (self.map.get_key_value(*key), value) {
+                self.events
+                    .emit(key, ChangeKind::Reinitialized, Some(entry));
+            }
+        }
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .filter_map(|Keyed { key, value }| {
+                    let (key, _) = self.map.get_key_value(*key)?;
+                    value.as_mut().map(|prev| (key, prev))
+                }),
+        )
+        .await;
+
+        prev_components.into_iter()
     }
 
     pub async fn update_async(
@@ -79,22 +256,85 @@ impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
     where
         Key: Clone + Eq + std::hash::Hash,
         FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
     {
+        self.update_async_with(updates, BatchOptions::default())
+            .await
+    }
+
+    /// Like [`update_async`](Self::update_async), with per-call [`BatchOptions`].
+    pub async fn update_async_with(
+        &mut self,
+        updates: impl IntoIterator<Item = (Key, Args)>,
+        options: BatchOptions<Key>,
+    ) -> impl Iterator<Item = Keyed<Key, Option<WithArgs<Args, Comp>>>>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let options = options.or_config(&self.config);
+        let mut updates: Vec<_> = updates.into_iter().collect();
+        if let Some(dedup) = options.dedup {
+            updates = dedup.apply(updates);
+        }
+        let progress = options.progress_for(updates.len());
+
         let updated_components_fut = updates.into_iter().map(|(key, args)| {
             let init = self.init.clone();
+            let instrument = &self.events.instrument;
+            let progress = &progress;
             async move {
+                let span = instrument.start(&key, &args);
                 let component = (init)(&key, &args).await;
-                (key, WithArgs { component, args })
+                span.finish(&key);
+                progress.report(&key);
+                (key, WithArgs::new(component, args))
             }
         });
 
-        join_all(updated_components_fut)
+        let mut prev_components = join_bounded(updated_components_fut, options.concurrency_limit)
             .await
             .into_iter()
             .map(|(key, component)| {
                 let prev = self.map.insert(key.clone(), component);
+                self.events
+                    .emit(&key, ChangeKind::upsert(prev.is_some()), self.map.get(&key));
                 Keyed::new(key, prev)
             })
+            .collect::<Vec<_>>();
+        self.enforce_capacity_async().await;
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .filter_map(|Keyed { key, value }| {
+                    value.as_mut().map(|prev| (&*key, &mut prev.component))
+                }),
+        )
+        .await;
+
+        prev_components.into_iter()
+    }
+
+    pub async fn get_or_init_async(&mut self, key: Key, args: Args) -> &mut Comp
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        self.reserve_slot_async(&key).await;
+        if self.map.contains_key(&key) {
+            let entry = self.map.get_mut(&key).expect("checked above");
+            entry.touch();
+            return &mut entry.component;
+        }
+        let span = self.events.instrument.start(&key, &args);
+        let component = (self.init)(&key, &args).await;
+        span.finish(&key);
+        let (_, entry) = self.insert_entry(key, WithArgs::new(component, args));
+        &mut entry.component
     }
 }
 
@@ -318,4 +558,54 @@ mod tests {
         assert_eq!(manager.map.get("key2").unwrap().component, Counter(20));
         assert_eq!(manager.map.get("key3").unwrap().component, Counter(30));
     }
+
+    #[tokio::test]
+    async fn test_get_or_init_async() {
+        let init = |_key: &&str, args: &Args| {
+            let value = args.value;
+            async move { Counter(value) }
+        };
+
+        let mut manager = ComponentMap::init_async([("key1", Args { value: 1 })], init).await;
+
+        let existing = manager.get_or_init_async("key1", Args { value: 100 }).await;
+        assert_eq!(*existing, Counter(1));
+
+        let inserted = manager.get_or_init_async("key2", Args { value: 2 }).await;
+        assert_eq!(*inserted, Counter(2));
+
+        assert_eq!(manager.map.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reinit_all_stream() {
+        let init = |_key: &&str, args: &Args| {
+            let value = args.value;
+            async move {
+                // Larger values take longer to initialise
+                for _ in 0..value {
+                    tokio::task::yield_now().await;
+                }
+                Counter(value)
+            }
+        };
+
+        let mut manager = ComponentMap::init_async(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .await;
+        manager.set_args(&"key1", Args { value: 5 });
+
+        let results: Vec<_> = manager
+            .reinit_all_stream()
+            .map(|Keyed { key, value }| (key, value))
+            .collect()
+            .await;
+
+        // key2 finishes first despite the iteration order of the map
+        assert_eq!(results, vec![("key2", Counter(2)), ("key1", Counter(1))]);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(5)));
+        assert_eq!(manager.is_dirty(&"key1"), Some(false));
+    }
 }
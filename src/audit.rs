@@ -0,0 +1,204 @@
+use crate::{ChangeKind, ComponentMap, KeyedStorage, Teardown, WithArgs};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::SystemTime,
+};
+
+/// One mutation recorded by the audit log enabled with
+/// [`with_audit_log`](ComponentMap::with_audit_log).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord<Key> {
+    pub timestamp: SystemTime,
+    pub key: Key,
+    /// What happened to the key; a failed init or reinit is recorded as
+    /// [`ChangeKind::Failed`] and left the entry untouched.
+    pub change: ChangeKind,
+}
+
+impl<Key> AuditRecord<Key> {
+    pub fn is_failure(&self) -> bool {
+        self.change == ChangeKind::Failed
+    }
+}
+
+/// Shared with the observer feeding it from the map's change events.
+pub(crate) type AuditLog<Key> = Arc<Mutex<AuditRecords<Key>>>;
+
+/// Ring buffer of the `capacity` most recent records, growing as they are
+/// pushed rather than allocating `capacity` up front.
+#[derive(Debug)]
+pub(crate) struct AuditRecords<Key> {
+    records: VecDeque<AuditRecord<Key>>,
+    capacity: usize,
+}
+
+impl<Key> AuditRecords<Key> {
+    fn push(&mut self, record: AuditRecord<Key>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Changes the capacity, dropping the oldest records that no longer fit
+    /// and the memory they used.
+    fn resize(&mut self, capacity: usize) {
+        let excess = self.records.len().saturating_sub(capacity);
+        self.records.drain(..excess);
+        self.records.shrink_to(capacity);
+        self.capacity = capacity;
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Starts recording every mutation in an in-memory log holding the
+    /// `capacity` most recent records; older ones are discarded.
+    ///
+    /// Calling it again keeps the existing log and its records, resized to
+    /// the new `capacity`.
+    pub fn with_audit_log(mut self, capacity: usize) -> Self
+    where
+        Key: Clone + Send + 'static,
+    {
+        if let Some(log) = &self.events.audit {
+            lock(log).resize(capacity);
+            return self;
+        }
+
+        let log: AuditLog<Key> = Arc::new(Mutex::new(AuditRecords {
+            records: VecDeque::new(),
+            capacity,
+        }));
+        self.events.audit = Some(log.clone());
+        self.events.push(Box::new(move |key: &Key, change, _, _| {
+            lock(&log).push(AuditRecord {
+                timestamp: SystemTime::now(),
+                key: key.clone(),
+                change,
+            });
+            true
+        }));
+        self
+    }
+
+    /// The recorded mutations, oldest first; empty if the log is not enabled.
+    pub fn audit_log(&self) -> Vec<AuditRecord<Key>>
+    where
+        Key: Clone,
+    {
+        match &self.events.audit {
+            Some(log) => lock(log).records.iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Removes and returns the recorded mutations, oldest first, e.g. to ship
+    /// them elsewhere; recording continues afterwards.
+    pub fn drain_audit_log(&mut self) -> Vec<AuditRecord<Key>> {
+        match &self.events.audit {
+            Some(log) => lock(log).records.drain(..).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn lock<Key>(log: &AuditLog<Key>) -> MutexGuard<'_, AuditRecords<Key>> {
+    log.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[test]
+    fn test_audit_log_records_mutations() {
+        let init = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError),
+            args => Ok(Counter(args)),
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1)], init)
+            .unwrap()
+            .with_audit_log(8);
+        let started = SystemTime::now();
+
+        manager
+            .try_update([("key2", 2), ("key3", 0)])
+            .for_each(drop);
+        manager.remove(&"key1");
+
+        let log = manager.audit_log();
+        let changes: Vec<_> = log
+            .iter()
+            .map(|record| (record.key, record.change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("key2", ChangeKind::Inserted),
+                ("key3", ChangeKind::Failed),
+                ("key1", ChangeKind::Removed),
+            ]
+        );
+        assert!(log[1].is_failure());
+        assert!(log.iter().all(|record| record.timestamp >= started));
+
+        assert_eq!(manager.drain_audit_log().len(), 3);
+        assert!(manager.audit_log().is_empty());
+    }
+
+    #[test]
+    fn test_audit_log_keeps_most_recent() {
+        let init = |_key: &usize, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([], init).with_audit_log(2);
+
+        manager.update((0..5).map(|key| (key, key))).for_each(drop);
+
+        let keys: Vec<_> = manager
+            .audit_log()
+            .iter()
+            .map(|record| record.key)
+            .collect();
+        assert_eq!(keys, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_audit_log_allocates_as_records_arrive() {
+        let init = |_key: &usize, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([], init).with_audit_log(usize::MAX);
+
+        manager.update([(0, 0)]).for_each(drop);
+
+        assert_eq!(manager.audit_log().len(), 1);
+    }
+
+    #[test]
+    fn test_audit_log_enabled_twice_keeps_one_log() {
+        let init = |_key: &usize, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([], init).with_audit_log(4);
+        manager.update((0..3).map(|key| (key, key))).for_each(drop);
+
+        let mut manager = manager.with_audit_log(2);
+        manager.update([(3, 3)]).for_each(drop);
+
+        let keys: Vec<_> = manager
+            .audit_log()
+            .iter()
+            .map(|record| record.key)
+            .collect();
+        assert_eq!(keys, vec![2, 3]);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::{RetryPolicy, retry::retry_async, timeout::deadline};
+use futures::{
+    StreamExt, TryStreamExt,
+    future::{join_all, try_join_all},
+    stream,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+/// Callback invoked as `(done, total, key)` whenever a key of a batch completes.
+pub type ProgressFn<Key> = Arc<dyn Fn(usize, usize, &Key) + Send + Sync>;
+
+/// Which entry of a batch update is kept when a key appears more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupPolicy {
+    FirstWins,
+    LastWins,
+}
+
+impl DedupPolicy {
+    /// Drops the duplicate keys of `updates` before anything is initialised,
+    /// keeping the survivors in their original order.
+    pub(crate) fn apply<Key, Args>(self, updates: Vec<(Key, Args)>) -> Vec<(Key, Args)>
+    where
+        Key: Eq + std::hash::Hash,
+    {
+        let mut chosen = HashMap::with_capacity(updates.len());
+        for (index, (key, _)) in updates.iter().enumerate() {
+            match self {
+                DedupPolicy::FirstWins => {
+                    chosen.entry(key).or_insert(index);
+                }
+                DedupPolicy::LastWins => {
+                    chosen.insert(key, index);
+                }
+            }
+        }
+
+        let keep: Vec<_> = updates
+            .iter()
+            .enumerate()
+            .map(|(index, (key, _))| chosen[key] == index)
+            .collect();
+        updates
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(update, keep)| keep.then_some(update))
+            .collect()
+    }
+}
+
+/// Per-call options for the async batch operations (`*_async_with`).
+///
+/// Options left unset fall back to the map's [`ComponentMapConfig`](crate::ComponentMapConfig).
+pub struct BatchOptions<Key> {
+    /// Maximum number of inits running at once; unbounded when `None`.
+    pub concurrency_limit: Option<usize>,
+    /// Retry policy applied per key by the fallible operations.
+    pub retry: Option<RetryPolicy>,
+    /// Bound on each key's init, retries included, in the operations that
+    /// report [`TimedOut`](crate::ReinitOutcome::TimedOut).
+    pub timeout: Option<Duration>,
+    /// Called as each key completes, whether or not its init succeeded.
+    pub progress: Option<ProgressFn<Key>>,
+    /// How repeated keys in a batch update are collapsed; every occurrence is
+    /// initialised when `None`.
+    pub dedup: Option<DedupPolicy>,
+}
+
+impl<Key> BatchOptions<Key> {
+    pub fn concurrency_limit(mut self, limit: usize) -> Self {
+        self.concurrency_limit = Some(limit);
+        self
+    }
+
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn progress(
+        mut self,
+        progress: impl Fn(usize, usize, &Key) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn dedup(mut self, policy: DedupPolicy) -> Self {
+        self.dedup = Some(policy);
+        self
+    }
+
+    pub(crate) fn progress_for(&self, total: usize) -> Progress<'_, Key> {
+        Progress {
+            sink: self.progress.as_ref(),
+            done: AtomicUsize::new(0),
+            total,
+        }
+    }
+
+    /// Runs `init` under this call's retry policy, bounded by its timeout;
+    /// `None` when the timeout elapsed first.
+    pub(crate) async fn run<T, Error, Fut>(
+        &self,
+        init: impl FnMut() -> Fut,
+    ) -> Option<Result<T, Error>>
+    where
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let attempts = retry_async(self.retry, init);
+        match self.timeout {
+            Some(timeout) => deadline(attempts, timeout).await,
+            None => Some(attempts.await),
+        }
+    }
+}
+
+impl<Key> Default for BatchOptions<Key> {
+    fn default() -> Self {
+        Self {
+            concurrency_limit: None,
+            retry: None,
+            timeout: None,
+            progress: None,
+            dedup: None,
+        }
+    }
+}
+
+impl<Key> Clone for BatchOptions<Key> {
+    fn clone(&self) -> Self {
+        Self {
+            concurrency_limit: self.concurrency_limit,
+            retry: self.retry,
+            timeout: self.timeout,
+            progress: self.progress.clone(),
+            dedup: self.dedup,
+        }
+    }
+}
+
+impl<Key> fmt::Debug for BatchOptions<Key> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchOptions")
+            .field("concurrency_limit", &self.concurrency_limit)
+            .field("retry", &self.retry)
+            .field("timeout", &self.timeout)
+            .field("progress", &self.progress.is_some())
+            .field("dedup", &self.dedup)
+            .finish()
+    }
+}
+
+/// Completion counter for a single batch.
+pub(crate) struct Progress<'a, Key> {
+    sink: Option<&'a ProgressFn<Key>>,
+    done: AtomicUsize,
+    total: usize,
+}
+
+impl<Key> Progress<'_, Key> {
+    pub(crate) fn report(&self, key: &Key) {
+        if let Some(sink) = self.sink {
+            let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+            sink(done, self.total, key);
+        }
+    }
+}
+
+/// Awaits `futures` with at most `limit` in flight, returning outputs in input order.
+pub(crate) async fn join_bounded<F>(
+    futures: impl IntoIterator<Item = F>,
+    limit: Option<usize>,
+) -> Vec<F::Output>
+where
+    F: Future,
+{
+    let Some(limit) = limit else {
+        return join_all(futures).await;
+    };
+
+    let mut outputs: Vec<_> = stream::iter(futures.into_iter().enumerate())
+        .map(|(index, future)| async move { (index, future.await) })
+        .buffer_unordered(limit.max(1))
+        .collect()
+        .await;
+
+    outputs.sort_unstable_by_key(|(index, _)| *index);
+    outputs.into_iter().map(|(_, output)| output).collect()
+}
+
+/// Like [`join_bounded`], but returns the first error as soon as it occurs,
+/// dropping the futures still in flight.
+pub(crate) async fn try_join_bounded<F, T, Error>(
+    futures: impl IntoIterator<Item = F>,
+    limit: Option<usize>,
+) -> Result<Vec<T>, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    let Some(limit) = limit else {
+        return try_join_all(futures).await;
+    };
+
+    let mut outputs: Vec<_> = stream::iter(futures.into_iter().enumerate())
+        .map(|(index, future)| async move { future.await.map(|output| (index, output)) })
+        .buffer_unordered(limit.max(1))
+        .try_collect()
+        .await?;
+
+    outputs.sort_unstable_by_key(|(index, _)| *index);
+    Ok(outputs.into_iter().map(|(_, output)| output).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ComponentMap, Keyed};
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        let running = Arc::new(Mutex::new((0, 0)));
+        let running_clone = running.clone();
+
+        let init = move |_key: &usize, args: &usize| {
+            let running = running_clone.clone();
+            let value = *args;
+            async move {
+                {
+                    let mut running = running.lock().unwrap();
+                    running.0 += 1;
+                    running.1 = running.1.max(running.0);
+                }
+                tokio::task::yield_now().await;
+                running.lock().unwrap().0 -= 1;
+                Counter(value)
+            }
+        };
+
+        let mut manager = ComponentMap::init_async_with(
+            (0..10).map(|key| (key, key)),
+            init,
+            BatchOptions::default().concurrency_limit(3),
+        )
+        .await;
+
+        assert_eq!(manager.len(), 10);
+        assert_eq!(manager.get(&7), Some(&Counter(7)));
+        assert_eq!(running.lock().unwrap().1, 3);
+
+        *running.lock().unwrap() = (0, 0);
+        let results: Vec<_> = manager
+            .reinit_all_async_with(BatchOptions::default().concurrency_limit(2))
+            .await
+            .collect();
+
+        assert_eq!(results.len(), 10);
+        assert_eq!(running.lock().unwrap().1, 2);
+    }
+
+    #[tokio::test]
+    async fn test_join_bounded_preserves_order() {
+        let futures = (0..5).map(|value| async move {
+            for _ in 0..(5 - value) {
+                tokio::task::yield_now().await;
+            }
+            value
+        });
+
+        assert_eq!(join_bounded(futures, Some(2)).await, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_progress() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let reported_clone = reported.clone();
+        let options = BatchOptions::default().progress(move |done, total, key: &usize| {
+            reported_clone.lock().unwrap().push((done, total, *key));
+        });
+
+        let init = |_key: &usize, args: &usize| {
+            let value = *args;
+            async move { Counter(value) }
+        };
+        let mut manager =
+            ComponentMap::init_async_with((0..4).map(|key| (key, key)), init, options.clone())
+                .await;
+
+        let mut reported_init = std::mem::take(&mut *reported.lock().unwrap());
+        let done: Vec<_> = reported_init.iter().map(|(done, _, _)| *done).collect();
+        assert_eq!(done, vec![1, 2, 3, 4]);
+        assert!(reported_init.iter().all(|(_, total, _)| *total == 4));
+        reported_init.sort_by_key(|(_, _, key)| *key);
+        let keys: Vec<_> = reported_init.iter().map(|(_, _, key)| *key).collect();
+        assert_eq!(keys, vec![0, 1, 2, 3]);
+
+        let _ = manager.reinit_async_with([&1, &2, &9], options).await;
+
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 2);
+        assert!(reported.iter().all(|(_, total, _)| *total == 2));
+    }
+
+    #[tokio::test]
+    async fn test_dedup_policies() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&str, args: &usize| {
+            let calls = calls_clone.clone();
+            let value = *args;
+            async move {
+                calls.lock().unwrap().push(value);
+                Ok::<_, ()>(Counter(value))
+            }
+        };
+        let mut manager = ComponentMap::try_init_async([("key1", 0)], init)
+            .await
+            .unwrap();
+        calls.lock().unwrap().clear();
+
+        let updates = [("key1", 1), ("key2", 2), ("key1", 3)];
+        let results: Vec<_> = manager
+            .try_update_async_with(
+                updates,
+                BatchOptions::default().dedup(DedupPolicy::LastWins),
+            )
+            .await
+            .map(|Keyed { key, .. }| key)
+            .collect();
+
+        assert_eq!(results, vec!["key2", "key1"]);
+        assert_eq!(*calls.lock().unwrap(), vec![2, 3]);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(3)));
+
+        manager
+            .try_update_async_with(
+                updates,
+                BatchOptions::default().dedup(DedupPolicy::FirstWins),
+            )
+            .await
+            .for_each(drop);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+    }
+}
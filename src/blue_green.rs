@@ -0,0 +1,192 @@
+use crate::{
+    AsyncTeardown, ReinitOutcome, SharedComponentMap, Teardown, retry::retry_async, shared::Retire,
+    timeout::deadline,
+};
+use std::{borrow::Borrow, convert::Infallible, time::Duration};
+use tokio::{runtime::Handle, task};
+
+impl<Key, Args, Comp, FnInit, FnDrop> SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Clone + Eq + std::hash::Hash + Send + 'static,
+    Args: Clone,
+    Comp: Send + 'static,
+    FnDrop: Teardown<Key, Comp> + AsyncTeardown<Key, Comp> + Clone + Send + 'static,
+{
+    /// Like [`reinit_async`](Self::reinit_async), but the previous component
+    /// is moved to the background and finalised there with the async teardown,
+    /// bounded by
+    /// [`ComponentMapConfig::shutdown_timeout`](crate::ComponentMapConfig::shutdown_timeout),
+    /// so a slow teardown never delays the swap or the callers waiting on it.
+    /// Outside a tokio runtime the sync teardown runs inline instead.
+    ///
+    /// Returns `false` if the key is missing or throttled.
+    pub async fn reinit_blue_green_async<Q>(&self, key: &Q) -> bool
+    where
+        Key: Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+    {
+        let Some(key) = self
+            .read()
+            .map
+            .get_key_value(key)
+            .map(|(key, _)| key.clone())
+        else {
+            return false;
+        };
+        let timeout = self.read().config.shutdown_timeout;
+        self.reinit_with(
+            key,
+            async |init, key, args| Ok::<_, Infallible>((init)(&key, &args).await),
+            Detached { timeout },
+        )
+        .await
+        .replaced()
+        .is_some()
+    }
+
+    /// Like [`try_reinit_async`](Self::try_reinit_async), finalising the
+    /// previous component in the background as
+    /// [`reinit_blue_green_async`](Self::reinit_blue_green_async) does.
+    pub async fn try_reinit_blue_green_async<Q, Error>(&self, key: &Q) -> ReinitOutcome<(), Error>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+    {
+        let Some(key) = self
+            .read()
+            .map
+            .get_key_value(key)
+            .map(|(key, _)| key.clone())
+        else {
+            return ReinitOutcome::Missing;
+        };
+        let (retry, timeout) = {
+            let map = self.read();
+            (map.config.retry, map.config.shutdown_timeout)
+        };
+        self.reinit_with(
+            key,
+            async move |init, key, args| retry_async(retry, || (init)(&key, &args)).await,
+            Detached { timeout },
+        )
+        .await
+    }
+}
+
+/// Moves each replaced component to a blocking-pool thread for teardown. The
+/// teardown future need not be `Send`, so it is driven there with `block_on`
+/// rather than spawned as a task. Nothing is left for queued callers, which
+/// run their own reinit instead.
+struct Detached {
+    timeout: Option<Duration>,
+}
+
+impl<Key, Comp, FnDrop> Retire<Key, Comp, FnDrop> for Detached
+where
+    Key: Clone + Send + 'static,
+    Comp: Send + 'static,
+    FnDrop: Teardown<Key, Comp> + AsyncTeardown<Key, Comp> + Clone + Send + 'static,
+{
+    type Prev = ();
+
+    fn retire(self, teardown: &FnDrop, key: &Key, mut prev: Comp) -> ((), Option<Comp>) {
+        let Ok(runtime) = Handle::try_current() else {
+            teardown.teardown(key, &mut prev);
+            return ((), None);
+        };
+        let (teardown, key, timeout) = (teardown.clone(), key.clone(), self.timeout);
+        drop(task::spawn_blocking(move || {
+            runtime.block_on(async {
+                let teardown = teardown.teardown_async(&key, &mut prev);
+                match timeout {
+                    Some(timeout) => drop(deadline(teardown, timeout).await),
+                    None => teardown.await,
+                }
+            })
+        }));
+        ((), None)
+    }
+
+    fn reuse(_prev: &Comp) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComponentMap, SharedComponentMap};
+    use futures_timer::Delay;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    /// Not `Clone`, so only the instance that was in the map can be torn
+    /// down. Records on drop whether it was torn down first.
+    #[derive(Debug)]
+    struct Conn {
+        id: usize,
+        closed: usize,
+        dropped: Arc<Mutex<Vec<(usize, usize)>>>,
+    }
+
+    impl Drop for Conn {
+        fn drop(&mut self) {
+            self.dropped.lock().unwrap().push((self.id, self.closed));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blue_green_tears_down_in_background() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let dropped_clone = dropped.clone();
+
+        let init = move |_key: &&str, args: &usize| {
+            let conn = Conn {
+                id: *args,
+                closed: 0,
+                dropped: dropped_clone.clone(),
+            };
+            async move { conn }
+        };
+        let manager = ComponentMap::init_async([("key1", 1)], init)
+            .await
+            .with_teardown(|_: &&str, conn: &mut Conn| {
+                std::thread::sleep(Duration::from_millis(20));
+                conn.closed += 1;
+            });
+        let shared = SharedComponentMap::from(manager);
+        shared.write().set_args(&"key1", 2);
+
+        assert!(shared.reinit_blue_green_async(&"key1").await);
+        assert_eq!(shared.read().get(&"key1").map(|conn| conn.id), Some(2));
+        assert!(dropped.lock().unwrap().is_empty());
+
+        while dropped.lock().unwrap().is_empty() {
+            Delay::new(Duration::from_millis(5)).await;
+        }
+        assert_eq!(*dropped.lock().unwrap(), vec![(1, 1)]);
+        assert!(!shared.reinit_blue_green_async(&"key2").await);
+    }
+
+    #[test]
+    fn test_blue_green_without_runtime_tears_down_inline() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let dropped_clone = dropped.clone();
+
+        let init = async move |_key: &&str, args: &usize| Conn {
+            id: *args,
+            closed: 0,
+            dropped: dropped_clone.clone(),
+        };
+        let manager = futures::executor::block_on(ComponentMap::init_async([("key1", 1)], init))
+            .with_teardown(|_: &&str, conn: &mut Conn| conn.closed += 1);
+        let shared = SharedComponentMap::from(manager);
+
+        assert!(futures::executor::block_on(
+            shared.reinit_blue_green_async(&"key1")
+        ));
+        assert_eq!(*dropped.lock().unwrap(), vec![(1, 1)]);
+    }
+}
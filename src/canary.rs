@@ -0,0 +1,112 @@
+use crate::{
+    AsyncTeardown, ComponentMap, Keyed, KeyedStorage, Lookup, ReinitOutcome, Teardown, WithArgs,
+};
+use std::borrow::Borrow;
+
+/// Outcome of [`try_reinit_canary`](ComponentMap::try_reinit_canary).
+#[derive(Debug)]
+pub struct CanaryReport<Key, Comp, Error> {
+    pub canaries: Vec<Keyed<Key, ReinitOutcome<Comp, Error>>>,
+    /// Outcomes for the remaining keys, or `None` if a canary failed or timed
+    /// out and they were left untouched.
+    pub rest: Option<Vec<Keyed<Key, ReinitOutcome<Comp, Error>>>>,
+}
+
+impl<Key, Comp, Error> CanaryReport<Key, Comp, Error> {
+    pub fn is_aborted(&self) -> bool {
+        self.rest.is_none()
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Reinitialises `canaries` first and only moves on to `rest` if none of
+    /// them failed, so a bad change is caught on a few keys before it reaches
+    /// the whole map.
+    pub fn try_reinit_canary<'q, Q, Error>(
+        &mut self,
+        canaries: impl IntoIterator<Item = &'q Q>,
+        rest: impl IntoIterator<Item = &'q Q>,
+    ) -> CanaryReport<&'q Q, Comp, Error>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: std::fmt::Debug,
+    {
+        let canaries: Vec<_> = self.try_reinit(canaries).collect();
+        let rest = passed(&canaries).then(|| self.try_reinit(rest).collect());
+        CanaryReport { canaries, rest }
+    }
+
+    /// Async counterpart of [`try_reinit_canary`](Self::try_reinit_canary).
+    pub async fn try_reinit_canary_async<'q, Q, Error>(
+        &mut self,
+        canaries: impl IntoIterator<Item = &'q Q>,
+        rest: impl IntoIterator<Item = &'q Q>,
+    ) -> CanaryReport<&'q Q, Comp, Error>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let canaries: Vec<_> = self.try_reinit_async(canaries).await.collect();
+        let rest = match passed(&canaries) {
+            true => Some(self.try_reinit_async(rest).await.collect()),
+            false => None,
+        };
+        CanaryReport { canaries, rest }
+    }
+}
+
+fn passed<Key, Comp, Error>(canaries: &[Keyed<Key, ReinitOutcome<Comp, Error>>]) -> bool {
+    !canaries
+        .iter()
+        .any(|canary| canary.value.is_failed() || matches!(canary.value, ReinitOutcome::TimedOut))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[tokio::test]
+    async fn test_canary_gates_the_rest() {
+        let init = async |_key: &&str, args: &usize| match args {
+            0 => Err(TestError),
+            _ => Ok(Counter(*args)),
+        };
+        let mut manager = ComponentMap::try_init_async([("key1", 1), ("key2", 2)], init)
+            .await
+            .unwrap();
+        manager.set_args(&"key1", 0);
+        manager.set_args(&"key2", 20);
+
+        let report = manager.try_reinit_canary_async([&"key1"], [&"key2"]).await;
+        assert!(report.is_aborted());
+        assert_eq!(report.canaries[0].value, ReinitOutcome::Failed(TestError));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(2)));
+
+        manager.set_args(&"key1", 10);
+        let report = manager.try_reinit_canary_async([&"key1"], [&"key2"]).await;
+        assert!(!report.is_aborted());
+        assert_eq!(
+            report.rest.unwrap()[0].value,
+            ReinitOutcome::Replaced(Counter(2))
+        );
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(20)));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    AsyncTeardown, ChangeKind, ComponentMap, Entries, Keyed, KeyedStorage, NoTeardown, Teardown,
+    WithArgs, teardown::teardown_all,
+};
+use futures::{
+    StreamExt,
+    future::{Either, select},
+    stream::FuturesUnordered,
+};
+use std::{future::Future, pin::pin};
+
+/// Drives `futures` concurrently until they all complete or `cancel` resolves.
+///
+/// Outputs are returned in input order; futures still in flight when `cancel`
+/// resolves are dropped and yield `None`.
+pub(crate) async fn join_until<F>(
+    futures: impl IntoIterator<Item = F>,
+    cancel: impl Future,
+) -> Vec<Option<F::Output>>
+where
+    F: Future,
+{
+    let mut pending = futures
+        .into_iter()
+        .enumerate()
+        .map(|(index, future)| async move { (index, future.await) })
+        .collect::<FuturesUnordered<_>>();
+
+    let mut outputs: Vec<_> = (0..pending.len()).map(|_| None).collect();
+    let mut cancel = pin!(cancel);
+
+    while let Either::Left((Some((index, output)), _)) =
+        select(pending.next(), cancel.as_mut()).await
+    {
+        outputs[index] = Some(output);
+    }
+
+    outputs
+}
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    /// Like [`init_async`](Self::init_async), but stops once `cancel` resolves.
+    ///
+    /// Returns the map of entries that completed before cancellation together
+    /// with the keys whose initialisation was aborted.
+    pub async fn init_async_until(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        cancel: impl Future,
+    ) -> (Self, Vec<Key>)
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let components_fut = entries
+            .iter()
+            .map(|(key, args)| (init)(key, args))
+            .collect::<Vec<_>>();
+
+        let components = join_until(components_fut, cancel).await;
+
+        let mut map = Entries::new();
+        let mut cancelled = Vec::new();
+        for ((key, args), component) in entries.into_iter().zip(components) {
+            match component {
+                Some(component) => {
+                    map.insert(key, WithArgs::new(component, args));
+                }
+                None => cancelled.push(key),
+            }
+        }
+
+        (Self::new(map, init, NoTeardown), cancelled)
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Like [`reinit_all_async`](Self::reinit_all_async), but stops once `cancel` resolves.
+    ///
+    /// Keys whose reinit was aborted keep their current component and yield `None`.
+    pub async fn reinit_all_async_until(
+        &mut self,
+        cancel: impl Future,
+    ) -> impl Iterator<Item = Keyed<&Key, Option<Comp>>>
+    where
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let next_components_fut = self.map.iter().map(|(key, component)| async {
+            let span = self.events.instrument.start(key, &component.args);
+            let next = (self.init)(key, &component.args).await;
+            span.finish(key);
+            next
+        });
+
+        let next_components = join_until(next_components_fut, cancel).await;
+
+        let mut prev_components = self
+            .map
+            .iter_mut()
+            .zip(next_components)
+            .map(|((key, component), next)| {
+                let prev = next.map(|next| component.replace_component(next));
+                if prev.is_some() {
+                    self.events
+                        .emit(key, ChangeKind::Reinitialized, Some(&*component));
+                }
+                Keyed::new(key, prev)
+            })
+            .collect::<Vec<_>>();
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .filter_map(|Keyed { key, value }| value.as_mut().map(|prev| (&**key, prev))),
+        )
+        .await;
+
+        prev_components.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::oneshot;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    // Args of 0 hang until cancelled
+    async fn hang_on_zero(value: usize) -> Counter {
+        if value == 0 {
+            futures::future::pending::<()>().await;
+        }
+        Counter(value)
+    }
+
+    #[tokio::test]
+    async fn test_init_async_until() {
+        let init = |_key: &&str, args: &usize| hang_on_zero(*args);
+        let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+
+        let cancel = async move {
+            tokio::task::yield_now().await;
+            cancel_tx.send(()).unwrap();
+        };
+        let ((manager, cancelled), _) = futures::join!(
+            ComponentMap::init_async_until([("key1", 1), ("key2", 0)], init, cancel_rx),
+            cancel,
+        );
+
+        assert_eq!(manager.len(), 1);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+        assert_eq!(cancelled, vec!["key2"]);
+    }
+
+    #[tokio::test]
+    async fn test_reinit_all_async_until() {
+        let init = |_key: &&str, args: &usize| hang_on_zero(*args);
+        let mut manager = ComponentMap::init_async([("key1", 1), ("key2", 2)], init).await;
+        manager.set_args(&"key1", 10);
+        manager.set_args(&"key2", 0);
+
+        let mut results: Vec<_> = manager
+            .reinit_all_async_until(tokio::task::yield_now())
+            .await
+            .map(|Keyed { key, value }| (*key, value))
+            .collect();
+        results.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(results, vec![("key1", Some(Counter(1))), ("key2", None)]);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(2)));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{ComponentMap, Entries, KeyedStorage, Lookup, Storage, Teardown, WithArgs};
+use core::borrow::Borrow;
+
+/// A [`ComponentMap`] layered over a parent, created by
+/// [`ComponentMap::child`].
+///
+/// Lookups check the child's own entries first and fall through to the parent
+/// on a miss, so e.g. each tenant can override or extend a shared base set of
+/// components without copying it. Writes only ever touch the child's entries.
+#[derive(Debug)]
+pub struct ChildComponentMap<
+    'a,
+    Key,
+    Args,
+    Comp,
+    FnInit,
+    FnDrop,
+    Store = Entries<Key, WithArgs<Args, Comp>>,
+> where
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+{
+    parent: &'a ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>,
+    local: ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>,
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Creates an empty child layered over this map, sharing its init,
+    /// teardown, and config.
+    pub fn child(&self) -> ChildComponentMap<'_, Key, Args, Comp, FnInit, FnDrop, Store>
+    where
+        FnInit: Clone,
+        FnDrop: Clone,
+        Store: Default,
+    {
+        let mut local = ComponentMap::new(
+            Store::default(),
+            (*self.init).clone(),
+            (*self.teardown).clone(),
+        );
+        local.config = self.config;
+        ChildComponentMap {
+            parent: self,
+            local,
+        }
+    }
+}
+
+impl<'a, Key, Args, Comp, FnInit, FnDrop, Store>
+    ChildComponentMap<'a, Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    Key: Eq + core::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    pub fn parent(&self) -> &'a ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store> {
+        self.parent
+    }
+
+    /// The child's own entries, without those of the parent.
+    pub fn local(&self) -> &ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store> {
+        &self.local
+    }
+
+    /// The child's own entries, for overriding or extending the parent through
+    /// the usual `update`, `remove`, and `reinit*` operations.
+    pub fn local_mut(&mut self) -> &mut ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store> {
+        &mut self.local
+    }
+
+    pub fn into_local(self) -> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store> {
+        self.local
+    }
+
+    /// Returns the child's component for `key`, or else the parent's.
+    pub fn get<Q>(&self, key: &Q) -> Option<&Comp>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.local.get(key).or_else(|| self.parent.get(key))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.local.contains_key(key) || self.parent.contains_key(key)
+    }
+
+    /// Whether `key` is served by the child rather than the parent.
+    pub fn is_local<Q>(&self, key: &Q) -> bool
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.local.contains_key(key)
+    }
+
+    /// Number of distinct keys across the child and the parent.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.local.is_empty() && self.parent.is_empty()
+    }
+
+    /// Iterates over the child's entries, then the parent's entries it does
+    /// not override.
+    pub fn iter(&self) -> impl Iterator<Item = (&Key, &Comp)> {
+        let local = self
+            .local
+            .map
+            .iter()
+            .map(|(key, entry)| (key, &entry.component));
+        let inherited = self
+            .parent
+            .map
+            .iter()
+            .filter(|(key, _)| !self.local.contains_key(*key))
+            .map(|(key, entry)| (key, &entry.component));
+        local.chain(inherited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_child_falls_through_to_parent() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let base = ComponentMap::init([("shared", 1), ("feed", 2)], init);
+
+        let mut tenant = base.child();
+        tenant
+            .local_mut()
+            .update([("feed", 20), ("extra", 30)])
+            .for_each(drop);
+
+        assert_eq!(tenant.get(&"shared"), Some(&Counter(1)));
+        assert_eq!(tenant.get(&"feed"), Some(&Counter(20)));
+        assert_eq!(tenant.get(&"extra"), Some(&Counter(30)));
+        assert!(tenant.is_local(&"feed"));
+        assert!(!tenant.is_local(&"shared"));
+        assert_eq!(tenant.len(), 3);
+        assert_eq!(base.get(&"feed"), Some(&Counter(2)));
+
+        tenant.local_mut().remove(&"feed");
+        assert_eq!(tenant.get(&"feed"), Some(&Counter(2)));
+        assert_eq!(tenant.get(&"missing"), None);
+    }
+}
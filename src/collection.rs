@@ -0,0 +1,1066 @@
+use crate::{
+    AsyncTeardown, ChangeKind, ComponentMap, Entries, Keyed, KeyedStorage, Lookup, Teardown,
+    WithArgs, storage::HashMap, teardown::teardown_all,
+};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&Comp>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.get(key).map(|component| {
+            component.touch();
+            &component.component
+        })
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut Comp>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.get_mut(key).map(|component| {
+            component.touch();
+            &mut component.component
+        })
+    }
+
+    pub fn get_args<Q>(&self, key: &Q) -> Option<&Args>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.get(key).map(|component| &component.args)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Key> {
+        self.map.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Comp> {
+        self.map.values().map(|component| &component.component)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Comp> {
+        self.map
+            .values_mut()
+            .map(|component| &mut component.component)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Key, &Comp, &Args)> {
+        self.map
+            .iter()
+            .map(|(key, component)| (key, &component.component, &component.args))
+    }
+
+    /// Clones every component into a map that is unaffected by later mutations.
+    pub fn snapshot(&self) -> HashMap<Key, Comp>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        Comp: Clone,
+    {
+        self.map
+            .iter()
+            .map(|(key, component)| (key.clone(), component.component.clone()))
+            .collect()
+    }
+
+    /// Clones the key and args of every entry, i.e. the state the map is
+    /// configured for: dirty entries report the args they will be rebuilt
+    /// from, and paused entries are left out.
+    ///
+    /// The result can be written back to disk, compared with
+    /// [`diff`](Self::diff), or used to [`init`](ComponentMap::init) another map.
+    pub fn export_args(&self) -> Vec<(Key, Args)>
+    where
+        Key: Clone,
+        Args: Clone,
+    {
+        self.map
+            .iter()
+            .map(|(key, component)| (key.clone(), component.args.clone()))
+            .collect()
+    }
+
+    /// Replaces the args for `key` without reinitialising, marking the entry dirty.
+    pub fn set_args<Q>(&mut self, key: &Q, args: Args) -> Option<Args>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map
+            .get_mut(key)
+            .map(|component| component.set_args(args))
+    }
+
+    pub fn is_dirty<Q>(&self, key: &Q) -> Option<bool>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.get(key).map(WithArgs::is_dirty)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn last_initialized_at<Q>(&self, key: &Q) -> Option<Instant>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.get(key).map(WithArgs::last_initialized_at)
+    }
+
+    /// Time since the component for `key` was last built successfully.
+    #[cfg(feature = "std")]
+    pub fn age<Q>(&self, key: &Q) -> Option<Duration>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.get(key).map(WithArgs::age)
+    }
+
+    pub fn insert_component(
+        &mut self,
+        key: Key,
+        args: Args,
+        component: Comp,
+    ) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.reserve_slot(&key);
+        self.insert_entry(key, WithArgs::new(component, args)).0
+    }
+
+    /// Stores `entry` at `key`, tearing down the entry it replaces, and emits
+    /// the matching event.
+    pub(crate) fn insert_entry(
+        &mut self,
+        key: Key,
+        entry: WithArgs<Args, Comp>,
+    ) -> (Option<WithArgs<Args, Comp>>, &mut WithArgs<Args, Comp>) {
+        self.map.upsert(key, entry, |key, entry, prev| {
+            let prev = prev.map(|mut prev| {
+                self.teardown.teardown(key, &mut prev.component);
+                prev
+            });
+            self.events
+                .emit(key, ChangeKind::upsert(prev.is_some()), Some(entry));
+            prev
+        })
+    }
+
+    /// Moves the entry at `old` to `new` without reinitialising it, along with
+    /// its tags and dependencies; a paused `old` stays paused under `new`.
+    ///
+    /// Returns `false` without modifying the map if `old` is missing or `new` is already present.
+    pub fn rename_key<Q>(&mut self, old: &Q, new: Key) -> bool
+    where
+        Key: Clone + Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        if self.map.contains_key(&new) || self.paused.contains_key::<Key>(&new) {
+            return false;
+        }
+
+        let old = match self.map.take(old) {
+            Some((old, component)) => {
+                self.events
+                    .emit(&old, ChangeKind::Removed, Some(&component));
+                self.events
+                    .emit(&new, ChangeKind::Inserted, Some(&component));
+                self.map.insert(new.clone(), component);
+                if let Some(args) = self.paused.remove::<Key>(&old) {
+                    self.paused.insert(new.clone(), args);
+                }
+                old
+            }
+            None => match self.paused.remove_entry(old) {
+                Some((old, args)) => {
+                    self.paused.insert(new.clone(), args);
+                    old
+                }
+                None => return false,
+            },
+        };
+
+        self.dependencies.rename(&old, &new);
+        for keys in self.groups.values_mut() {
+            if keys.remove::<Key>(&old) {
+                keys.insert(new.clone());
+            }
+        }
+        true
+    }
+
+    /// Removes the entry for `key` through the teardown, or discards its args
+    /// if it is paused, and drops its tags and dependencies.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.remove_entry(key).map(|(_, component)| component)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(Key, WithArgs<Args, Comp>)>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.forget(key);
+        self.take_entry(key)
+    }
+
+    /// Removes the entry for `key` through the teardown, leaving the side
+    /// tables untouched.
+    pub(crate) fn take_entry<Q>(&mut self, key: &Q) -> Option<(Key, WithArgs<Args, Comp>)>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.take(key).map(|(key, mut component)| {
+            self.teardown.teardown(&key, &mut component.component);
+            self.events
+                .emit(&key, ChangeKind::Removed, Some(&component));
+            (key, component)
+        })
+    }
+
+    /// Drops what the side tables hold for a key that left the map for good:
+    /// its paused args, dependencies, and tags.
+    pub(crate) fn forget<Q>(&mut self, key: &Q)
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.paused.remove(key);
+        self.dependencies.remove(key);
+        self.groups.retain(|_, keys| {
+            keys.remove(key);
+            !keys.is_empty()
+        });
+    }
+
+    fn forget_all(&mut self) {
+        self.paused.clear();
+        self.dependencies.clear();
+        self.groups.clear();
+    }
+
+    pub fn remove_many<'q, Q>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, Option<WithArgs<Args, Comp>>>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        keys.into_iter()
+            .map(|key| {
+                let prev = self.remove(key);
+                Keyed::new(key, prev)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    pub fn retain(
+        &mut self,
+        mut predicate: impl FnMut(&Key, &WithArgs<Args, Comp>) -> bool,
+    ) -> impl Iterator<Item = (Key, WithArgs<Args, Comp>)>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        let mut removed = self
+            .map
+            .take_if(|key, component| !predicate(key, component))
+            .collect::<Vec<_>>();
+
+        for (key, component) in removed.iter_mut() {
+            self.teardown.teardown(key, &mut component.component);
+            self.events.emit(key, ChangeKind::Removed, Some(component));
+            self.forget(key);
+        }
+
+        removed.into_iter()
+    }
+
+    pub fn clear(&mut self) {
+        for (key, mut component) in self.map.take_all() {
+            self.teardown.teardown(&key, &mut component.component);
+            self.events
+                .emit(&key, ChangeKind::Removed, Some(&component));
+        }
+        self.forget_all();
+    }
+
+    /// Removes every entry, handing ownership to the caller without running teardown.
+    pub fn drain(&mut self) -> impl Iterator<Item = (Key, WithArgs<Args, Comp>)> {
+        let removed = self.map.take_all().collect::<Vec<_>>();
+        for (key, entry) in &removed {
+            self.events.emit(key, ChangeKind::Removed, Some(entry));
+        }
+        self.forget_all();
+
+        removed.into_iter()
+    }
+
+    pub async fn remove_async<Q>(&mut self, key: &Q) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        self.forget(key);
+        let (key, mut prev) = self.map.take(key)?;
+        self.teardown
+            .teardown_async(&key, &mut prev.component)
+            .await;
+        self.events.emit(&key, ChangeKind::Removed, Some(&prev));
+        Some(prev)
+    }
+
+    pub async fn remove_many_async<'q, Q>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, Option<WithArgs<Args, Comp>>>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let mut prev_entries = keys
+            .into_iter()
+            .map(|key| {
+                self.forget(key);
+                Keyed::new(key, self.map.take(key))
+            })
+            .collect::<Vec<_>>();
+
+        teardown_all(
+            &*self.teardown,
+            prev_entries.iter_mut().filter_map(|Keyed { value, .. }| {
+                value
+                    .as_mut()
+                    .map(|(key, prev)| (&*key, &mut prev.component))
+            }),
+        )
+        .await;
+        for (key, entry) in prev_entries
+            .iter()
+            .filter_map(|Keyed { value, .. }| value.as_ref())
+        {
+            self.events.emit(key, ChangeKind::Removed, Some(entry));
+        }
+
+        prev_entries
+            .into_iter()
+            .map(|Keyed { key, value }| Keyed::new(key, value.map(|(_, prev)| prev)))
+    }
+
+    pub async fn clear_async(&mut self)
+    where
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let mut removed = self.map.take_all().collect::<Vec<_>>();
+        teardown_all(
+            &*self.teardown,
+            removed
+                .iter_mut()
+                .map(|(key, component)| (&*key, &mut component.component)),
+        )
+        .await;
+
+        for (key, entry) in &removed {
+            self.events.emit(key, ChangeKind::Removed, Some(entry));
+        }
+        self.forget_all();
+    }
+
+    pub async fn retain_async(
+        &mut self,
+        mut predicate: impl FnMut(&Key, &WithArgs<Args, Comp>) -> bool,
+    ) -> impl Iterator<Item = (Key, WithArgs<Args, Comp>)>
+    where
+        Key: Eq + core::hash::Hash,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let mut removed = self
+            .map
+            .take_if(|key, component| !predicate(key, component))
+            .collect::<Vec<_>>();
+        teardown_all(
+            &*self.teardown,
+            removed
+                .iter_mut()
+                .map(|(key, component)| (&*key, &mut component.component)),
+        )
+        .await;
+
+        for (key, entry) in &removed {
+            self.events.emit(key, ChangeKind::Removed, Some(entry));
+            self.forget(key);
+        }
+
+        removed.into_iter()
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, S>
+    ComponentMap<Key, Args, Comp, FnInit, FnDrop, Entries<Key, WithArgs<Args, Comp>, S>>
+where
+    FnDrop: Teardown<Key, Comp>,
+    S: core::hash::BuildHasher,
+{
+    /// Number of entries the map can hold without reallocating; unrelated to
+    /// the eviction limit of [`ComponentMapConfig::capacity`](crate::ComponentMapConfig::capacity).
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize)
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.map.reserve(additional);
+    }
+
+    /// Releases the memory left over after mass removals.
+    pub fn shrink_to_fit(&mut self)
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.map.shrink_to_fit();
+        self.paused.shrink_to_fit();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Args {
+        value: usize,
+    }
+
+    #[test]
+    fn test_len_is_empty_and_contains_key() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        assert_eq!(manager.len(), 2);
+        assert!(!manager.is_empty());
+        assert!(manager.contains_key(&"key1"));
+        assert!(!manager.contains_key(&"nonexistent"));
+
+        let _: Vec<_> = manager.remove_many(["key1", "key2"]).collect();
+
+        assert_eq!(manager.len(), 0);
+        assert!(manager.is_empty());
+        assert!(!manager.contains_key(&"key1"));
+    }
+
+    #[test]
+    fn test_get_and_get_args() {
+        let init = |_key: &&str, args: &Args| Counter(args.value * 2);
+        let manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        assert_eq!(manager.get(&"key1"), Some(&Counter(2)));
+        assert_eq!(manager.get_args(&"key1"), Some(&Args { value: 1 }));
+        assert!(manager.get(&"nonexistent").is_none());
+        assert!(manager.get_args(&"nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        *manager.get_mut(&"key1").unwrap() = Counter(999);
+
+        assert_eq!(manager.get(&"key1"), Some(&Counter(999)));
+        assert!(manager.get_mut(&"nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_borrowed_key_lookups() {
+        let init = |_key: &String, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [
+                ("key1".to_string(), Args { value: 1 }),
+                ("key2".to_string(), Args { value: 2 }),
+            ],
+            init,
+        );
+
+        assert_eq!(manager.get("key1"), Some(&Counter(1)));
+        assert_eq!(manager.get_args("key2"), Some(&Args { value: 2 }));
+
+        let removed: Vec<_> = manager.remove_many(["key1"]).collect();
+        assert_eq!(removed[0].key, "key1");
+        assert_eq!(removed[0].value.as_ref().unwrap().component, Counter(1));
+
+        assert_eq!(manager.remove("key2").unwrap().component, Counter(2));
+        assert!(manager.map.is_empty());
+    }
+
+    #[test]
+    fn test_keys_values_and_iter() {
+        let init = |_key: &&str, args: &Args| Counter(args.value * 10);
+        let manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        let mut keys: Vec<_> = manager.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["key1", "key2"]);
+
+        let mut values: Vec<_> = manager.values().map(|component| component.0).collect();
+        values.sort();
+        assert_eq!(values, vec![10, 20]);
+
+        let mut entries: Vec<_> = manager
+            .iter()
+            .map(|(key, component, args)| (*key, component.0, args.value))
+            .collect();
+        entries.sort();
+        assert_eq!(entries, vec![("key1", 10, 1), ("key2", 20, 2)]);
+    }
+
+    #[test]
+    fn test_snapshot_is_isolated_from_mutations() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        let snapshot = manager.snapshot();
+        manager
+            .update([("key1", Args { value: 5 }), ("key2", Args { value: 2 })])
+            .for_each(drop);
+
+        assert_eq!(snapshot, HashMap::from([("key1", Counter(1))]));
+        assert_eq!(manager.snapshot().len(), 2);
+    }
+
+    #[test]
+    fn test_export_args_seeds_another_map() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+        manager.set_args(&"key1", Args { value: 10 });
+
+        let mut exported = manager.export_args();
+        exported.sort_by_key(|(key, _)| *key);
+        assert_eq!(
+            exported,
+            vec![("key1", Args { value: 10 }), ("key2", Args { value: 2 })]
+        );
+
+        let seeded = ComponentMap::init(exported, init);
+        assert_eq!(seeded.get(&"key1"), Some(&Counter(10)));
+        // The dirty entry still differs from its exported args until reinitialised
+        assert_eq!(manager.diff(manager.export_args()).modifications.len(), 1);
+    }
+
+    #[test]
+    fn test_values_mut() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        manager
+            .values_mut()
+            .for_each(|component| component.0 += 100);
+
+        assert_eq!(manager.get(&"key1"), Some(&Counter(101)));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(102)));
+    }
+
+    #[test]
+    fn test_set_args_marks_dirty_without_reinit() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        assert_eq!(manager.is_dirty(&"key1"), Some(false));
+
+        let prev = manager.set_args(&"key1", Args { value: 2 });
+
+        assert_eq!(prev, Some(Args { value: 1 }));
+        assert_eq!(manager.is_dirty(&"key1"), Some(true));
+        assert_eq!(manager.get_args(&"key1"), Some(&Args { value: 2 }));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+
+        assert!(
+            manager
+                .set_args(&"nonexistent", Args { value: 3 })
+                .is_none()
+        );
+        assert!(manager.is_dirty(&"nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_reinit_clears_dirty() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        manager.set_args(&"key1", Args { value: 10 });
+        manager.set_args(&"key2", Args { value: 20 });
+
+        let _: Vec<_> = manager.reinit(["key1"]).collect();
+
+        assert_eq!(manager.is_dirty(&"key1"), Some(false));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert_eq!(manager.is_dirty(&"key2"), Some(true));
+
+        let _: Vec<_> = manager.reinit_all().collect();
+        assert_eq!(manager.is_dirty(&"key2"), Some(false));
+    }
+
+    #[test]
+    fn test_age_resets_only_on_successful_reinit() {
+        let init = |_key: &&str, args: &Args| match args.value {
+            0 => Err("no value"),
+            value => Ok(Counter(value)),
+        };
+        let mut manager = ComponentMap::try_init([("key1", Args { value: 1 })], init).unwrap();
+        let built = manager.last_initialized_at(&"key1").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+
+        manager.set_args(&"key1", Args { value: 0 });
+        manager.try_reinit([&"key1"]).for_each(drop);
+        assert_eq!(manager.last_initialized_at(&"key1"), Some(built));
+        assert!(manager.age(&"key1").unwrap() >= Duration::from_millis(5));
+
+        manager.set_args(&"key1", Args { value: 2 });
+        manager.try_reinit([&"key1"]).for_each(drop);
+        assert!(manager.last_initialized_at(&"key1").unwrap() > built);
+        assert_eq!(manager.age(&"missing"), None);
+    }
+
+    #[test]
+    fn test_insert_component_skips_init() {
+        let init = |_key: &&str, _args: &Args| -> Counter { panic!("init should not be called") };
+        let mut manager: ComponentMap<&str, Args, Counter, _> = ComponentMap::init([], init);
+
+        let prev = manager.insert_component("key1", Args { value: 1 }, Counter(100));
+
+        assert!(prev.is_none());
+        assert_eq!(manager.get(&"key1"), Some(&Counter(100)));
+        assert_eq!(manager.get_args(&"key1"), Some(&Args { value: 1 }));
+    }
+
+    #[test]
+    fn test_insert_component_replaces_existing() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init).with_teardown(
+            move |_: &&str, component: &mut Counter| {
+                closed_clone.lock().unwrap().push(component.0);
+            },
+        );
+
+        let prev = manager
+            .insert_component("key1", Args { value: 2 }, Counter(200))
+            .unwrap();
+
+        assert_eq!(prev.component, Counter(1));
+        assert_eq!(prev.args.value, 1);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(200)));
+        assert_eq!(*closed.lock().unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_retain() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [
+                ("key1", Args { value: 1 }),
+                ("key2", Args { value: 2 }),
+                ("key3", Args { value: 3 }),
+            ],
+            init,
+        )
+        .with_teardown(move |key: &&str, _: &mut Counter| {
+            closed_clone.lock().unwrap().push(*key);
+        });
+
+        let mut removed: Vec<_> = manager
+            .retain(|_, component| component.args.value == 2)
+            .map(|(key, component)| (key, component.component))
+            .collect();
+        removed.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(removed, vec![("key1", Counter(1)), ("key3", Counter(3))]);
+        assert_eq!(manager.len(), 1);
+        assert!(manager.contains_key(&"key2"));
+
+        let mut closed = closed.lock().unwrap().clone();
+        closed.sort();
+        assert_eq!(closed, vec!["key1", "key3"]);
+    }
+
+    #[test]
+    fn test_retain_applies_without_consuming_results() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        let _ = manager.retain(|key, _| *key == "key1");
+
+        assert_eq!(manager.len(), 1);
+        assert!(manager.contains_key(&"key1"));
+    }
+
+    #[tokio::test]
+    async fn test_retain_async() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_async_teardown(async move |key: &&str, _: &mut Counter| {
+            closed_clone.lock().unwrap().push(*key);
+        });
+
+        let removed: Vec<_> = manager
+            .retain_async(|key, _| *key == "key1")
+            .await
+            .collect();
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].0, "key2");
+        assert_eq!(*closed.lock().unwrap(), vec!["key2"]);
+    }
+
+    #[test]
+    fn test_clear_runs_teardown_and_keeps_init() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_teardown(move |key: &&str, _: &mut Counter| {
+            closed_clone.lock().unwrap().push(*key);
+        });
+
+        manager.clear();
+
+        assert!(manager.is_empty());
+        let mut closed_keys = closed.lock().unwrap().clone();
+        closed_keys.sort();
+        assert_eq!(closed_keys, vec!["key1", "key2"]);
+
+        // Manager can be repopulated with the original init function
+        let _: Vec<_> = manager.update([("key3", Args { value: 3 })]).collect();
+        assert_eq!(manager.get(&"key3"), Some(&Counter(3)));
+    }
+
+    #[tokio::test]
+    async fn test_clear_async() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_async_teardown(async move |_: &&str, _: &mut Counter| {
+            *closed_clone.lock().unwrap() += 1;
+        });
+
+        manager.clear_async().await;
+
+        assert!(manager.is_empty());
+        assert_eq!(*closed.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_drain() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_teardown(move |_: &&str, _: &mut Counter| {
+            *closed_clone.lock().unwrap() += 1;
+        });
+
+        let mut drained: Vec<_> = manager
+            .drain()
+            .map(|(key, component)| (key, component.component, component.args))
+            .collect();
+        drained.sort_by_key(|(key, _, _)| *key);
+
+        assert_eq!(
+            drained,
+            vec![
+                ("key1", Counter(1), Args { value: 1 }),
+                ("key2", Counter(2), Args { value: 2 }),
+            ]
+        );
+        assert!(manager.is_empty());
+
+        drop(manager);
+        assert_eq!(*closed.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rename_key() {
+        let call_count = Arc::new(Mutex::new(0));
+        let call_count_clone = call_count.clone();
+
+        let init = move |_key: &String, args: &Args| {
+            *call_count_clone.lock().unwrap() += 1;
+            Counter(args.value)
+        };
+        let mut manager = ComponentMap::init([("old".to_string(), Args { value: 1 })], init);
+
+        assert!(manager.rename_key("old", "new".to_string()));
+
+        assert!(!manager.contains_key("old"));
+        assert_eq!(manager.get("new"), Some(&Counter(1)));
+        assert_eq!(manager.get_args("new"), Some(&Args { value: 1 }));
+        assert_eq!(*call_count.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_rename_key_carries_tags_dependencies_and_pause() {
+        let init = |_key: &String, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [
+                ("old".to_string(), Args { value: 1 }),
+                ("dep".to_string(), Args { value: 2 }),
+                ("user".to_string(), Args { value: 3 }),
+            ],
+            init,
+        );
+        manager.add_tag("old", "group");
+        manager
+            .add_dependency("old".to_string(), "dep".to_string())
+            .unwrap();
+        manager
+            .add_dependency("user".to_string(), "old".to_string())
+            .unwrap();
+
+        assert!(manager.rename_key("old", "new".to_string()));
+        assert_eq!(manager.tags("new").collect::<Vec<_>>(), vec!["group"]);
+        assert_eq!(manager.tags("old").count(), 0);
+        assert_eq!(manager.dependencies("new"), ["dep".to_string()]);
+        assert!(manager.dependencies("old").is_empty());
+        assert_eq!(manager.dependencies("user"), ["new".to_string()]);
+
+        assert!(manager.pause("new"));
+        assert!(manager.rename_key("new", "renamed".to_string()));
+        assert!(manager.is_paused("renamed"));
+        assert!(!manager.is_paused("new"));
+        assert_eq!(manager.tags("renamed").collect::<Vec<_>>(), vec!["group"]);
+    }
+
+    #[test]
+    fn test_removals_drop_tags_dependencies_and_paused_args() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [
+                ("key1", Args { value: 1 }),
+                ("key2", Args { value: 2 }),
+                ("key3", Args { value: 3 }),
+            ],
+            init,
+        );
+        for key in ["key1", "key2", "key3"] {
+            manager.add_tag(&key, "group");
+        }
+        manager.add_dependency("key1", "key3").unwrap();
+        manager.add_dependency("key2", "key3").unwrap();
+
+        manager.remove(&"key1");
+        assert!(manager.dependencies(&"key1").is_empty());
+        assert_eq!(manager.tags(&"key1").count(), 0);
+
+        manager.retain(|key, _| *key != "key2").for_each(drop);
+        assert!(manager.dependencies(&"key2").is_empty());
+        assert_eq!(manager.tags(&"key2").count(), 0);
+
+        manager.pause(&"key3");
+        assert!(manager.remove(&"key3").is_none());
+        assert!(!manager.is_paused(&"key3"));
+        assert_eq!(manager.resume(&"key3"), None);
+        assert_eq!(manager.iter_group("group").count(), 0);
+    }
+
+    #[test]
+    fn test_rename_key_missing_or_occupied() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        assert!(!manager.rename_key(&"nonexistent", "key3"));
+        assert!(!manager.rename_key(&"key1", "key2"));
+
+        assert_eq!(manager.len(), 2);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(2)));
+    }
+
+    #[test]
+    fn test_remove_existing_key() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        let removed = manager.remove(&"key1").unwrap();
+
+        assert_eq!(removed.component, Counter(1));
+        assert_eq!(removed.args.value, 1);
+        assert_eq!(manager.map.len(), 1);
+        assert!(!manager.map.contains_key("key1"));
+    }
+
+    #[test]
+    fn test_remove_nonexistent_key() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        assert!(manager.remove(&"nonexistent").is_none());
+        assert_eq!(manager.map.len(), 1);
+    }
+
+    #[test]
+    fn test_capacity_management() {
+        let init = |_key: &usize, args: &Args| Counter(args.value);
+        let entries = (0..1000).map(|key| (key, Args { value: key }));
+        let mut manager = ComponentMap::init_with_capacity(entries, init, 1000);
+        assert_eq!(manager.len(), 1000);
+        assert!(manager.capacity() >= 1000);
+
+        manager.retain(|key, _| *key < 10).for_each(drop);
+        let before = manager.capacity();
+        manager.shrink_to_fit();
+        assert!(manager.capacity() < before);
+        assert!(manager.capacity() >= 10);
+
+        manager.reserve(100);
+        assert!(manager.capacity() >= 110);
+
+        let empty: ComponentMap<usize, Args, Counter, _> = ComponentMap::with_capacity(init, 50);
+        assert!(empty.is_empty());
+        assert!(empty.capacity() >= 50);
+    }
+
+    #[test]
+    fn test_remove_entry() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        let (key, removed) = manager.remove_entry(&"key1").unwrap();
+
+        assert_eq!(key, "key1");
+        assert_eq!(removed.component, Counter(1));
+        assert!(manager.map.is_empty());
+    }
+
+    #[test]
+    fn test_remove_many_mixed() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [
+                ("key1", Args { value: 1 }),
+                ("key2", Args { value: 2 }),
+                ("key3", Args { value: 3 }),
+            ],
+            init,
+        );
+
+        let results: Vec<_> = manager
+            .remove_many(["key1", "nonexistent", "key3"])
+            .collect();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].key, "key1");
+        assert_eq!(results[0].value.as_ref().unwrap().component, Counter(1));
+        assert_eq!(results[1].key, "nonexistent");
+        assert!(results[1].value.is_none());
+        assert_eq!(results[2].value.as_ref().unwrap().component, Counter(3));
+
+        assert_eq!(manager.map.len(), 1);
+        assert!(manager.map.contains_key("key2"));
+    }
+}
@@ -0,0 +1,447 @@
+use crate::{
+    ComponentMap, Keyed, NoTeardown, Teardown, WithArgs, events::Instrumentation,
+    key_lock::KeyLocks,
+};
+use dashmap::DashMap;
+use futures::future::join_all;
+use std::borrow::Borrow;
+
+/// Sharded counterpart of [`ComponentMap`] for read-heavy workloads.
+///
+/// Entries are spread over independently locked shards, so lookups only
+/// contend with writers touching the same shard. Every method takes `&self`;
+/// share the map behind an `Arc`.
+///
+/// Builds and removals are reported to the instrument the source map was
+/// given with [`with_instrument`](ComponentMap::with_instrument); its other
+/// listeners are not carried over.
+#[derive(Debug)]
+pub struct ConcurrentComponentMap<Key, Args, Comp, FnInit, FnDrop = NoTeardown>
+where
+    Key: Eq + std::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    map: DashMap<Key, WithArgs<Args, Comp>>,
+    key_locks: KeyLocks<Key, ()>,
+    init: FnInit,
+    teardown: FnDrop,
+    instrument: Instrumentation<Key, Args>,
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> From<ComponentMap<Key, Args, Comp, FnInit, FnDrop>>
+    for ConcurrentComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Eq + std::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    fn from(map: ComponentMap<Key, Args, Comp, FnInit, FnDrop>) -> Self {
+        let instrument = map.events.instrument.clone();
+        let (map, init, teardown) = map.into_raw_parts();
+        Self {
+            map: map.into_iter().collect(),
+            key_locks: KeyLocks::default(),
+            init,
+            teardown,
+            instrument,
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> ConcurrentComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Eq + std::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Key: Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<Comp>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Comp: Clone,
+    {
+        self.map.get(key).map(|entry| entry.component.clone())
+    }
+
+    /// Calls `f` with the component for `key` while holding only its shard's read lock.
+    pub fn with_component<Q, R>(&self, key: &Q, f: impl FnOnce(&Comp) -> R) -> Option<R>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+    {
+        self.map.get(key).map(|entry| f(&entry.component))
+    }
+
+    /// Initialises a component from `args` and inserts it under `key`,
+    /// returning the entry it replaced.
+    ///
+    /// Takes the key's writer lock when it is free. An async reinit holding it
+    /// finds the entry updated once its init completes and discards its own
+    /// component, so the update is never overwritten with one built from the
+    /// old args.
+    pub fn update(&self, key: Key, args: Args) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Clone,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let claim = self.key_locks.claim(&key);
+        let mut guard = claim.try_lock();
+
+        let component = self
+            .instrument
+            .build(&key, &args, || (self.init)(&key, &args));
+        let next = WithArgs::new(component, args);
+        let prev = self.insert(key, next);
+
+        // Reinits queued behind this update must run their own init
+        if let Some(guard) = &mut guard {
+            guard.complete(None);
+        }
+        prev
+    }
+
+    /// Like [`update`](Self::update) for async inits, waiting for the key's
+    /// writer lock so it runs after any reinit of the key in flight.
+    pub async fn update_async(&self, key: Key, args: Args) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Clone,
+        FnInit: AsyncFn(&Key, &Args) -> Comp,
+    {
+        let claim = self.key_locks.claim(&key);
+        let mut guard = claim.lock().await;
+
+        let span = self.instrument.start(&key, &args);
+        let component = (self.init)(&key, &args).await;
+        span.finish(&key);
+        let next = WithArgs::new(component, args);
+        let prev = self.insert(key, next);
+
+        // Reinits queued behind this update must run their own init
+        guard.complete(None);
+        prev
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+    {
+        self.map.remove(key).map(|(key, mut prev)| {
+            self.teardown.teardown(&key, &mut prev.component);
+            if let Some(instrument) = &self.instrument.instrument {
+                instrument.on_remove(&key);
+            }
+            prev
+        })
+    }
+
+    /// Reinitialises every component one shard at a time, so only lookups on
+    /// the shard currently being rebuilt wait.
+    pub fn reinit_all(&self) -> Vec<Keyed<Key, Comp>>
+    where
+        Key: Clone,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        self.map
+            .iter_mut()
+            .map(|mut entry| {
+                let (key, component) = entry.pair_mut();
+                let next = self
+                    .instrument
+                    .build(key, &component.args, || (self.init)(key, &component.args));
+                let mut prev = component.replace_component(next);
+                self.teardown.teardown(key, &mut prev);
+                Keyed::new(key.clone(), prev)
+            })
+            .collect()
+    }
+
+    /// Reinitialises every component concurrently without holding any lock
+    /// while the inits run; each replacement then locks only its own shard.
+    ///
+    /// Keys removed or updated while their init was running keep their
+    /// current entry, tear down the component built for them, and yield `None`.
+    pub async fn reinit_all_async(&self) -> Vec<Keyed<Key, Option<Comp>>>
+    where
+        Key: Clone,
+        Args: Clone,
+        FnInit: AsyncFn(&Key, &Args) -> Comp,
+    {
+        let entries: Vec<_> = self
+            .map
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.args.clone(), entry.generation()))
+            .collect();
+
+        let next_components = join_all(entries.iter().map(async |(key, args, _)| {
+            let span = self.instrument.start(key, args);
+            let next = (self.init)(key, args).await;
+            span.finish(key);
+            next
+        }))
+        .await;
+
+        entries
+            .into_iter()
+            .zip(next_components)
+            .map(|((key, _, generation), next)| {
+                let prev = self.replace_if_current(&key, generation, next);
+                Keyed::new(key, prev)
+            })
+            .collect()
+    }
+
+    /// Reinitialises the component for `key` from its current args and tears
+    /// down the previous one. Returns whether the component was replaced:
+    /// `false` if the key is missing, or was removed or updated while the
+    /// init ran, in which case the new component is torn down instead.
+    ///
+    /// Only this entry is locked: concurrent reinits of the same key share a
+    /// single init, while readers and writers of every other key proceed. The
+    /// entry itself is only locked for the final swap, so readers of `key`
+    /// keep seeing the previous component until then.
+    pub async fn reinit_key_async<Q>(&self, key: &Q) -> bool
+    where
+        Key: Clone + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Args: Clone,
+        FnInit: AsyncFn(&Key, &Args) -> Comp,
+    {
+        let Some(key) = self.map.get(key).map(|entry| entry.key().clone()) else {
+            return false;
+        };
+        let claim = self.key_locks.claim(&key);
+        let mut guard = claim.lock().await;
+
+        // A reinit of the key completed while we waited on it
+        if guard.shared().is_some() {
+            return true;
+        }
+
+        let Some((args, generation)) = self
+            .map
+            .get::<Key>(&key)
+            .map(|entry| (entry.args.clone(), entry.generation()))
+        else {
+            return false;
+        };
+        let span = self.instrument.start(&key, &args);
+        let next = (self.init)(&key, &args).await;
+        span.finish(&key);
+        let replaced = self.replace_if_current(&key, generation, next).is_some();
+
+        guard.complete(replaced.then_some(()));
+        replaced
+    }
+
+    fn insert(&self, key: Key, next: WithArgs<Args, Comp>) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Clone,
+    {
+        self.map.insert(key.clone(), next).map(|mut prev| {
+            self.teardown.teardown(&key, &mut prev.component);
+            prev
+        })
+    }
+
+    /// Swaps `next` in for the component of `key` and tears down the previous
+    /// one, unless the entry was removed or replaced since `generation`, in
+    /// which case `next` is torn down instead.
+    fn replace_if_current(&self, key: &Key, generation: u64, mut next: Comp) -> Option<Comp> {
+        let current = self
+            .map
+            .get_mut(key)
+            .filter(|entry| entry.generation() == generation);
+        match current {
+            Some(mut entry) => {
+                let mut prev = entry.replace_component(next);
+                self.teardown.teardown(key, &mut prev);
+                Some(prev)
+            }
+            None => {
+                self.teardown.teardown(key, &mut next);
+                None
+            }
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> Drop
+    for ConcurrentComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Eq + std::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    fn drop(&mut self) {
+        for mut entry in self.map.iter_mut() {
+            let (key, component) = entry.pair_mut();
+            self.teardown.teardown(key, &mut component.component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_concurrent_get_update_remove() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let manager = ComponentMap::init([("key1", 1)], init).with_teardown(
+            move |key: &&str, _: &mut Counter| closed_clone.lock().unwrap().push(*key),
+        );
+        let concurrent = ConcurrentComponentMap::from(manager);
+
+        assert_eq!(concurrent.get(&"key1"), Some(Counter(1)));
+        assert_eq!(
+            concurrent.with_component(&"key1", |component| component.0),
+            Some(1)
+        );
+
+        let prev = concurrent.update("key1", 2);
+        assert_eq!(prev.map(|prev| prev.component), Some(Counter(1)));
+        assert!(concurrent.update("key2", 3).is_none());
+        assert_eq!(concurrent.len(), 2);
+
+        assert_eq!(concurrent.remove(&"key2").map(|prev| prev.args), Some(3));
+        assert!(!concurrent.contains_key(&"key2"));
+
+        drop(concurrent);
+        assert_eq!(*closed.lock().unwrap(), vec!["key1", "key2", "key1"]);
+    }
+
+    #[test]
+    fn test_concurrent_reinit_all_from_threads() {
+        let init = |key: &usize, args: &usize| Counter(key + args);
+        let concurrent = Arc::new(ConcurrentComponentMap::from(ComponentMap::init(
+            (0..100).map(|key| (key, 0)),
+            init,
+        )));
+
+        let reader = {
+            let concurrent = concurrent.clone();
+            std::thread::spawn(move || (0..100).all(|key| concurrent.contains_key(&key)))
+        };
+        let prev = concurrent.reinit_all();
+
+        assert!(reader.join().unwrap());
+        assert_eq!(prev.len(), 100);
+        assert_eq!(concurrent.get(&42), Some(Counter(42)));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reinit_all_async() {
+        let init = |_key: &&str, args: &usize| {
+            let value = *args;
+            async move {
+                tokio::task::yield_now().await;
+                Counter(value * 10)
+            }
+        };
+        let concurrent = ConcurrentComponentMap::from(
+            ComponentMap::init_async([("key1", 1), ("key2", 2)], init).await,
+        );
+
+        let (prev, removed) = tokio::join!(concurrent.reinit_all_async(), async {
+            concurrent.remove(&"key2")
+        });
+        let mut prev: Vec<_> = prev
+            .into_iter()
+            .map(|Keyed { key, value }| (key, value))
+            .collect();
+        prev.sort_by_key(|(key, _)| *key);
+
+        assert!(removed.is_some());
+        assert_eq!(prev, vec![("key1", Some(Counter(10))), ("key2", None)]);
+        assert_eq!(concurrent.get(&"key1"), Some(Counter(10)));
+    }
+
+    #[tokio::test]
+    async fn test_reinit_key_async_only_locks_its_entry() {
+        let calls = Arc::new(Mutex::new(0));
+        let calls_clone = calls.clone();
+
+        let init = move |_key: &&str, args: &usize| {
+            let calls = calls_clone.clone();
+            let value = *args;
+            async move {
+                *calls.lock().unwrap() += 1;
+                tokio::task::yield_now().await;
+                Counter(value)
+            }
+        };
+        let concurrent = ConcurrentComponentMap::from(
+            ComponentMap::init_async([("key1", 1), ("key2", 2)], init).await,
+        );
+        *calls.lock().unwrap() = 0;
+
+        let (first, second, reads) = tokio::join!(
+            concurrent.reinit_key_async(&"key1"),
+            concurrent.reinit_key_async(&"key1"),
+            async {
+                // Runs while the shared reinit of key1 is pending
+                let key2 = concurrent.remove(&"key2");
+                (concurrent.get(&"key1"), key2.map(|prev| prev.component))
+            },
+        );
+
+        assert!(first);
+        assert!(second);
+        assert_eq!(reads, (Some(Counter(1)), Some(Counter(2))));
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert!(!concurrent.contains_key(&"key2"));
+        assert!(!concurrent.reinit_key_async(&"key3").await);
+        assert!(concurrent.key_locks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_during_reinit_is_kept() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = async |_key: &&str, args: &usize| {
+            tokio::task::yield_now().await;
+            Counter(*args)
+        };
+        let manager = ComponentMap::init_async([("key1", 1)], init)
+            .await
+            .with_teardown(move |_: &&str, component: &mut Counter| {
+                closed_clone.lock().unwrap().push(component.0)
+            });
+        let concurrent = ConcurrentComponentMap::from(manager);
+
+        let (reinit_all, (prev, reinit)) = tokio::join!(concurrent.reinit_all_async(), async {
+            let prev = concurrent.update_async("key1", 2).await;
+            (prev, concurrent.reinit_key_async(&"key1").await)
+        });
+
+        // The batch reinit found key1 updated and discarded its component
+        assert_eq!(reinit_all[0].value, None);
+        assert_eq!(prev.map(|prev| prev.component), Some(Counter(1)));
+        assert!(reinit);
+        assert_eq!(concurrent.get(&"key1"), Some(Counter(2)));
+        assert_eq!(*closed.lock().unwrap(), vec![1, 1, 2]);
+        assert!(concurrent.key_locks.is_empty());
+    }
+}
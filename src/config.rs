@@ -0,0 +1,216 @@
+#[cfg(feature = "std")]
+use crate::{BatchOptions, RetryPolicy};
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+/// Manager-wide defaults for the async operations and the TTL of every entry.
+///
+/// Any field left unset on the [`BatchOptions`] of a call falls back to the
+/// value configured here. Only `capacity` is available without the `std`
+/// feature, which the async operations and every clock-based option need.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ComponentMapConfig {
+    #[cfg(feature = "std")]
+    pub concurrency_limit: Option<usize>,
+    #[cfg(feature = "std")]
+    pub retry: Option<RetryPolicy>,
+    /// Bound on each key's init, retries included.
+    ///
+    /// Only enforced by the async operations that can report it:
+    /// `try_reinit_async` and `try_update_async` leave keys exceeding it
+    /// untouched and report them as [`TimedOut`](crate::ReinitOutcome::TimedOut).
+    /// Wrap the init with [`with_timeout`](crate::with_timeout) to bound the
+    /// rest.
+    #[cfg(feature = "std")]
+    pub timeout: Option<Duration>,
+    /// Bound applied to each teardown by `shutdown_async` when the call passes
+    /// none, and to the background teardowns of blue/green reinits.
+    #[cfg(feature = "std")]
+    pub shutdown_timeout: Option<Duration>,
+    /// Max age of entries without their own TTL, checked by `get_fresh`.
+    #[cfg(feature = "std")]
+    pub ttl: Option<Duration>,
+    /// Max number of entries; inserting beyond it evicts the least recently
+    /// used entry, through the async teardown in the `*_async` operations and
+    /// the sync one everywhere else.
+    pub capacity: Option<usize>,
+    /// Min time between reinit attempts of the same key, counted from its
+    /// last reinit, successful or not.
+    ///
+    /// Only enforced by the per-key reinits that can report it:
+    /// `try_reinit`, `try_reinit_async`, `try_reinit_with_retry`, and every
+    /// `SharedComponentMap` reinit, background ones included, report keys
+    /// arriving sooner as [`Throttled`](crate::ReinitOutcome::Throttled).
+    /// Explicit `reinit` and the `*_all` batch reinits always run, but still
+    /// count as attempts.
+    #[cfg(feature = "std")]
+    pub min_reinit_interval: Option<Duration>,
+    /// Window over which `ComponentMapHandle::update_debounced` coalesces
+    /// updates to the same key.
+    #[cfg(feature = "std")]
+    pub debounce: Option<Duration>,
+}
+
+impl ComponentMapConfig {
+    #[cfg(feature = "std")]
+    pub fn concurrency_limit(mut self, limit: usize) -> Self {
+        self.concurrency_limit = Some(limit);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = Some(timeout);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn min_reinit_interval(mut self, interval: Duration) -> Self {
+        self.min_reinit_interval = Some(interval);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn debounce(mut self, window: Duration) -> Self {
+        self.debounce = Some(window);
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Key> BatchOptions<Key> {
+    /// Fills every option not set for this call from `config`.
+    pub(crate) fn or_config(self, config: &ComponentMapConfig) -> Self {
+        Self {
+            concurrency_limit: self.concurrency_limit.or(config.concurrency_limit),
+            retry: self.retry.or(config.retry),
+            timeout: self.timeout.or(config.timeout),
+            progress: self.progress,
+            dedup: self.dedup,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{Backoff, ComponentMap, ReinitOutcome};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[tokio::test]
+    async fn test_config_supplies_retry_default() {
+        let attempts = Arc::new(Mutex::new(0usize));
+        let attempts_clone = attempts.clone();
+
+        // Every other attempt fails
+        let init = move |_key: &&str, args: &usize| {
+            let attempts = attempts_clone.clone();
+            let value = *args;
+            async move {
+                let mut attempts = attempts.lock().unwrap();
+                *attempts += 1;
+                if attempts.is_multiple_of(2) {
+                    Err(TestError)
+                } else {
+                    Ok(Counter(value))
+                }
+            }
+        };
+
+        let no_retry = RetryPolicy::new(1, Backoff::Fixed(Duration::ZERO));
+        let retry = RetryPolicy::new(2, Backoff::Fixed(Duration::ZERO));
+        let mut manager = ComponentMap::try_init_async([("key1", 1)], init)
+            .await
+            .unwrap()
+            .with_config(ComponentMapConfig::default().retry(retry));
+
+        let results: Vec<_> = manager.try_update_async([("key1", 10)]).await.collect();
+        assert!(matches!(results[0].value, ReinitOutcome::Replaced(_)));
+        assert_eq!(*attempts.lock().unwrap(), 3);
+
+        // A per-call policy overrides the configured one
+        let results: Vec<_> = manager
+            .try_update_async_with([("key1", 20)], BatchOptions::default().retry(no_retry))
+            .await
+            .collect();
+        assert!(results[0].value.is_failed());
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+    }
+
+    #[tokio::test]
+    async fn test_call_timeout_overrides_config_default() {
+        // Inits take as many milliseconds as their args
+        let init = async |_key: &&str, args: &u64| {
+            futures_timer::Delay::new(Duration::from_millis(*args)).await;
+            Ok::<_, TestError>(Counter(*args as usize))
+        };
+        let mut manager = ComponentMap::try_init_async([("key1", 1)], init)
+            .await
+            .unwrap()
+            .with_config(ComponentMapConfig::default().timeout(Duration::from_millis(20)));
+
+        let results: Vec<_> = manager.try_update_async([("key1", 500)]).await.collect();
+        assert!(matches!(results[0].value, ReinitOutcome::TimedOut));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+        let results: Vec<_> = manager.try_reinit_async([&"key1"]).await.collect();
+        assert!(matches!(results[0].value, ReinitOutcome::Replaced(_)));
+
+        // A per-call timeout overrides the configured one
+        let results: Vec<_> = manager
+            .try_update_async_with(
+                [("key1", 100)],
+                BatchOptions::default().timeout(Duration::from_secs(5)),
+            )
+            .await
+            .collect();
+        assert!(matches!(results[0].value, ReinitOutcome::Replaced(_)));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(100)));
+
+        let results: Vec<_> = manager.try_reinit_async([&"key1"]).await.collect();
+        assert!(matches!(results[0].value, ReinitOutcome::TimedOut));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(100)));
+    }
+
+    #[test]
+    fn test_config_survives_builders() {
+        let config = ComponentMapConfig::default()
+            .concurrency_limit(4)
+            .shutdown_timeout(Duration::from_secs(1));
+        let init = |_key: &&str, args: &usize| Counter(*args);
+
+        let manager = ComponentMap::init([("key1", 1)], init)
+            .with_config(config)
+            .with_teardown(|_key: &&str, _component: &mut Counter| {});
+
+        assert_eq!(manager.config, config);
+    }
+}
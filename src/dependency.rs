@@ -0,0 +1,594 @@
+#[cfg(feature = "std")]
+use crate::batch::join_bounded;
+use crate::{
+    ChangeKind, ComponentMap, Entries, Keyed, KeyedStorage, Lookup, NoTeardown, Storage, Teardown,
+    WithArgs,
+    priority::prioritized,
+    storage::{HashMap, HashSet},
+};
+use alloc::vec::Vec;
+use core::{borrow::Borrow, fmt, marker::PhantomData};
+
+/// Error from declaring a dependency the map cannot satisfy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyError<Key> {
+    /// `key` depends on `dependency`, which is not in the map.
+    Missing { key: Key, dependency: Key },
+    /// The declarations form a cycle: each key depends on the next, and the
+    /// last on the first.
+    Cycle(Vec<Key>),
+}
+
+impl<Key: fmt::Debug> fmt::Display for DependencyError<Key> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyError::Missing { key, dependency } => {
+                write!(f, "{key:?} depends on missing key {dependency:?}")
+            }
+            DependencyError::Cycle(keys) => {
+                f.write_str("dependency cycle")?;
+                for (index, key) in keys.iter().chain(keys.first()).enumerate() {
+                    let separator = if index == 0 { ": " } else { " -> " };
+                    write!(f, "{separator}{key:?}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<Key: fmt::Debug> core::error::Error for DependencyError<Key> {}
+
+/// Dependencies declared between the keys of a map, from each key to the keys
+/// it depends on.
+#[derive(Debug)]
+pub(crate) struct Dependencies<Key> {
+    edges: HashMap<Key, Vec<Key>>,
+}
+
+impl<Key> Default for Dependencies<Key> {
+    fn default() -> Self {
+        Self {
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<Key> Dependencies<Key> {
+    pub(crate) fn clear(&mut self) {
+        self.edges.clear();
+    }
+}
+
+impl<Key> Dependencies<Key>
+where
+    Key: Eq + core::hash::Hash,
+{
+    fn of<Q>(&self, key: &Q) -> &[Key]
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.edges.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Drops the dependencies declared by `key`.
+    pub(crate) fn remove<Q>(&mut self, key: &Q)
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.edges.remove(key);
+    }
+
+    /// Moves the dependencies declared by `old` to `new` and points its
+    /// dependents at `new`.
+    pub(crate) fn rename(&mut self, old: &Key, new: &Key)
+    where
+        Key: Clone,
+    {
+        if let Some(dependencies) = self.edges.remove(old) {
+            self.edges.insert(new.clone(), dependencies);
+        }
+        for dependency in self.edges.values_mut().flatten() {
+            if dependency == old {
+                *dependency = new.clone();
+            }
+        }
+    }
+
+    fn insert(&mut self, key: Key, dependency: Key) {
+        let dependencies = self.edges.entry(key).or_default();
+        if !dependencies.contains(&dependency) {
+            dependencies.push(dependency);
+        }
+    }
+
+    /// Records `key` depending on `dependency` unless that closes a cycle.
+    fn try_insert(&mut self, key: Key, dependency: Key) -> Result<(), DependencyError<Key>>
+    where
+        Key: Clone,
+    {
+        if self.of(&key).contains(&dependency) {
+            return Ok(());
+        }
+        let dependent = key.clone();
+        self.insert(key, dependency);
+        // Any new cycle runs through the new edge, so it is listed from `key`
+        let cycle = self.visit(&dependent, &mut HashSet::new(), &mut Vec::new());
+        let Some(cycle) = cycle.map(|cycle| cycle.into_iter().cloned().collect()) else {
+            return Ok(());
+        };
+
+        let dependencies = self
+            .edges
+            .get_mut(&dependent)
+            .expect("the dependency was just inserted");
+        dependencies.pop();
+        if dependencies.is_empty() {
+            self.edges.remove(&dependent);
+        }
+        Err(DependencyError::Cycle(cycle))
+    }
+
+    fn find_cycle(&self) -> Option<Vec<&Key>> {
+        let mut done = HashSet::new();
+        let mut path = Vec::new();
+        self.edges
+            .keys()
+            .find_map(|key| self.visit(key, &mut done, &mut path))
+    }
+
+    /// Depth-first walk from `key`, returning the cycle it runs into, if any.
+    /// `path` holds the keys being visited, `done` those known to be acyclic.
+    fn visit<'a>(
+        &'a self,
+        key: &'a Key,
+        done: &mut HashSet<&'a Key>,
+        path: &mut Vec<&'a Key>,
+    ) -> Option<Vec<&'a Key>> {
+        if done.contains(key) {
+            return None;
+        }
+        if let Some(start) = path.iter().position(|visiting| *visiting == key) {
+            return Some(path[start..].to_vec());
+        }
+        path.push(key);
+        for dependency in self.of(key) {
+            if let Some(cycle) = self.visit(dependency, done, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(key);
+        None
+    }
+
+    /// Depth of every key in the graph: keys without dependencies sit at 0 and
+    /// every other key one past its deepest dependency.
+    pub(crate) fn depths(&self) -> HashMap<&Key, usize> {
+        let mut remaining: HashMap<&Key, usize> = self
+            .edges
+            .iter()
+            .map(|(key, dependencies)| (key, dependencies.len()))
+            .collect();
+        let mut dependents: HashMap<&Key, Vec<&Key>> = HashMap::new();
+        for (key, dependencies) in &self.edges {
+            for dependency in dependencies {
+                dependents.entry(dependency).or_default().push(key);
+            }
+        }
+
+        let mut ready: Vec<_> = dependents
+            .keys()
+            .filter(|key| !remaining.contains_key(*key))
+            .copied()
+            .collect();
+        let mut depths: HashMap<_, _> = ready.iter().map(|key| (*key, 0)).collect();
+        while let Some(key) = ready.pop() {
+            let depth = depths[key] + 1;
+            for dependent in dependents.get(key).into_iter().flatten() {
+                let current = depths.entry(*dependent).or_insert(depth);
+                *current = (*current).max(depth);
+                let left = remaining
+                    .get_mut(dependent)
+                    .expect("every dependent has declared dependencies");
+                *left -= 1;
+                if *left == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+        depths
+    }
+}
+
+/// Components of the keys an entry depends on, passed to the init of
+/// [`init_resolved`](ComponentMap::init_resolved). Dependencies are always
+/// initialised before their dependents.
+#[derive(Debug)]
+pub struct Resolved<'a, Key, Args, Comp, Store = Entries<Key, WithArgs<Args, Comp>>> {
+    map: &'a Store,
+    dependencies: &'a [Key],
+    entries: PhantomData<fn() -> WithArgs<Args, Comp>>,
+}
+
+impl<'a, Key, Args, Comp, Store> Resolved<'a, Key, Args, Comp, Store>
+where
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+{
+    fn new(map: &'a Store, dependencies: &'a [Key]) -> Self {
+        Self {
+            map,
+            dependencies,
+            entries: PhantomData,
+        }
+    }
+}
+
+impl<'a, Key, Args, Comp, Store> Resolved<'a, Key, Args, Comp, Store>
+where
+    Key: Eq + core::hash::Hash,
+    WithArgs<Args, Comp>: 'a,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Returns the component for `key` if it is a declared dependency.
+    pub fn get<Q>(&self, key: &Q) -> Option<&'a Comp>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.dependencies
+            .iter()
+            .any(|dependency| dependency.borrow() == key)
+            .then(|| self.map.get(key).map(|entry| &entry.component))
+            .flatten()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'a Key, &'a Comp)> {
+        let map = self.map;
+        self.dependencies.iter().filter_map(move |dependency| {
+            map.get_key_value(dependency)
+                .map(|(key, entry)| (key, &entry.component))
+        })
+    }
+}
+
+/// Groups `entries` by depth in `dependencies`, shallowest first.
+fn levels<Key, Args>(
+    entries: impl IntoIterator<Item = (Key, Args)>,
+    dependencies: &Dependencies<Key>,
+) -> Vec<Vec<(Key, Args)>>
+where
+    Key: Eq + core::hash::Hash,
+{
+    let depths = dependencies.depths();
+    let mut levels: Vec<Vec<_>> = Vec::new();
+    for (key, args) in entries {
+        let depth = depths.get(&key).copied().unwrap_or_default();
+        if levels.len() <= depth {
+            levels.resize_with(depth + 1, Vec::new);
+        }
+        levels[depth].push((key, args));
+    }
+    levels
+}
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit>
+where
+    Key: Clone + Eq + core::hash::Hash,
+{
+    /// Like [`init`](Self::init), building each component only after those of
+    /// the keys it depends on. Each pair in `dependencies` holds a key and one
+    /// of its dependencies; the order is kept by later `reinit_all*` calls.
+    ///
+    /// Fails without initialising anything if a dependency is missing or the
+    /// declarations form a cycle.
+    pub fn init_ordered(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        dependencies: impl IntoIterator<Item = (Key, Key)>,
+        init: FnInit,
+    ) -> Result<Self, DependencyError<Key>>
+    where
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let (entries, dependencies) = Self::resolve(entries, dependencies)?;
+        let mut manager = Self::new(Entries::new(), init, NoTeardown);
+        for (key, args) in levels(entries, &dependencies).into_iter().flatten() {
+            let component = (manager.init)(&key, &args);
+            manager.map.insert(key, WithArgs::new(component, args));
+        }
+        manager.dependencies = dependencies;
+        Ok(manager)
+    }
+
+    /// Async counterpart of [`init_ordered`](Self::init_ordered); keys at the
+    /// same depth are initialised concurrently.
+    #[cfg(feature = "std")]
+    pub async fn init_ordered_async(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        dependencies: impl IntoIterator<Item = (Key, Key)>,
+        init: FnInit,
+    ) -> Result<Self, DependencyError<Key>>
+    where
+        FnInit: AsyncFn(&Key, &Args) -> Comp,
+    {
+        let (entries, dependencies) = Self::resolve(entries, dependencies)?;
+        let mut manager = Self::new(Entries::new(), init, NoTeardown);
+        for level in levels(entries, &dependencies) {
+            let init = &manager.init;
+            let components =
+                join_bounded(level.iter().map(|(key, args)| (init)(key, args)), None).await;
+            for ((key, args), component) in level.into_iter().zip(components) {
+                manager.map.insert(key, WithArgs::new(component, args));
+            }
+        }
+        manager.dependencies = dependencies;
+        Ok(manager)
+    }
+
+    /// Like [`init_ordered`](Self::init_ordered), handing each init the
+    /// components of the keys it depends on.
+    pub fn init_resolved(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        dependencies: impl IntoIterator<Item = (Key, Key)>,
+        init: FnInit,
+    ) -> Result<Self, DependencyError<Key>>
+    where
+        FnInit: Fn(&Key, &Args, &Resolved<Key, Args, Comp>) -> Comp,
+    {
+        let (entries, dependencies) = Self::resolve(entries, dependencies)?;
+        let mut manager = Self::new(Entries::new(), init, NoTeardown);
+        for (key, args) in levels(entries, &dependencies).into_iter().flatten() {
+            let resolved = Resolved::new(&manager.map, dependencies.of(&key));
+            let component = (manager.init)(&key, &args, &resolved);
+            manager.map.insert(key, WithArgs::new(component, args));
+        }
+        manager.dependencies = dependencies;
+        Ok(manager)
+    }
+
+    /// Async counterpart of [`init_resolved`](Self::init_resolved); keys at
+    /// the same depth are initialised concurrently.
+    #[cfg(feature = "std")]
+    pub async fn init_resolved_async(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        dependencies: impl IntoIterator<Item = (Key, Key)>,
+        init: FnInit,
+    ) -> Result<Self, DependencyError<Key>>
+    where
+        FnInit: AsyncFn(&Key, &Args, &Resolved<Key, Args, Comp>) -> Comp,
+    {
+        let (entries, dependencies) = Self::resolve(entries, dependencies)?;
+        let mut manager = Self::new(Entries::new(), init, NoTeardown);
+        for level in levels(entries, &dependencies) {
+            let (init, map) = (&manager.init, &manager.map);
+            let components = join_bounded(
+                level.iter().map(|(key, args)| async {
+                    let resolved = Resolved::new(map, dependencies.of(key));
+                    (init)(key, args, &resolved).await
+                }),
+                None,
+            )
+            .await;
+            for ((key, args), component) in level.into_iter().zip(components) {
+                manager.map.insert(key, WithArgs::new(component, args));
+            }
+        }
+        manager.dependencies = dependencies;
+        Ok(manager)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn resolve(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        dependencies: impl IntoIterator<Item = (Key, Key)>,
+    ) -> Result<(HashMap<Key, Args>, Dependencies<Key>), DependencyError<Key>> {
+        let entries: HashMap<_, _> = entries.into_iter().collect();
+        let mut resolved = Dependencies::default();
+        for (key, dependency) in dependencies {
+            if !entries.contains_key(&dependency) {
+                return Err(DependencyError::Missing { key, dependency });
+            }
+            resolved.insert(key, dependency);
+        }
+        if let Some(cycle) = resolved.find_cycle() {
+            return Err(DependencyError::Cycle(cycle.into_iter().cloned().collect()));
+        }
+        Ok((entries, resolved))
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Declares that `key` depends on `dependency`, so the `reinit_all*`
+    /// operations rebuild `dependency` first. A declaration that would close a
+    /// cycle is rejected and not recorded.
+    pub fn add_dependency(&mut self, key: Key, dependency: Key) -> Result<(), DependencyError<Key>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+    {
+        if !self.map.contains_key(&dependency) {
+            return Err(DependencyError::Missing { key, dependency });
+        }
+        self.dependencies.try_insert(key, dependency)
+    }
+
+    /// Reinitialises every component of a map built with
+    /// [`init_resolved`](ComponentMap::init_resolved), one at a time in
+    /// dependency order, so each init sees the rebuilt components of its
+    /// dependencies.
+    pub fn reinit_all_resolved(&mut self) -> Vec<Keyed<Key, Comp>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args, &Resolved<Key, Args, Comp, Store>) -> Comp,
+    {
+        let keys: Vec<_> = prioritized(self.map.iter(), &self.dependencies, &self.priorities)
+            .into_iter()
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        keys.into_iter()
+            .filter_map(|key| {
+                let resolved = Resolved::new(&self.map, self.dependencies.of(&key));
+                let args = &self.map.get(&key)?.args;
+                let next = self
+                    .events
+                    .instrument
+                    .build(&key, args, || (self.init)(&key, args, &resolved));
+                let component = self.map.get_mut(&key)?;
+                let mut prev = component.replace_component(next);
+                self.teardown.teardown(&key, &mut prev);
+                self.events
+                    .emit(&key, ChangeKind::Reinitialized, Some(&*component));
+                Some(Keyed::new(key, prev))
+            })
+            .collect()
+    }
+
+    /// Keys `key` was declared to depend on.
+    pub fn dependencies<Q>(&self, key: &Q) -> &[Key]
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.dependencies.of(key)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_init_ordered_builds_dependencies_first() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let order_clone = order.clone();
+        let init = move |key: &&'static str, args: &usize| {
+            order_clone.lock().unwrap().push(*key);
+            Counter(*args)
+        };
+        let mut manager = ComponentMap::init_ordered(
+            [("client_b", 3), ("client_a", 2), ("transport", 1)],
+            [("client_a", "transport"), ("client_b", "client_a")],
+            init,
+        )
+        .unwrap();
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["transport", "client_a", "client_b"]
+        );
+        assert_eq!(manager.dependencies(&"client_a"), ["transport"]);
+
+        // Dependencies outrank priorities
+        manager.set_priority(&"client_b", 10);
+        order.lock().unwrap().clear();
+        manager.reinit_all().for_each(drop);
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["transport", "client_a", "client_b"]
+        );
+
+        assert_eq!(
+            manager.add_dependency("transport", "missing"),
+            Err(DependencyError::Missing {
+                key: "transport",
+                dependency: "missing"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_init_ordered_async_reports_missing_dependency() {
+        let init = async |_key: &&str, args: &usize| Counter(*args);
+        let result =
+            ComponentMap::init_ordered_async([("client", 1)], [("client", "transport")], init)
+                .await;
+        assert_eq!(
+            result.err(),
+            Some(DependencyError::Missing {
+                key: "client",
+                dependency: "transport"
+            })
+        );
+    }
+
+    #[test]
+    fn test_cycles_are_rejected() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let result = ComponentMap::init_ordered(
+            [("a", 1), ("b", 2), ("c", 3)],
+            [("a", "b"), ("b", "c"), ("c", "a")],
+            init,
+        );
+        let Err(DependencyError::Cycle(mut cycle)) = result else {
+            panic!("expected a cycle");
+        };
+        cycle.sort();
+        assert_eq!(cycle, vec!["a", "b", "c"]);
+
+        let mut manager =
+            ComponentMap::init_ordered([("a", 1), ("b", 2)], [("a", "b")], init).unwrap();
+        assert_eq!(
+            manager.add_dependency("b", "a"),
+            Err(DependencyError::Cycle(vec!["b", "a"]))
+        );
+        assert!(manager.dependencies(&"b").is_empty());
+        assert_eq!(
+            manager.add_dependency("a", "a").unwrap_err().to_string(),
+            "dependency cycle: \"a\" -> \"a\""
+        );
+        assert_eq!(manager.dependencies(&"a"), ["b"]);
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Client {
+        transport: usize,
+        market: usize,
+    }
+
+    #[tokio::test]
+    async fn test_init_resolved_sees_dependencies() {
+        let init =
+            async |_key: &&str, args: &usize, resolved: &Resolved<&str, usize, Client>| Client {
+                transport: resolved
+                    .get(&"transport")
+                    .map_or(*args, |parent| parent.transport),
+                market: *args,
+            };
+        let manager = ComponentMap::init_resolved_async(
+            [("transport", 1), ("client_a", 2), ("client_b", 3)],
+            [("client_a", "transport"), ("client_b", "transport")],
+            init,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            manager.get(&"client_b").map(|client| client.transport),
+            Some(1)
+        );
+
+        let init = |_key: &&str, args: &usize, resolved: &Resolved<&str, usize, Counter>| {
+            Counter(args + resolved.iter().map(|(_, parent)| parent.0).sum::<usize>())
+        };
+        let mut manager = ComponentMap::init_resolved(
+            [("transport", 1), ("client", 10)],
+            [("client", "transport")],
+            init,
+        )
+        .unwrap();
+        assert_eq!(manager.get(&"client"), Some(&Counter(11)));
+
+        manager.set_args(&"transport", 5);
+        manager.reinit_all_resolved();
+        assert_eq!(manager.get(&"client"), Some(&Counter(15)));
+    }
+}
@@ -0,0 +1,57 @@
+use core::fmt;
+use derive_more::Constructor;
+
+/// Init error tagged with the key whose initialisation failed.
+#[derive(Debug, Clone, PartialEq, Eq, Constructor)]
+pub struct KeyedError<Key, Error> {
+    pub key: Key,
+    pub error: Error,
+}
+
+impl<Key, Error> fmt::Display for KeyedError<Key, Error>
+where
+    Key: fmt::Debug,
+    Error: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to initialise {:?}: {}", self.key, self.error)
+    }
+}
+
+impl<Key, Error> core::error::Error for KeyedError<Key, Error>
+where
+    Key: fmt::Debug,
+    Error: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[derive(Debug)]
+    struct TestError;
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("connection refused")
+        }
+    }
+
+    impl std::error::Error for TestError {}
+
+    #[test]
+    fn test_display_and_source() {
+        let error = KeyedError::new("key1", TestError);
+
+        assert_eq!(
+            error.to_string(),
+            "failed to initialise \"key1\": connection refused"
+        );
+        assert_eq!(error.source().unwrap().to_string(), "connection refused");
+    }
+}
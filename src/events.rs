@@ -0,0 +1,419 @@
+use crate::WithArgs;
+#[cfg(feature = "std")]
+use crate::{ComponentMap, Instrument, KeyedStorage, Teardown, audit::AuditLog, stats::StatsTable};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use futures::{Stream, channel::mpsc};
+#[cfg(feature = "std")]
+use std::{sync::Arc, time::Instant};
+
+/// What happened to a key in a [`ChangeEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A new key was added to the map.
+    Inserted,
+    /// An existing key received a component built from new args.
+    Replaced,
+    /// An existing key was rebuilt from its current args.
+    Reinitialized,
+    Removed,
+    /// Init failed and the entry, if any, was left untouched.
+    Failed,
+}
+
+impl ChangeKind {
+    /// Kind of an insert that did or did not displace an existing entry.
+    pub(crate) fn upsert(replaced: bool) -> Self {
+        if replaced {
+            ChangeKind::Replaced
+        } else {
+            ChangeKind::Inserted
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent<Key> {
+    pub key: Key,
+    pub kind: ChangeKind,
+}
+
+/// Called with the entry after the change, or the removed entry for
+/// [`ChangeKind::Removed`], and the args and error of a failed init; returns
+/// `false` once it should no longer be notified.
+type Observer<Key, Args, Comp> = Box<
+    dyn FnMut(&Key, ChangeKind, Option<&WithArgs<Args, Comp>>, Option<Failure<'_, Args>>) -> bool
+        + Send
+        + Sync,
+>;
+
+/// The args an init failed from, and its error.
+pub(crate) type Failure<'a, Args> = (&'a Args, &'a dyn fmt::Debug);
+
+/// Sinks notified of every mutation of a [`ComponentMap`].
+pub(crate) struct Observers<Key, Args, Comp> {
+    observers: Vec<Observer<Key, Args, Comp>>,
+    /// Counters fed by one of the observers, once enabled with `with_stats`.
+    #[cfg(feature = "std")]
+    pub(crate) stats: Option<StatsTable<Key>>,
+    /// Log fed by one of the observers, once enabled with `with_audit_log`.
+    #[cfg(feature = "std")]
+    pub(crate) audit: Option<AuditLog<Key>>,
+    pub(crate) instrument: Instrumentation<Key, Args>,
+}
+
+impl<Key, Args, Comp> Observers<Key, Args, Comp> {
+    pub(crate) fn push(&mut self, observer: Observer<Key, Args, Comp>) {
+        self.observers.push(observer);
+    }
+
+    pub(crate) fn emit(
+        &mut self,
+        key: &Key,
+        kind: ChangeKind,
+        entry: Option<&WithArgs<Args, Comp>>,
+    ) {
+        self.notify(key, kind, entry, None);
+    }
+
+    /// Reports that the init of `key` from `args` failed with `error`.
+    pub(crate) fn emit_failed(&mut self, key: &Key, args: &Args, error: &dyn fmt::Debug) {
+        self.notify(key, ChangeKind::Failed, None, Some((args, error)));
+    }
+
+    fn notify(
+        &mut self,
+        key: &Key,
+        kind: ChangeKind,
+        entry: Option<&WithArgs<Args, Comp>>,
+        failure: Option<Failure<'_, Args>>,
+    ) {
+        #[cfg(feature = "std")]
+        if let Some(instrument) = &self.instrument.instrument {
+            match kind {
+                ChangeKind::Failed => instrument.on_failure(key),
+                ChangeKind::Removed => instrument.on_remove(key),
+                _ => {}
+            }
+        }
+        self.observers
+            .retain_mut(|observer| observer(key, kind, entry, failure));
+    }
+
+    /// Emits `kind` for a rebuild of `entry` if `result` succeeded, and the
+    /// failure of its args otherwise.
+    pub(crate) fn emit_result<T, Error>(
+        &mut self,
+        key: &Key,
+        result: &Result<T, Error>,
+        kind: ChangeKind,
+        entry: &WithArgs<Args, Comp>,
+    ) where
+        Error: fmt::Debug,
+    {
+        match result {
+            Ok(_) => self.emit(key, kind, Some(entry)),
+            Err(error) => self.emit_failed(key, &entry.args, error),
+        }
+    }
+}
+
+impl<Key, Args, Comp> Default for Observers<Key, Args, Comp> {
+    fn default() -> Self {
+        Self {
+            observers: Vec::new(),
+            #[cfg(feature = "std")]
+            stats: None,
+            #[cfg(feature = "std")]
+            audit: None,
+            instrument: Instrumentation::default(),
+        }
+    }
+}
+
+impl<Key, Args, Comp> fmt::Debug for Observers<Key, Args, Comp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observers")
+            .field("len", &self.observers.len())
+            .finish()
+    }
+}
+
+/// The [`Instrument`](crate::Instrument) set with `with_instrument`, which
+/// every build of a component reports to, whether it calls the init function
+/// or not.
+pub(crate) struct Instrumentation<Key, Args> {
+    #[cfg(feature = "std")]
+    pub(crate) instrument: Option<Arc<dyn Instrument<Key, Args> + Send + Sync>>,
+    #[cfg(not(feature = "std"))]
+    marker: PhantomData<fn(&Key, &Args)>,
+}
+
+impl<Key, Args> Instrumentation<Key, Args> {
+    /// Reports that a build of `key` is starting. The span owns what it needs,
+    /// so async builds can hold it across an await without borrowing the map.
+    pub(crate) fn start(&self, key: &Key, args: &Args) -> BuildSpan<Key, Args> {
+        #[cfg(feature = "std")]
+        {
+            let instrument = self.instrument.clone();
+            if let Some(instrument) = &instrument {
+                instrument.before_init(key, args);
+            }
+            BuildSpan {
+                instrument,
+                started: Instant::now(),
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = (key, args);
+            BuildSpan {
+                marker: PhantomData,
+            }
+        }
+    }
+
+    /// Runs the sync `build` of `key` inside a span.
+    pub(crate) fn build<Output>(
+        &self,
+        key: &Key,
+        args: &Args,
+        build: impl FnOnce() -> Output,
+    ) -> Output {
+        let span = self.start(key, args);
+        let output = build();
+        span.finish(key);
+        output
+    }
+}
+
+impl<Key, Args> fmt::Debug for Instrumentation<Key, Args> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Instrumentation").finish_non_exhaustive()
+    }
+}
+
+impl<Key, Args> Clone for Instrumentation<Key, Args> {
+    fn clone(&self) -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            instrument: self.instrument.clone(),
+            #[cfg(not(feature = "std"))]
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Key, Args> Default for Instrumentation<Key, Args> {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            instrument: None,
+            #[cfg(not(feature = "std"))]
+            marker: PhantomData,
+        }
+    }
+}
+
+/// A build in progress, started with [`Instrumentation::start`].
+pub(crate) struct BuildSpan<Key, Args> {
+    #[cfg(feature = "std")]
+    instrument: Option<Arc<dyn Instrument<Key, Args> + Send + Sync>>,
+    #[cfg(feature = "std")]
+    started: Instant,
+    #[cfg(not(feature = "std"))]
+    marker: PhantomData<fn(&Key, &Args)>,
+}
+
+impl<Key, Args> BuildSpan<Key, Args> {
+    /// Reports that the build of `key` returned, whether or not it succeeded.
+    pub(crate) fn finish(self, key: &Key) {
+        #[cfg(feature = "std")]
+        if let Some(instrument) = self.instrument {
+            instrument.after_init(key, self.started.elapsed());
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = key;
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Returns a stream receiving a [`ChangeEvent`] for every subsequent
+    /// mutation of the map. Dropping the stream unsubscribes it.
+    ///
+    /// At most `capacity` events (at least one) wait in the stream. Events
+    /// that happen while it is full are dropped, so a consumer that falls
+    /// behind misses them and should re-read the map rather than replay the
+    /// stream; the map itself never waits on a subscriber.
+    pub fn subscribe(
+        &mut self,
+        capacity: usize,
+    ) -> impl Stream<Item = ChangeEvent<Key>> + use<Key, Args, Comp, FnInit, FnDrop, Store>
+    where
+        Key: Clone + Send + 'static,
+    {
+        // The channel holds one message per sender on top of its buffer
+        let (mut sender, receiver) = mpsc::channel(capacity.saturating_sub(1));
+        self.events.push(Box::new(move |key: &Key, kind, _, _| {
+            let event = ChangeEvent {
+                key: key.clone(),
+                kind,
+            };
+            match sender.try_send(event) {
+                Ok(()) => true,
+                Err(error) => error.is_full(),
+            }
+        }));
+        receiver
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use futures::{FutureExt, StreamExt};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    fn drain<Key>(
+        events: &mut (impl Stream<Item = ChangeEvent<Key>> + Unpin),
+    ) -> Vec<(Key, ChangeKind)> {
+        std::iter::from_fn(|| events.next().now_or_never().flatten())
+            .map(|event| (event.key, event.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_subscribe_sync_mutations() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1)], init);
+        let mut events = manager.subscribe(16);
+
+        manager.update([("key1", 2), ("key2", 3)]).for_each(drop);
+        manager.reinit([&"key1"]).for_each(drop);
+        manager.remove(&"key2");
+
+        assert_eq!(
+            drain(&mut events),
+            vec![
+                ("key1", ChangeKind::Replaced),
+                ("key2", ChangeKind::Inserted),
+                ("key1", ChangeKind::Reinitialized),
+                ("key2", ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_many_and_drain_apply_without_consuming_results() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1), ("key2", 2), ("key3", 3)], init);
+        let mut events = manager.subscribe(16);
+
+        let _ = manager.remove_many(["key1"]);
+        assert!(!manager.contains_key(&"key1"));
+
+        let mut drained = manager.drain();
+        assert!(drained.next().is_some());
+        drop(drained);
+        assert!(manager.is_empty());
+
+        let removed = drain(&mut events)
+            .into_iter()
+            .filter(|(_, kind)| *kind == ChangeKind::Removed);
+        assert_eq!(removed.count(), 3);
+    }
+
+    #[test]
+    fn test_subscribe_reports_failures() {
+        let init = |_key: &&str, args: &usize| {
+            if *args == 0 {
+                Err(TestError)
+            } else {
+                Ok(Counter(*args))
+            }
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1)], init).unwrap();
+        let mut events = manager.subscribe(16);
+
+        manager
+            .try_update([("key1", 0), ("key2", 2)])
+            .for_each(drop);
+
+        assert_eq!(
+            drain(&mut events),
+            vec![("key1", ChangeKind::Failed), ("key2", ChangeKind::Inserted)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_async_mutations() {
+        let init = |_key: &&str, args: &usize| {
+            let value = *args;
+            async move { Counter(value) }
+        };
+        let mut manager = ComponentMap::init_async([("key1", 1)], init).await;
+        let mut events = manager.subscribe(16);
+
+        manager.reinit_all_async().await.for_each(drop);
+        manager.update_async([("key2", 2)]).await.for_each(drop);
+        manager.clear_async().await;
+
+        let mut received = drain(&mut events);
+        received[2..].sort_by_key(|(key, _)| *key);
+        assert_eq!(
+            received,
+            vec![
+                ("key1", ChangeKind::Reinitialized),
+                ("key2", ChangeKind::Inserted),
+                ("key1", ChangeKind::Removed),
+                ("key2", ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_subscriber_misses_events_until_drained() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1)], init);
+        let mut events = manager.subscribe(2);
+
+        manager
+            .update([("key2", 2), ("key3", 3), ("key4", 4)])
+            .for_each(drop);
+        assert_eq!(
+            drain(&mut events),
+            vec![
+                ("key2", ChangeKind::Inserted),
+                ("key3", ChangeKind::Inserted)
+            ]
+        );
+
+        manager.remove(&"key4");
+        assert_eq!(drain(&mut events), vec![("key4", ChangeKind::Removed)]);
+        assert_eq!(manager.events.observers.len(), 1);
+    }
+
+    #[test]
+    fn test_dropped_subscriber_is_pruned() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1)], init);
+        drop(manager.subscribe(16));
+
+        manager.reinit_all().for_each(drop);
+
+        assert_eq!(manager.events.observers.len(), 0);
+    }
+}
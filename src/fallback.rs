@@ -0,0 +1,106 @@
+use core::ops::{Deref, DerefMut};
+
+/// Which initializer of a [`with_fallback`] chain produced a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitSource {
+    Primary,
+    Fallback,
+}
+
+/// Component tagged with the initializer that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sourced<Comp> {
+    pub component: Comp,
+    pub source: InitSource,
+}
+
+impl<Comp> Deref for Sourced<Comp> {
+    type Target = Comp;
+
+    fn deref(&self) -> &Comp {
+        &self.component
+    }
+}
+
+impl<Comp> DerefMut for Sourced<Comp> {
+    fn deref_mut(&mut self) -> &mut Comp {
+        &mut self.component
+    }
+}
+
+/// Chains two fallible initializers: `fallback` is tried whenever `primary` fails.
+///
+/// The resulting init function produces [`Sourced`] components, so the map
+/// records which initializer built each entry across reinits. If both fail,
+/// the error of `fallback` is returned.
+pub fn with_fallback<Key, Args, Comp, Error>(
+    primary: impl Fn(&Key, &Args) -> Result<Comp, Error> + Clone,
+    fallback: impl Fn(&Key, &Args) -> Result<Comp, Error> + Clone,
+) -> impl Fn(&Key, &Args) -> Result<Sourced<Comp>, Error> + Clone {
+    move |key: &Key, args: &Args| {
+        (primary)(key, args)
+            .map(|component| Sourced {
+                component,
+                source: InitSource::Primary,
+            })
+            .or_else(|_| {
+                (fallback)(key, args).map(|component| Sourced {
+                    component,
+                    source: InitSource::Fallback,
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ComponentMap;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Connection {
+        Live(usize),
+        Stub,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError(&'static str);
+
+    #[test]
+    fn test_with_fallback() {
+        let primary = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError("primary")),
+            port => Ok(Connection::Live(port)),
+        };
+        let fallback = |_key: &&str, _args: &usize| Ok(Connection::Stub);
+
+        let mut manager = ComponentMap::try_init(
+            [("key1", 80), ("key2", 0)],
+            with_fallback(primary, fallback),
+        )
+        .unwrap();
+
+        let key1 = manager.get(&"key1").unwrap();
+        assert_eq!(**key1, Connection::Live(80));
+        assert_eq!(key1.source, InitSource::Primary);
+
+        let key2 = manager.get(&"key2").unwrap();
+        assert_eq!(**key2, Connection::Stub);
+        assert_eq!(key2.source, InitSource::Fallback);
+
+        manager.set_args(&"key2", 443);
+        let _: Vec<_> = manager.try_reinit(["key2"]).collect();
+        assert_eq!(manager.get(&"key2").unwrap().source, InitSource::Primary);
+    }
+
+    #[test]
+    fn test_with_fallback_both_fail() {
+        let primary = |_key: &&str, _args: &usize| Err::<Connection, _>(TestError("primary"));
+        let fallback = |_key: &&str, _args: &usize| Err::<Connection, _>(TestError("fallback"));
+
+        let result = ComponentMap::try_init([("key1", 80)], with_fallback(primary, fallback));
+
+        assert_eq!(result.err().unwrap().error, TestError("fallback"));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{ComponentMap, KeyedStorage, Lookup, ReinitOutcome, Teardown, WithArgs};
+use core::{
+    borrow::Borrow,
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// Generations are drawn from one global counter, so an entry that is removed
+// and re-added never reuses a generation a caller may still hold.
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) fn next() -> u64 {
+    GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Error returned by [`try_update_if`](ComponentMap::try_update_if) when the
+/// entry changed since the caller read its generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationMismatch {
+    pub expected: u64,
+    /// Current generation, or `None` if the key is gone.
+    pub actual: Option<u64>,
+}
+
+impl fmt::Display for GenerationMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.actual {
+            Some(actual) => write!(f, "expected generation {}, found {actual}", self.expected),
+            None => write!(f, "expected generation {}, entry is gone", self.expected),
+        }
+    }
+}
+
+impl core::error::Error for GenerationMismatch {}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Generation of the entry for `key`, which changes whenever its component
+    /// is rebuilt or its args are replaced.
+    pub fn generation<Q>(&self, key: &Q) -> Option<u64>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.map.get(key).map(WithArgs::generation)
+    }
+
+    /// Like [`try_update`](Self::try_update) for a single key, applied only if
+    /// the entry is still at `expected_generation`.
+    ///
+    /// A mismatch leaves the map untouched, so a controller working from a
+    /// stale read finds out instead of overwriting a newer update.
+    #[allow(clippy::type_complexity)]
+    pub fn try_update_if<Error>(
+        &mut self,
+        key: Key,
+        expected_generation: u64,
+        args: Args,
+    ) -> Result<ReinitOutcome<WithArgs<Args, Comp>, Error>, GenerationMismatch>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: fmt::Debug,
+    {
+        let actual = self.generation(&key);
+        if actual != Some(expected_generation) {
+            return Err(GenerationMismatch {
+                expected: expected_generation,
+                actual,
+            });
+        }
+
+        Ok(self.try_update_entry(key, args).value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[test]
+    fn test_try_update_if_detects_lost_updates() {
+        let init = |_key: &&str, args: &usize| Ok::<_, TestError>(Counter(*args));
+        let mut manager = ComponentMap::try_init([("key1", 1)], init).unwrap();
+
+        let seen_by_first = manager.generation(&"key1").unwrap();
+        let seen_by_second = seen_by_first;
+
+        let outcome = manager.try_update_if("key1", seen_by_first, 2).unwrap();
+        assert_eq!(outcome.replaced().map(|prev| prev.args), Some(1));
+
+        let current = manager.generation(&"key1");
+        assert_ne!(current, Some(seen_by_first));
+        assert_eq!(
+            manager.try_update_if("key1", seen_by_second, 3).err(),
+            Some(GenerationMismatch {
+                expected: seen_by_second,
+                actual: current,
+            })
+        );
+        assert_eq!(manager.get_args(&"key1"), Some(&2));
+
+        manager.remove(&"key1");
+        assert!(manager.try_update_if("key1", seen_by_first, 4).is_err());
+    }
+
+    #[test]
+    fn test_generation_changes_on_reinit_and_set_args() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1)], init);
+
+        let initial = manager.generation(&"key1");
+        manager.reinit([&"key1"]).for_each(drop);
+        let reinitialised = manager.generation(&"key1");
+        manager.set_args(&"key1", 2);
+
+        assert_ne!(initial, reinitialised);
+        assert_ne!(reinitialised, manager.generation(&"key1"));
+        assert_eq!(manager.generation(&"missing"), None);
+    }
+}
@@ -0,0 +1,377 @@
+use crate::{ChangeKind, ComponentMap, Teardown, WithArgs};
+use futures::future::{self, Either};
+use futures_timer::Delay;
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fmt,
+    future::Future,
+    time::Instant,
+};
+use tokio::sync::{mpsc, oneshot};
+
+// The manager is owned by a single background task and only reachable through
+// commands, so callers never see a lock. Commands are handled one at a time;
+// replaced components are finalised with the sync teardown.
+
+/// Error returned by [`ComponentMapHandle`] once the background task has stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandleClosed;
+
+impl fmt::Display for HandleClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("component map task has stopped")
+    }
+}
+
+impl std::error::Error for HandleClosed {}
+
+enum Command<Key, Args, Comp> {
+    Get {
+        key: Key,
+        reply: oneshot::Sender<Option<Comp>>,
+    },
+    Update {
+        key: Key,
+        args: Args,
+        reply: oneshot::Sender<Option<WithArgs<Args, Comp>>>,
+    },
+    Reinit {
+        key: Key,
+        reply: oneshot::Sender<Option<Comp>>,
+    },
+    Debounce {
+        key: Key,
+        args: Args,
+    },
+    Shutdown {
+        reply: oneshot::Sender<()>,
+    },
+}
+
+/// Cloneable handle to a [`ComponentMap`] running on its own tokio task.
+pub struct ComponentMapHandle<Key, Args, Comp> {
+    commands: mpsc::Sender<Command<Key, Args, Comp>>,
+}
+
+impl<Key, Args, Comp> Clone for ComponentMapHandle<Key, Args, Comp> {
+    fn clone(&self) -> Self {
+        Self {
+            commands: self.commands.clone(),
+        }
+    }
+}
+
+impl<Key, Args, Comp> fmt::Debug for ComponentMapHandle<Key, Args, Comp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComponentMapHandle")
+            .field("closed", &self.commands.is_closed())
+            .finish()
+    }
+}
+
+impl<Key, Args, Comp> ComponentMapHandle<Key, Args, Comp>
+where
+    Key: Send + 'static,
+    Args: Send + 'static,
+    Comp: Send + 'static,
+{
+    /// Moves `map` onto a new task that serves commands from a channel holding
+    /// up to `capacity` pending requests.
+    ///
+    /// The task stops once every handle is dropped or on [`shutdown`](Self::shutdown),
+    /// dropping the map.
+    pub fn spawn<FnInit, FnDrop, Fut>(
+        map: ComponentMap<Key, Args, Comp, FnInit, FnDrop>,
+        capacity: usize,
+    ) -> Self
+    where
+        Key: Eq + std::hash::Hash,
+        Comp: Clone,
+        FnInit: Fn(&Key, &Args) -> Fut + Send + 'static,
+        Fut: Future<Output = Comp> + Send + 'static,
+        FnDrop: Teardown<Key, Comp> + Send + 'static,
+    {
+        let (commands, receiver) = mpsc::channel(capacity.max(1));
+        tokio::spawn(serve(map, receiver));
+        Self { commands }
+    }
+
+    pub async fn get_cloned(&self, key: Key) -> Result<Option<Comp>, HandleClosed> {
+        self.request(|reply| Command::Get { key, reply }).await
+    }
+
+    /// Initialises a component from `args` and inserts it under `key`,
+    /// returning the entry it replaced.
+    pub async fn update(
+        &self,
+        key: Key,
+        args: Args,
+    ) -> Result<Option<WithArgs<Args, Comp>>, HandleClosed> {
+        self.request(|reply| Command::Update { key, args, reply })
+            .await
+    }
+
+    /// Reinitialises the component for `key`, returning the previous one, or
+    /// `None` if the key is missing.
+    pub async fn reinit(&self, key: Key) -> Result<Option<Comp>, HandleClosed> {
+        self.request(|reply| Command::Reinit { key, reply }).await
+    }
+
+    /// Queues an update of `key` to `args`, applied once
+    /// [`ComponentMapConfig::debounce`](crate::ComponentMapConfig::debounce)
+    /// has passed since the first update queued for the key. Later updates in
+    /// that window replace the queued args, so only the latest are initialised.
+    /// An [`update`](Self::update) or [`reinit`](Self::reinit) of the key
+    /// discards the queued args.
+    ///
+    /// Lookups keep returning the current component until the update lands.
+    pub async fn update_debounced(&self, key: Key, args: Args) -> Result<(), HandleClosed> {
+        self.commands
+            .send(Command::Debounce { key, args })
+            .await
+            .map_err(|_| HandleClosed)
+    }
+
+    /// Stops the task after the commands already queued, dropping the map.
+    pub async fn shutdown(&self) -> Result<(), HandleClosed> {
+        self.request(|reply| Command::Shutdown { reply }).await
+    }
+
+    async fn request<T>(
+        &self,
+        command: impl FnOnce(oneshot::Sender<T>) -> Command<Key, Args, Comp>,
+    ) -> Result<T, HandleClosed> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(command(reply))
+            .await
+            .map_err(|_| HandleClosed)?;
+        response.await.map_err(|_| HandleClosed)
+    }
+}
+
+async fn serve<Key, Args, Comp, FnInit, FnDrop, Fut>(
+    mut map: ComponentMap<Key, Args, Comp, FnInit, FnDrop>,
+    mut commands: mpsc::Receiver<Command<Key, Args, Comp>>,
+) where
+    Key: Eq + std::hash::Hash,
+    Comp: Clone,
+    FnInit: Fn(&Key, &Args) -> Fut,
+    Fut: Future<Output = Comp>,
+    FnDrop: Teardown<Key, Comp>,
+{
+    // Debounced updates wait here, latest args per key, until the window
+    // opened by the first update of that key elapses
+    let mut debounced: HashMap<Key, (Instant, Args)> = HashMap::new();
+
+    loop {
+        let deadline = debounced.values().map(|(at, _)| *at).min();
+        let command = match deadline {
+            Some(at) => {
+                let window = Delay::new(at.saturating_duration_since(Instant::now()));
+                match future::select(Box::pin(commands.recv()), window).await {
+                    Either::Left((command, _)) => command,
+                    Either::Right(_) => {
+                        let due = take_due(&mut debounced, Instant::now());
+                        apply_debounced(&mut map, due).await;
+                        continue;
+                    }
+                }
+            }
+            None => commands.recv().await,
+        };
+        let Some(command) = command else {
+            break;
+        };
+
+        match command {
+            Command::Get { key, reply } => {
+                let _ = reply.send(map.get(&key).cloned());
+            }
+            Command::Update { key, args, reply } => {
+                debounced.remove(&key);
+                let span = map.events.instrument.start(&key, &args);
+                let component = (map.init)(&key, &args).await;
+                span.finish(&key);
+                let _ = reply.send(map.insert_component(key, args, component));
+            }
+            Command::Reinit { key, reply } => {
+                debounced.remove(&key);
+                let next = match map.map.get(&key) {
+                    Some(component) => {
+                        let span = map.events.instrument.start(&key, &component.args);
+                        let next = (map.init)(&key, &component.args).await;
+                        span.finish(&key);
+                        Some(next)
+                    }
+                    None => None,
+                };
+                let prev = next.and_then(|next| {
+                    let component = map.map.get_mut(&key)?;
+                    let mut prev = component.replace_component(next);
+                    map.teardown.teardown(&key, &mut prev);
+                    map.events
+                        .emit(&key, ChangeKind::Reinitialized, Some(&*component));
+                    Some(prev)
+                });
+                let _ = reply.send(prev);
+            }
+            Command::Debounce { key, args } => {
+                let window = map.config.debounce.unwrap_or_default();
+                match debounced.entry(key) {
+                    Entry::Occupied(mut queued) => queued.get_mut().1 = args,
+                    Entry::Vacant(queued) => {
+                        queued.insert((Instant::now() + window, args));
+                    }
+                }
+            }
+            Command::Shutdown { reply } => {
+                apply_debounced(&mut map, debounced).await;
+                drop(map);
+                let _ = reply.send(());
+                return;
+            }
+        }
+    }
+
+    apply_debounced(&mut map, debounced).await;
+}
+
+/// Splits off the debounced updates whose window has elapsed by `now`.
+fn take_due<Key, Args>(
+    debounced: &mut HashMap<Key, (Instant, Args)>,
+    now: Instant,
+) -> HashMap<Key, (Instant, Args)>
+where
+    Key: Eq + std::hash::Hash,
+{
+    let (due, pending) = std::mem::take(debounced)
+        .into_iter()
+        .partition(|(_, (at, _))| *at <= now);
+    *debounced = pending;
+    due
+}
+
+async fn apply_debounced<Key, Args, Comp, FnInit, FnDrop, Fut>(
+    map: &mut ComponentMap<Key, Args, Comp, FnInit, FnDrop>,
+    debounced: HashMap<Key, (Instant, Args)>,
+) where
+    Key: Eq + std::hash::Hash,
+    FnInit: Fn(&Key, &Args) -> Fut,
+    Fut: Future<Output = Comp>,
+    FnDrop: Teardown<Key, Comp>,
+{
+    for (key, (_, args)) in debounced {
+        let span = map.events.instrument.start(&key, &args);
+        let component = (map.init)(&key, &args).await;
+        span.finish(&key);
+        map.insert_component(key, args, component);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ComponentMapConfig;
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    fn init(_key: &&'static str, args: &usize) -> impl Future<Output = Counter> + Send + use<> {
+        let value = *args;
+        async move { Counter(value) }
+    }
+
+    #[tokio::test]
+    async fn test_handle_commands() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let manager = ComponentMap::init_async([("key1", 1)], init)
+            .await
+            .with_teardown(move |key: &&str, _: &mut Counter| {
+                closed_clone.lock().unwrap().push(*key)
+            });
+        let handle = ComponentMapHandle::spawn(manager, 8);
+        let other = handle.clone();
+
+        assert_eq!(handle.get_cloned("key1").await, Ok(Some(Counter(1))));
+        assert_eq!(handle.reinit("key1").await, Ok(Some(Counter(1))));
+        assert_eq!(handle.reinit("key2").await, Ok(None));
+
+        let prev = other.update("key1", 5).await.unwrap();
+        assert_eq!(prev.map(|prev| prev.component), Some(Counter(1)));
+        assert!(other.update("key2", 2).await.unwrap().is_none());
+        assert_eq!(handle.get_cloned("key2").await, Ok(Some(Counter(2))));
+
+        handle.shutdown().await.unwrap();
+        assert_eq!(other.get_cloned("key1").await, Err(HandleClosed));
+
+        let mut closed = closed.lock().unwrap().clone();
+        closed.sort();
+        assert_eq!(closed, vec!["key1", "key1", "key1", "key2"]);
+    }
+
+    #[tokio::test]
+    async fn test_debounced_updates_are_coalesced() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&'static str, args: &usize| {
+            calls_clone.fetch_add(1, Ordering::Relaxed);
+            let value = *args;
+            async move { Counter(value) }
+        };
+        let manager = ComponentMap::init_async([("key1", 1)], init)
+            .await
+            .with_config(ComponentMapConfig::default().debounce(Duration::from_millis(20)));
+        calls.store(0, Ordering::Relaxed);
+        let handle = ComponentMapHandle::spawn(manager, 8);
+
+        for args in 2..=4 {
+            handle.update_debounced("key1", args).await.unwrap();
+        }
+        handle.update_debounced("key2", 7).await.unwrap();
+        assert_eq!(handle.get_cloned("key1").await, Ok(Some(Counter(1))));
+
+        Delay::new(Duration::from_millis(40)).await;
+        assert_eq!(handle.get_cloned("key1").await, Ok(Some(Counter(4))));
+        assert_eq!(handle.get_cloned("key2").await, Ok(Some(Counter(7))));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        // Pending updates are applied before the task stops
+        handle.update_debounced("key1", 5).await.unwrap();
+        handle.shutdown().await.unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_debounce_windows_are_per_key() {
+        let manager = ComponentMap::init_async([("key1", 1), ("key2", 2), ("key3", 3)], init)
+            .await
+            .with_config(ComponentMapConfig::default().debounce(Duration::from_millis(100)));
+        let handle = ComponentMapHandle::spawn(manager, 8);
+
+        handle.update_debounced("key1", 10).await.unwrap();
+        handle.update_debounced("key3", 30).await.unwrap();
+        Delay::new(Duration::from_millis(60)).await;
+        handle.update_debounced("key2", 20).await.unwrap();
+        // An explicit update wins over the args still queued for the key
+        assert!(handle.update("key3", 31).await.unwrap().is_some());
+
+        Delay::new(Duration::from_millis(60)).await;
+        assert_eq!(handle.get_cloned("key1").await, Ok(Some(Counter(10))));
+        assert_eq!(handle.get_cloned("key2").await, Ok(Some(Counter(2))));
+        assert_eq!(handle.get_cloned("key3").await, Ok(Some(Counter(31))));
+
+        Delay::new(Duration::from_millis(80)).await;
+        assert_eq!(handle.get_cloned("key2").await, Ok(Some(Counter(20))));
+        assert_eq!(handle.get_cloned("key3").await, Ok(Some(Counter(31))));
+    }
+}
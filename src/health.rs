@@ -0,0 +1,189 @@
+use crate::{ComponentMap, Keyed, KeyedStorage, Teardown, WithArgs};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+use futures::future::{BoxFuture, join_all};
+
+/// Result of checking a single component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Health {
+    Healthy,
+    /// Usable, but not at full capacity.
+    Degraded(String),
+    Unhealthy(String),
+}
+
+impl Health {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Health::Healthy)
+    }
+
+    fn severity(&self) -> u8 {
+        match self {
+            Health::Healthy => 0,
+            Health::Degraded(_) => 1,
+            Health::Unhealthy(_) => 2,
+        }
+    }
+}
+
+/// Per-key health of a map, from [`health_report`](ComponentMap::health_report).
+#[derive(Debug)]
+pub struct HealthReport<'a, Key> {
+    pub entries: Vec<Keyed<&'a Key, Health>>,
+}
+
+impl<Key> HealthReport<'_, Key> {
+    /// The worst health of any entry; an empty map is healthy.
+    pub fn overall(&self) -> &Health {
+        self.entries
+            .iter()
+            .map(|entry| &entry.value)
+            .max_by_key(|health| health.severity())
+            .unwrap_or(&Health::Healthy)
+    }
+
+    pub fn unhealthy(&self) -> impl Iterator<Item = &Keyed<&Key, Health>> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.value.is_healthy())
+    }
+}
+
+type CheckFn<Key, Comp> = Box<dyn Fn(&Key, &Comp) -> Health + Send + Sync>;
+type AsyncCheckFn<Key, Comp> =
+    Box<dyn for<'a> Fn(&'a Key, &'a Comp) -> BoxFuture<'a, Health> + Send + Sync>;
+
+/// The health check registered on a [`ComponentMap`], if any.
+#[derive(Default)]
+pub(crate) enum HealthCheck<Key, Comp> {
+    #[default]
+    None,
+    Sync(CheckFn<Key, Comp>),
+    Async(AsyncCheckFn<Key, Comp>),
+}
+
+impl<Key, Comp> fmt::Debug for HealthCheck<Key, Comp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HealthCheck::None => "None",
+            HealthCheck::Sync(_) => "Sync",
+            HealthCheck::Async(_) => "Async",
+        })
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Registers the check run by [`health_report`](Self::health_report),
+    /// replacing any previous one.
+    pub fn set_health_check(
+        &mut self,
+        check: impl Fn(&Key, &Comp) -> Health + Send + Sync + 'static,
+    ) {
+        self.health = HealthCheck::Sync(Box::new(check));
+    }
+
+    /// Registers an async check, run by [`health_report_async`](Self::health_report_async).
+    pub fn set_async_health_check(
+        &mut self,
+        check: impl for<'a> Fn(&'a Key, &'a Comp) -> BoxFuture<'a, Health> + Send + Sync + 'static,
+    ) {
+        self.health = HealthCheck::Async(Box::new(check));
+    }
+
+    /// Checks every component with the registered sync check, or returns
+    /// `None` if there is none.
+    pub fn health_report(&self) -> Option<HealthReport<'_, Key>> {
+        let HealthCheck::Sync(check) = &self.health else {
+            return None;
+        };
+
+        let entries = self
+            .map
+            .iter()
+            .map(|(key, component)| Keyed::new(key, check(key, &component.component)))
+            .collect();
+        Some(HealthReport { entries })
+    }
+
+    /// Checks every component concurrently with the registered check, sync or
+    /// async, or returns `None` if there is none.
+    pub async fn health_report_async(&self) -> Option<HealthReport<'_, Key>> {
+        let check = match &self.health {
+            HealthCheck::None => return None,
+            HealthCheck::Sync(_) => return self.health_report(),
+            HealthCheck::Async(check) => check,
+        };
+
+        let (keys, checks): (Vec<_>, Vec<_>) = self
+            .map
+            .iter()
+            .map(|(key, component)| (key, check(key, &component.component)))
+            .unzip();
+        let entries = keys
+            .into_iter()
+            .zip(join_all(checks).await)
+            .map(|(key, health)| Keyed::new(key, health))
+            .collect();
+        Some(HealthReport { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, string::ToString, vec, vec::Vec};
+    use futures::FutureExt;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Conn {
+        latency_ms: usize,
+    }
+
+    fn classify(latency_ms: usize) -> Health {
+        match latency_ms {
+            0..100 => Health::Healthy,
+            100..1000 => Health::Degraded(format!("{latency_ms}ms")),
+            _ => Health::Unhealthy("timed out".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_health_report() {
+        let init = |_key: &&str, args: &usize| Conn { latency_ms: *args };
+        let mut manager = ComponentMap::init([("a", 10), ("b", 250)], init);
+        assert!(manager.health_report().is_none());
+
+        manager.set_health_check(|_key, conn: &Conn| classify(conn.latency_ms));
+        let report = manager.health_report().unwrap();
+
+        assert_eq!(report.overall(), &Health::Degraded("250ms".to_string()));
+        let unhealthy: Vec<_> = report.unhealthy().map(|entry| *entry.key).collect();
+        assert_eq!(unhealthy, vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn test_async_health_report() {
+        let init = |_key: &&str, args: &usize| Conn { latency_ms: *args };
+        let mut manager = ComponentMap::init([("a", 10), ("b", 5000)], init);
+
+        manager.set_async_health_check(|_key, conn: &Conn| {
+            async move {
+                tokio::task::yield_now().await;
+                classify(conn.latency_ms)
+            }
+            .boxed()
+        });
+        assert!(manager.health_report().is_none());
+
+        let report = manager.health_report_async().await.unwrap();
+        assert_eq!(report.entries.len(), 2);
+        assert_eq!(
+            report.overall(),
+            &Health::Unhealthy("timed out".to_string())
+        );
+    }
+}
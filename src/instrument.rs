@@ -0,0 +1,215 @@
+use crate::{ComponentMap, KeyedStorage, Teardown, WithArgs};
+use std::{sync::Arc, time::Duration};
+
+/// Observability hooks attached to a [`ComponentMap`] with
+/// [`with_instrument`](ComponentMap::with_instrument), for plugging in a
+/// tracing or metrics backend.
+///
+/// Every method defaults to doing nothing. Hooks take `&self` and may be
+/// called from concurrent inits, so backends keep their state behind
+/// interior mutability, as metrics registries already do.
+pub trait Instrument<Key, Args> {
+    /// Called right before every init, reinit, or rebuild of `key`.
+    fn before_init(&self, _key: &Key, _args: &Args) {}
+
+    /// Called once a build started by `before_init` returns, whether or not
+    /// it succeeded.
+    fn after_init(&self, _key: &Key, _elapsed: Duration) {}
+
+    /// Init failed for `key`, leaving its entry, if any, untouched.
+    fn on_failure(&self, _key: &Key) {}
+
+    fn on_remove(&self, _key: &Key) {}
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Reports every build of a component to `instrument`, including those
+    /// made by `rebuild` functions rather than the init function, along with
+    /// failures and removals. Async builds are timed until their future
+    /// completes. Replaces any previous instrument.
+    pub fn with_instrument(
+        mut self,
+        instrument: impl Instrument<Key, Args> + Send + Sync + 'static,
+    ) -> Self {
+        self.events.instrument.instrument = Some(Arc::new(instrument));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LazyComponentMap;
+    use futures_timer::Delay;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Instrument<&'static str, usize> for Recorder {
+        fn before_init(&self, key: &&'static str, args: &usize) {
+            self.0.lock().unwrap().push(format!("before {key} {args}"));
+        }
+
+        fn after_init(&self, key: &&'static str, _elapsed: Duration) {
+            self.0.lock().unwrap().push(format!("after {key}"));
+        }
+
+        fn on_failure(&self, key: &&'static str) {
+            self.0.lock().unwrap().push(format!("failure {key}"));
+        }
+
+        fn on_remove(&self, key: &&'static str) {
+            self.0.lock().unwrap().push(format!("remove {key}"));
+        }
+    }
+
+    #[test]
+    fn test_instrument_sees_inits_failures_and_removals() {
+        let init = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError),
+            args => Ok(Counter(args)),
+        };
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = ComponentMap::try_init([("key1", 1)], init)
+            .unwrap()
+            .with_instrument(Recorder(log.clone()));
+
+        manager
+            .try_update([("key2", 2), ("key3", 0)])
+            .for_each(drop);
+        manager.remove(&"key1");
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "before key2 2",
+                "after key2",
+                "before key3 0",
+                "after key3",
+                "failure key3",
+                "remove key1",
+            ]
+        );
+    }
+
+    #[cfg(feature = "dashmap")]
+    #[tokio::test]
+    async fn test_instrument_carries_over_to_concurrent_map() {
+        let init = async |_key: &&str, args: &usize| Counter(*args);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let manager = ComponentMap::init_async([("key1", 1)], init)
+            .await
+            .with_instrument(Recorder(log.clone()));
+        let concurrent = crate::ConcurrentComponentMap::from(manager);
+
+        concurrent.update_async("key2", 2).await;
+        concurrent.reinit_key_async(&"key1").await;
+        concurrent.remove(&"key2");
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "before key2 2",
+                "after key2",
+                "before key1 1",
+                "after key1",
+                "remove key2",
+            ]
+        );
+    }
+
+    #[cfg(feature = "arc-swap")]
+    #[test]
+    fn test_instrument_carries_over_to_swap_map() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let manager =
+            ComponentMap::init([("key1", 1)], init).with_instrument(Recorder(log.clone()));
+        let swap = crate::SwapComponentMap::from(manager);
+
+        swap.update([("key2", 2)]);
+        swap.remove(&"key1");
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["before key2 2", "after key2", "remove key1"]
+        );
+    }
+
+    #[test]
+    fn test_instrument_sees_rebuilds_and_lazy_inits() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut manager =
+            ComponentMap::init([("key1", 1)], init).with_instrument(Recorder(log.clone()));
+
+        let rebuild = |_key: &&str, args: &usize, prev: Option<&Counter>| {
+            Counter(args + prev.map_or(0, |prev| prev.0))
+        };
+        manager.reinit_from_prev([&"key1"], rebuild).for_each(drop);
+        manager
+            .update_from_prev([("key2", 2)], rebuild)
+            .for_each(drop);
+
+        let mut lazy = LazyComponentMap::new([("key3", 3)], |_key: &&str, args: &usize| {
+            Ok::<_, TestError>(Counter(*args))
+        })
+        .with_instrument(Recorder(log.clone()));
+        lazy.try_get_or_init(&"key3").unwrap().unwrap();
+
+        assert_eq!(manager.get(&"key1"), Some(&Counter(2)));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "before key1 1",
+                "after key1",
+                "before key2 2",
+                "after key2",
+                "before key3 3",
+                "after key3",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_instrument_times_async_inits_until_they_complete() {
+        struct Timer(Arc<Mutex<Vec<Duration>>>);
+
+        impl Instrument<&'static str, u64> for Timer {
+            fn after_init(&self, _key: &&'static str, elapsed: Duration) {
+                self.0.lock().unwrap().push(elapsed);
+            }
+        }
+
+        let init = async |_key: &&str, millis: &u64| {
+            Delay::new(Duration::from_millis(*millis)).await;
+            Counter(*millis as usize)
+        };
+        let elapsed = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = ComponentMap::init_async([("key1", 20)], init)
+            .await
+            .with_instrument(Timer(elapsed.clone()));
+
+        manager.reinit_async([&"key1"]).await.for_each(drop);
+        manager.get_or_init_async("key2", 20).await;
+
+        let elapsed = elapsed.lock().unwrap();
+        assert_eq!(elapsed.len(), 2);
+        assert!(
+            elapsed
+                .iter()
+                .all(|elapsed| *elapsed >= Duration::from_millis(20))
+        );
+    }
+}
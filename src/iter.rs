@@ -0,0 +1,169 @@
+use crate::{ComponentMap, KeyedStorage, Storage, Teardown, WithArgs};
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> Extend<(Key, Args)>
+    for ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    Key: Eq + core::hash::Hash,
+    FnInit: Fn(&Key, &Args) -> Comp,
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    fn extend<Iter: IntoIterator<Item = (Key, Args)>>(&mut self, entries: Iter) {
+        for (key, args) in entries {
+            let component = self
+                .events
+                .instrument
+                .build(&key, &args, || (self.init)(&key, &args));
+            self.insert_component(key, args, component);
+        }
+    }
+}
+
+/// Consumes the map, handing ownership of every entry to the caller without running teardown.
+impl<Key, Args, Comp, FnInit, FnDrop, Store> IntoIterator
+    for ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>
+        + IntoIterator<Item = (Key, WithArgs<Args, Comp>)>
+        + Default,
+{
+    type Item = (Key, WithArgs<Args, Comp>);
+    type IntoIter = Store::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let (map, _, _) = self.into_raw_parts();
+        map.into_iter()
+    }
+}
+
+impl<'a, Key, Args, Comp, FnInit, FnDrop, Store> IntoIterator
+    for &'a ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+    &'a Store: IntoIterator<Item = (&'a Key, &'a WithArgs<Args, Comp>)>,
+{
+    type Item = (&'a Key, &'a WithArgs<Args, Comp>);
+    type IntoIter = <&'a Store as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.map).into_iter()
+    }
+}
+
+impl<'a, Key, Args, Comp, FnInit, FnDrop, Store> IntoIterator
+    for &'a mut ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+    &'a mut Store: IntoIterator<Item = (&'a Key, &'a mut WithArgs<Args, Comp>)>,
+{
+    type Item = (&'a Key, &'a mut WithArgs<Args, Comp>);
+    type IntoIter = <&'a mut Store as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&mut self.map).into_iter()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Args {
+        value: usize,
+    }
+
+    #[test]
+    fn test_extend() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        manager.extend([("key2", Args { value: 2 }), ("key3", Args { value: 3 })]);
+
+        assert_eq!(manager.len(), 3);
+        assert_eq!(manager.get(&"key2"), Some(&Counter(2)));
+        assert_eq!(manager.get(&"key3"), Some(&Counter(3)));
+    }
+
+    #[test]
+    fn test_extend_from_iterator_pipeline() {
+        let init = |key: &String, args: &Args| Counter(key.len() + args.value);
+        let mut manager: ComponentMap<String, Args, Counter, _> = ComponentMap::init([], init);
+
+        manager.extend((1..=3).map(|value| (format!("key{value}"), Args { value })));
+
+        assert_eq!(manager.len(), 3);
+        assert_eq!(manager.get("key3"), Some(&Counter(7)));
+    }
+
+    #[test]
+    fn test_extend_replaces_existing_with_teardown() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init).with_teardown(
+            move |_: &&str, component: &mut Counter| {
+                closed_clone.lock().unwrap().push(component.0);
+            },
+        );
+
+        manager.extend([("key1", Args { value: 10 })]);
+
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert_eq!(manager.get_args(&"key1"), Some(&Args { value: 10 }));
+        assert_eq!(*closed.lock().unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_into_iter_owned() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_teardown(move |_: &&str, _: &mut Counter| {
+            *closed_clone.lock().unwrap() += 1;
+        });
+
+        let mut entries: Vec<_> = manager
+            .into_iter()
+            .map(|(key, component)| (key, component.component))
+            .collect();
+        entries.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(entries, vec![("key1", Counter(1)), ("key2", Counter(2))]);
+        assert_eq!(*closed.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_into_iter_ref_and_mut() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        for (_, component) in &mut manager {
+            component.component.0 += component.args.value;
+        }
+
+        let mut values = Vec::new();
+        for (key, component) in &manager {
+            values.push((*key, component.component.0));
+        }
+        values.sort();
+
+        assert_eq!(values, vec![("key1", 2), ("key2", 4)]);
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Per-key writer locks, kept only while some caller holds or waits on one.
+#[derive(Debug)]
+pub(crate) struct KeyLocks<Key, Shared>(Mutex<HashMap<Key, Arc<KeyLock<Shared>>>>);
+
+/// Serialises writers to one key. A reinit leaves what its callers get behind,
+/// so reinits that queued while it ran return that instead of running init
+/// again.
+#[derive(Debug)]
+pub(crate) struct KeyLock<Shared> {
+    /// Writes completed under this lock, to tell which ones a waiter queued behind.
+    writes: AtomicU64,
+    shared: futures::lock::Mutex<Option<Shared>>,
+}
+
+impl<Shared> Default for KeyLock<Shared> {
+    fn default() -> Self {
+        Self {
+            writes: AtomicU64::new(0),
+            shared: futures::lock::Mutex::new(None),
+        }
+    }
+}
+
+impl<Key, Shared> KeyLocks<Key, Shared>
+where
+    Key: Clone + Eq + std::hash::Hash,
+{
+    /// Joins the writers to `key`, creating its lock if nobody else holds it.
+    pub(crate) fn claim(&self, key: &Key) -> KeyClaim<'_, Key, Shared> {
+        let lock = self.entries().entry(key.clone()).or_default().clone();
+        KeyClaim {
+            locks: self,
+            key: key.clone(),
+            lock,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Arc<KeyLock<Shared>>>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<Key, Shared> Default for KeyLocks<Key, Shared> {
+    fn default() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+}
+
+/// A caller's hold on the lock for one key. Dropping the last claim removes
+/// the lock, so every caller that overlaps another waits on the same one.
+pub(crate) struct KeyClaim<'a, Key, Shared>
+where
+    Key: Clone + Eq + std::hash::Hash,
+{
+    locks: &'a KeyLocks<Key, Shared>,
+    key: Key,
+    lock: Arc<KeyLock<Shared>>,
+}
+
+impl<Key, Shared> KeyClaim<'_, Key, Shared>
+where
+    Key: Clone + Eq + std::hash::Hash,
+{
+    /// Waits until this caller is the only writer to the key.
+    pub(crate) async fn lock(&self) -> KeyGuard<'_, Shared> {
+        let seen = self.lock.writes.load(Ordering::Acquire);
+        let shared = self.lock.shared.lock().await;
+        KeyGuard {
+            writes: &self.lock.writes,
+            seen,
+            shared,
+        }
+    }
+
+    /// Like [`lock`](Self::lock), but gives up if another writer holds it.
+    #[cfg(feature = "dashmap")]
+    pub(crate) fn try_lock(&self) -> Option<KeyGuard<'_, Shared>> {
+        let seen = self.lock.writes.load(Ordering::Acquire);
+        let shared = self.lock.shared.try_lock()?;
+        Some(KeyGuard {
+            writes: &self.lock.writes,
+            seen,
+            shared,
+        })
+    }
+}
+
+impl<Key, Shared> Drop for KeyClaim<'_, Key, Shared>
+where
+    Key: Clone + Eq + std::hash::Hash,
+{
+    fn drop(&mut self) {
+        let mut entries = self.locks.entries();
+        // The table's reference and ours: nobody else holds or waits on it
+        if entries
+            .get(&self.key)
+            .is_some_and(|current| Arc::ptr_eq(current, &self.lock))
+            && Arc::strong_count(&self.lock) == 2
+        {
+            entries.remove(&self.key);
+        }
+    }
+}
+
+/// Exclusive access to the writes of one key.
+pub(crate) struct KeyGuard<'a, Shared> {
+    writes: &'a AtomicU64,
+    seen: u64,
+    shared: futures::lock::MutexGuard<'a, Option<Shared>>,
+}
+
+impl<Shared> KeyGuard<'_, Shared> {
+    /// What a reinit that completed while this caller waited left behind.
+    pub(crate) fn shared(&self) -> Option<&Shared> {
+        if self.writes.load(Ordering::Acquire) == self.seen {
+            return None;
+        }
+        self.shared.as_ref()
+    }
+
+    /// Records a completed write, leaving `shared` for the reinits queued
+    /// behind it; updates leave nothing, so those run their own init.
+    pub(crate) fn complete(&mut self, shared: Option<Shared>) {
+        *self.shared = shared;
+        self.writes.fetch_add(1, Ordering::Release);
+    }
+}
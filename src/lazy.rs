@@ -0,0 +1,299 @@
+use crate::{ComponentMap, Entries, NoTeardown, Teardown, storage::HashMap};
+use core::borrow::Borrow;
+
+/// A [`ComponentMap`] whose entries hold only their args until first accessed.
+///
+/// Components are built by [`get_or_init`](Self::get_or_init) and its
+/// variants, so startup only pays for the keys that are actually used.
+#[derive(Debug)]
+pub struct LazyComponentMap<Key, Args, Comp, FnInit, FnDrop = NoTeardown>
+where
+    FnDrop: Teardown<Key, Comp>,
+{
+    pending: HashMap<Key, Args>,
+    map: ComponentMap<Key, Args, Comp, FnInit, FnDrop>,
+}
+
+impl<Key, Args, Comp, FnInit> LazyComponentMap<Key, Args, Comp, FnInit>
+where
+    Key: Eq + core::hash::Hash,
+{
+    /// Records `entries` without initialising any of them.
+    pub fn new(entries: impl IntoIterator<Item = (Key, Args)>, init: FnInit) -> Self {
+        Self {
+            pending: entries.into_iter().collect(),
+            map: ComponentMap::new(Entries::new(), init, NoTeardown),
+        }
+    }
+
+    pub fn with_teardown<FnDropNext>(
+        self,
+        teardown: FnDropNext,
+    ) -> LazyComponentMap<Key, Args, Comp, FnInit, FnDropNext>
+    where
+        FnDropNext: Fn(&Key, &mut Comp),
+    {
+        LazyComponentMap {
+            pending: self.pending,
+            map: self.map.with_teardown(teardown),
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> LazyComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Eq + core::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    /// Number of keys, initialised or not.
+    pub fn len(&self) -> usize {
+        self.pending.len() + self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of keys whose component has not been built yet.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.pending.contains_key(key) || self.map.contains_key(key)
+    }
+
+    pub fn is_initialized<Q>(&self, key: &Q) -> bool
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the component for `key` only if it has already been built.
+    pub fn get<Q>(&self, key: &Q) -> Option<&Comp>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    /// The components built so far.
+    pub fn initialized(&self) -> &ComponentMap<Key, Args, Comp, FnInit, FnDrop> {
+        &self.map
+    }
+
+    /// Reports every build to `instrument`, as
+    /// [`ComponentMap::with_instrument`] does.
+    #[cfg(feature = "std")]
+    pub fn with_instrument(
+        mut self,
+        instrument: impl crate::Instrument<Key, Args> + Send + Sync + 'static,
+    ) -> Self {
+        self.map = self.map.with_instrument(instrument);
+        self
+    }
+
+    /// Returns the component for `key`, building it on first access, or
+    /// `None` if the key is unknown.
+    pub fn get_or_init<Q>(&mut self, key: &Q) -> Option<&mut Comp>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        match self.pending.remove_entry(key) {
+            Some((key, args)) => Some(self.map.get_or_init(key, args)),
+            None => self.map.get_mut(key),
+        }
+    }
+
+    /// Like [`get_or_init`](Self::get_or_init) for fallible inits. On failure
+    /// the key stays pending, so the next access retries it.
+    pub fn try_get_or_init<Q, Error>(&mut self, key: &Q) -> Option<Result<&mut Comp, Error>>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let Some((pending_key, args)) = self.pending.remove_entry(key) else {
+            return self.map.get_mut(key).map(Ok);
+        };
+
+        let result = self
+            .map
+            .events
+            .instrument
+            .build(&pending_key, &args, || (self.map.init)(&pending_key, &args));
+        match result {
+            Ok(component) => {
+                self.map.insert_component(pending_key, args, component);
+                self.map.get_mut(key).map(Ok)
+            }
+            Err(error) => {
+                self.map.events.emit_failed(&pending_key, &args, &error);
+                self.pending.insert(pending_key, args);
+                Some(Err(error))
+            }
+        }
+    }
+
+    /// Like [`get_or_init`](Self::get_or_init) for async inits. The key only
+    /// leaves `pending` once its init completes, so dropping the future
+    /// midway keeps it pending.
+    pub async fn get_or_init_async<Q>(&mut self, key: &Q) -> Option<&mut Comp>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        FnInit: AsyncFn(&Key, &Args) -> Comp,
+    {
+        let Some((pending_key, args)) = self.pending.get_key_value(key) else {
+            return self.map.get_mut(key);
+        };
+
+        let span = self.map.events.instrument.start(pending_key, args);
+        let component = (self.map.init)(pending_key, args).await;
+        span.finish(pending_key);
+        let (pending_key, args) = self.pending.remove_entry(key)?;
+        self.map.insert_component(pending_key, args, component);
+        self.map.get_mut(key)
+    }
+
+    /// Builds every pending component, e.g. to warm up before serving.
+    pub fn init_pending(&mut self)
+    where
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        for (key, args) in core::mem::take(&mut self.pending) {
+            self.map.get_or_init(key, args);
+        }
+    }
+
+    /// Splits into the components built so far and the args still pending.
+    #[allow(clippy::type_complexity)]
+    pub fn into_inner(
+        self,
+    ) -> (
+        ComponentMap<Key, Args, Comp, FnInit, FnDrop>,
+        HashMap<Key, Args>,
+    ) {
+        (self.map, self.pending)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[test]
+    fn test_components_are_built_on_first_access() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = calls.clone();
+        let init = move |key: &usize, args: &usize| {
+            calls_clone.lock().unwrap().push(*key);
+            Counter(*args)
+        };
+
+        let mut lazy = LazyComponentMap::new((0..1000).map(|key| (key, key * 2)), init);
+        assert_eq!(lazy.len(), 1000);
+        assert!(calls.lock().unwrap().is_empty());
+
+        assert_eq!(lazy.get_or_init(&7), Some(&mut Counter(14)));
+        assert_eq!(lazy.get_or_init(&7), Some(&mut Counter(14)));
+        assert_eq!(lazy.get_or_init(&5000), None);
+
+        assert_eq!(*calls.lock().unwrap(), vec![7]);
+        assert!(lazy.is_initialized(&7));
+        assert_eq!(lazy.get(&8), None);
+        assert_eq!(lazy.pending_len(), 999);
+        assert_eq!(lazy.len(), 1000);
+    }
+
+    #[test]
+    fn test_try_get_or_init_keeps_failed_keys_pending() {
+        let fail = Arc::new(Mutex::new(true));
+        let fail_clone = fail.clone();
+        let init = move |_key: &&str, args: &usize| {
+            if *fail_clone.lock().unwrap() {
+                Err(TestError)
+            } else {
+                Ok(Counter(*args))
+            }
+        };
+
+        let mut lazy = LazyComponentMap::new([("key1", 1)], init);
+        assert_eq!(lazy.try_get_or_init(&"key1"), Some(Err(TestError)));
+        assert_eq!(lazy.pending_len(), 1);
+
+        *fail.lock().unwrap() = false;
+        assert_eq!(lazy.try_get_or_init(&"key1"), Some(Ok(&mut Counter(1))));
+        assert_eq!(lazy.pending_len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_or_init_async() {
+        let init = |_key: &&str, args: &usize| {
+            let value = *args;
+            async move { Counter(value) }
+        };
+
+        let mut lazy = LazyComponentMap::new([("key1", 1), ("key2", 2)], init);
+        assert_eq!(lazy.get_or_init_async(&"key2").await, Some(&mut Counter(2)));
+
+        let (map, pending) = lazy.into_inner();
+        assert_eq!(map.get(&"key2"), Some(&Counter(2)));
+        assert_eq!(pending, HashMap::from([("key1", 1)]));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_get_or_init_async_keeps_key_pending() {
+        let init = async |_key: &&str, args: &usize| {
+            tokio::task::yield_now().await;
+            Counter(*args)
+        };
+
+        let mut lazy = LazyComponentMap::new([("key1", 1)], init);
+        assert!(lazy.get_or_init_async(&"key1").now_or_never().is_none());
+        assert_eq!(lazy.pending_len(), 1);
+        assert!(lazy.contains_key(&"key1"));
+
+        assert_eq!(lazy.get_or_init_async(&"key1").await, Some(&mut Counter(1)));
+        assert_eq!(lazy.pending_len(), 0);
+    }
+
+    #[test]
+    fn test_init_pending_and_teardown() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut lazy = LazyComponentMap::new([("key1", 1), ("key2", 2)], init).with_teardown(
+            move |key: &&str, _: &mut Counter| closed_clone.lock().unwrap().push(*key),
+        );
+
+        lazy.get_or_init(&"key1");
+        drop(lazy);
+        // Never-built components have nothing to tear down
+        assert_eq!(*closed.lock().unwrap(), vec!["key1"]);
+
+        let mut lazy = LazyComponentMap::new([("key1", 1), ("key2", 2)], init);
+        lazy.init_pending();
+        assert_eq!(lazy.pending_len(), 0);
+        assert_eq!(lazy.get(&"key2"), Some(&Counter(2)));
+    }
+}
@@ -1,10 +1,159 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+use core::sync::atomic::{AtomicU64, Ordering};
 use derive_more::Constructor;
-use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+use storage::HashMap;
 
+mod arena;
+#[cfg(feature = "std")]
 mod async_fallible;
+#[cfg(feature = "std")]
 mod async_infallible;
+#[cfg(feature = "std")]
+mod audit;
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "tokio")]
+mod blue_green;
+#[cfg(feature = "std")]
+mod canary;
+#[cfg(feature = "std")]
+mod cancel;
+mod child;
+mod collection;
+#[cfg(feature = "dashmap")]
+mod concurrent;
+mod config;
+mod dependency;
+mod error;
+mod events;
+mod fallback;
+mod generation;
+#[cfg(feature = "tokio")]
+mod handle;
+mod health;
+#[cfg(feature = "std")]
+mod instrument;
+mod iter;
+#[cfg(feature = "std")]
+mod key_lock;
+mod lazy;
+mod listener;
+mod lru;
+mod merge;
+mod ordered;
+mod outcome;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pause;
+#[cfg(feature = "tokio")]
+mod periodic;
+mod policy;
+mod priority;
+mod reconcile;
+#[cfg(feature = "hot-reload")]
+mod reload;
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "std")]
+mod rolling;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+#[cfg(feature = "tokio")]
+mod spawn;
+#[cfg(feature = "std")]
+mod spec;
+mod state;
+#[cfg(feature = "std")]
+mod stats;
+mod storage;
+#[cfg(feature = "std")]
+mod supervisor;
+#[cfg(feature = "arc-swap")]
+mod swap;
 mod sync_fallible;
 mod sync_infallible;
+mod tags;
+mod teardown;
+#[cfg(feature = "std")]
+mod throttle;
+#[cfg(feature = "std")]
+mod timeout;
+#[cfg(feature = "std")]
+mod timing;
+mod transform;
+#[cfg(feature = "tokio")]
+mod trigger;
+#[cfg(feature = "std")]
+mod ttl;
+#[cfg(feature = "tokio")]
+mod watch;
+
+pub use arena::{ArenaComponentMap, ComponentId};
+#[cfg(feature = "std")]
+pub use audit::AuditRecord;
+#[cfg(feature = "std")]
+pub use batch::{BatchOptions, DedupPolicy};
+#[cfg(feature = "std")]
+pub use canary::CanaryReport;
+pub use child::ChildComponentMap;
+#[cfg(feature = "dashmap")]
+pub use concurrent::ConcurrentComponentMap;
+pub use config::ComponentMapConfig;
+pub use dependency::{DependencyError, Resolved};
+pub use error::KeyedError;
+pub use events::{ChangeEvent, ChangeKind};
+pub use fallback::{InitSource, Sourced, with_fallback};
+pub use generation::GenerationMismatch;
+#[cfg(feature = "tokio")]
+pub use handle::{ComponentMapHandle, HandleClosed};
+pub use health::{Health, HealthReport};
+#[cfg(feature = "std")]
+pub use instrument::Instrument;
+pub use lazy::LazyComponentMap;
+pub use listener::LifecycleListener;
+pub use merge::MergePolicy;
+pub use outcome::ReinitOutcome;
+#[cfg(feature = "tokio")]
+pub use periodic::ReinitTask;
+pub use policy::{ErrorPolicy, Threshold};
+pub use reconcile::{ChangeSet, SyncReport};
+#[cfg(feature = "hot-reload")]
+pub use reload::{ConfigFormat, ConfigWatcher, ReloadError};
+#[cfg(feature = "std")]
+pub use retry::{Backoff, RetryPolicy};
+#[cfg(feature = "std")]
+pub use shared::SharedComponentMap;
+#[cfg(feature = "snapshot")]
+pub use snapshot::{SnapshotEntry, SnapshotState, read_snapshot};
+#[cfg(feature = "std")]
+pub use spec::ComponentSpec;
+pub use state::EntryState;
+#[cfg(feature = "std")]
+pub use stats::ComponentStats;
+pub use storage::{Entries, KeyedStorage, Lookup, Storage};
+#[cfg(feature = "std")]
+pub use supervisor::{RestartStrategy, SupervisionReport, Supervisor};
+#[cfg(feature = "arc-swap")]
+pub use swap::{Snapshot, SwapComponentMap};
+#[cfg(feature = "std")]
+pub use teardown::ShutdownOutcome;
+pub use teardown::{AsyncTeardown, AsyncTeardownFn, NoTeardown, Teardown};
+#[cfg(feature = "std")]
+pub use timeout::{TimeoutError, with_timeout};
+#[cfg(feature = "std")]
+pub use timing::{DurationSummary, InitTimings, with_timing, with_timing_async};
+#[cfg(feature = "tokio")]
+pub use trigger::ReinitTrigger;
 
 #[derive(Debug, Constructor)]
 pub struct Keyed<Key, Value> {
@@ -12,14 +161,391 @@ pub struct Keyed<Key, Value> {
     pub value: Value,
 }
 
-#[derive(Debug, Constructor)]
+#[derive(Debug)]
 pub struct WithArgs<Args, Comp> {
     pub component: Comp,
     pub args: Args,
+    dirty: bool,
+    #[cfg(feature = "std")]
+    initialized_at: Instant,
+    #[cfg(feature = "std")]
+    attempted_at: Option<Instant>,
+    #[cfg(feature = "std")]
+    ttl: Option<Duration>,
+    priority: Option<i32>,
+    last_used: AtomicU64,
+    generation: u64,
+    failed: bool,
+    last_error: Option<String>,
+    reinitializing: bool,
 }
 
-#[derive(Debug, Constructor)]
-pub struct ComponentMap<Key, Args, Comp, FnInit> {
-    pub map: HashMap<Key, WithArgs<Args, Comp>>,
-    pub init: FnInit,
+impl<Args, Comp> WithArgs<Args, Comp> {
+    pub fn new(component: Comp, args: Args) -> Self {
+        Self {
+            component,
+            args,
+            dirty: false,
+            #[cfg(feature = "std")]
+            initialized_at: Instant::now(),
+            #[cfg(feature = "std")]
+            attempted_at: None,
+            #[cfg(feature = "std")]
+            ttl: None,
+            priority: None,
+            last_used: AtomicU64::new(lru::tick()),
+            generation: generation::next(),
+            failed: false,
+            last_error: None,
+            reinitializing: false,
+        }
+    }
+
+    /// Whether the args were replaced via `set_args` since the component was last initialised.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Max age set for this entry with `set_ttl`, overriding the map's default.
+    #[cfg(feature = "std")]
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Priority set with `set_priority`, if any; see
+    /// [`ComponentMap::priority`] for the one in effect.
+    pub fn priority(&self) -> Option<i32> {
+        self.priority
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// When the component was last built successfully; failed reinits leave it unchanged.
+    #[cfg(feature = "std")]
+    pub fn last_initialized_at(&self) -> Instant {
+        self.initialized_at
+    }
+
+    /// Time since [`last_initialized_at`](Self::last_initialized_at).
+    #[cfg(feature = "std")]
+    pub fn age(&self) -> Duration {
+        self.initialized_at.elapsed()
+    }
+
+    /// Whether the last reinit failed, cleared once the component is rebuilt.
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Debug rendering of the error attached to the last failed reinit with
+    /// [`record_error`](ComponentMap::record_error).
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    pub(crate) fn last_used(&self) -> u64 {
+        self.last_used.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn touch(&self) {
+        self.last_used.store(lru::tick(), Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn is_stale(&self, config: &ComponentMapConfig) -> bool {
+        self.ttl.or(config.ttl).is_some_and(|ttl| self.age() >= ttl)
+    }
+
+    /// Without the `std` feature there is no clock, so entries never expire.
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn is_stale(&self, _config: &ComponentMapConfig) -> bool {
+        false
+    }
+
+    /// Carries the bookkeeping of this entry over to the component and args
+    /// `f` converts its own into.
+    pub(crate) fn map_parts<Args2, Comp2>(
+        self,
+        f: impl FnOnce(Comp, Args) -> (Comp2, Args2),
+    ) -> WithArgs<Args2, Comp2> {
+        let (component, args) = f(self.component, self.args);
+        WithArgs {
+            component,
+            args,
+            dirty: self.dirty,
+            #[cfg(feature = "std")]
+            initialized_at: self.initialized_at,
+            #[cfg(feature = "std")]
+            attempted_at: self.attempted_at,
+            #[cfg(feature = "std")]
+            ttl: self.ttl,
+            priority: self.priority,
+            last_used: self.last_used,
+            generation: self.generation,
+            failed: self.failed,
+            last_error: self.last_error,
+            reinitializing: self.reinitializing,
+        }
+    }
+
+    pub(crate) fn set_args(&mut self, args: Args) -> Args {
+        self.dirty = true;
+        self.generation = generation::next();
+        core::mem::replace(&mut self.args, args)
+    }
+
+    pub(crate) fn record_failure(&mut self) {
+        self.reinitializing = false;
+        #[cfg(feature = "std")]
+        {
+            self.attempted_at = Some(Instant::now());
+        }
+        self.failed = true;
+        self.last_error = None;
+    }
+
+    pub(crate) fn replace_component(&mut self, component: Comp) -> Comp {
+        self.dirty = false;
+        self.reinitializing = false;
+        self.failed = false;
+        self.last_error = None;
+        #[cfg(feature = "std")]
+        {
+            self.initialized_at = Instant::now();
+            self.attempted_at = Some(self.initialized_at);
+        }
+        self.generation = generation::next();
+        core::mem::replace(&mut self.component, component)
+    }
+}
+
+#[derive(Debug)]
+pub struct ComponentMap<
+    Key,
+    Args,
+    Comp,
+    FnInit,
+    FnDrop = NoTeardown,
+    Store = Entries<Key, WithArgs<Args, Comp>>,
+> where
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+{
+    pub map: Store,
+    pub(crate) init: Owned<FnInit>,
+    pub(crate) teardown: Owned<FnDrop>,
+    pub config: ComponentMapConfig,
+    pub(crate) events: events::Observers<Key, Args, Comp>,
+    pub(crate) health: health::HealthCheck<Key, Comp>,
+    pub(crate) paused: HashMap<Key, Args>,
+    pub(crate) dependencies: dependency::Dependencies<Key>,
+    pub(crate) groups: tags::Groups<Key>,
+    pub(crate) priorities: priority::Prioritizer<Key, Args>,
+}
+
+/// Holds the init and teardown of a [`ComponentMap`] or [`ArenaComponentMap`]
+/// so they can be moved out of it despite its `Drop` impl. They are only
+/// taken when the map is consumed, so an empty `Owned` is never observed.
+#[derive(Debug, Clone)]
+pub(crate) struct Owned<T>(Option<T>);
+
+impl<T> Owned<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self(Some(value))
+    }
+
+    pub(crate) fn take(&mut self) -> T {
+        self.0.take().expect("only taken once")
+    }
+}
+
+impl<T> core::ops::Deref for Owned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.as_ref().expect("only read before it is taken")
+    }
+}
+
+impl<T> core::ops::DerefMut for Owned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0.as_mut().expect("only read before it is taken")
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+{
+    pub fn new(map: Store, init: FnInit, teardown: FnDrop) -> Self {
+        Self {
+            map,
+            init: Owned::new(init),
+            teardown: Owned::new(teardown),
+            config: ComponentMapConfig::default(),
+            events: events::Observers::default(),
+            health: health::HealthCheck::default(),
+            paused: HashMap::new(),
+            dependencies: dependency::Dependencies::default(),
+            groups: HashMap::new(),
+            priorities: priority::Prioritizer::default(),
+        }
+    }
+
+    /// Replaces the defaults used by the async operations.
+    pub fn with_config(mut self, config: ComponentMapConfig) -> Self
+    where
+        Key: Eq + core::hash::Hash,
+        Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+    {
+        self.config = config;
+        self.enforce_capacity();
+        self
+    }
+
+    /// Swaps the init function without touching existing components; follow
+    /// with `reinit_all` to rebuild them using the new function.
+    pub fn with_init<FnInitNext>(
+        self,
+        init: FnInitNext,
+    ) -> ComponentMap<Key, Args, Comp, FnInitNext, FnDrop, Store>
+    where
+        Store: Default,
+    {
+        self.rebuild(|map, _, teardown| (map, init, teardown))
+    }
+
+    pub fn with_teardown<FnDropNext>(
+        self,
+        teardown: FnDropNext,
+    ) -> ComponentMap<Key, Args, Comp, FnInit, FnDropNext, Store>
+    where
+        FnDropNext: Fn(&Key, &mut Comp),
+        Store: Default,
+    {
+        self.rebuild(|map, init, _| (map, init, teardown))
+    }
+
+    pub fn with_async_teardown<FnDropNext>(
+        self,
+        teardown: FnDropNext,
+    ) -> ComponentMap<Key, Args, Comp, FnInit, AsyncTeardownFn<FnDropNext>, Store>
+    where
+        FnDropNext: AsyncFn(&Key, &mut Comp),
+        Store: Default,
+    {
+        self.rebuild(|map, init, _| (map, init, AsyncTeardownFn(teardown)))
+    }
+
+    /// Moves the entries into `storage`, e.g. a `BTreeMap` to keep them in
+    /// key order. An `IndexMap` (`indexmap` feature) keeps insertion order, so
+    /// start from one with [`new`](Self::new) rather than converting a map
+    /// whose order is already lost.
+    pub fn with_storage<StoreNext>(
+        self,
+        storage: StoreNext,
+    ) -> ComponentMap<Key, Args, Comp, FnInit, FnDrop, StoreNext>
+    where
+        Store: Default,
+        StoreNext: KeyedStorage<Key, WithArgs<Args, Comp>>,
+    {
+        self.rebuild(|entries, init, teardown| {
+            let mut storage = storage;
+            for (key, entry) in entries {
+                storage.insert(key, entry);
+            }
+            (storage, init, teardown)
+        })
+    }
+
+    /// Moves the entries, init, and teardown out through `rebuild` and carries
+    /// the remaining state over to the map built from its output.
+    fn rebuild<FnInitNext, FnDropNext, StoreNext>(
+        mut self,
+        rebuild: impl FnOnce(Store, FnInit, FnDrop) -> (StoreNext, FnInitNext, FnDropNext),
+    ) -> ComponentMap<Key, Args, Comp, FnInitNext, FnDropNext, StoreNext>
+    where
+        FnDropNext: Teardown<Key, Comp>,
+        Store: Default,
+        StoreNext: Storage<Key, WithArgs<Args, Comp>>,
+    {
+        let (map, init, teardown) = self.take_raw_parts();
+        let (map, init, teardown) = rebuild(map, init, teardown);
+        ComponentMap {
+            map,
+            init: Owned::new(init),
+            teardown: Owned::new(teardown),
+            config: self.config,
+            events: core::mem::take(&mut self.events),
+            health: core::mem::take(&mut self.health),
+            paused: core::mem::take(&mut self.paused),
+            dependencies: core::mem::take(&mut self.dependencies),
+            groups: core::mem::take(&mut self.groups),
+            priorities: core::mem::take(&mut self.priorities),
+        }
+    }
+
+    /// Decomposes the map without running teardown, dropping its remaining state.
+    pub(crate) fn into_raw_parts(mut self) -> (Store, FnInit, FnDrop)
+    where
+        Store: Default,
+    {
+        self.take_raw_parts()
+    }
+
+    /// Empties the map, leaving nothing for `Drop` to tear down.
+    fn take_raw_parts(&mut self) -> (Store, FnInit, FnDrop)
+    where
+        Store: Default,
+    {
+        (
+            core::mem::take(&mut self.map),
+            self.init.take(),
+            self.teardown.take(),
+        )
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, S>
+    ComponentMap<Key, Args, Comp, FnInit, FnDrop, Entries<Key, WithArgs<Args, Comp>, S>>
+where
+    FnDrop: Teardown<Key, Comp>,
+{
+    /// Rebuilds the map around `hasher`, e.g. a faster one for hot lookup
+    /// paths or a deterministic one for reproducible tests.
+    #[allow(clippy::type_complexity)]
+    pub fn with_hasher<SNext>(
+        self,
+        hasher: SNext,
+    ) -> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Entries<Key, WithArgs<Args, Comp>, SNext>>
+    where
+        Key: Eq + core::hash::Hash,
+        S: Default,
+        SNext: core::hash::BuildHasher,
+    {
+        self.rebuild(|entries, init, teardown| {
+            let mut map = Entries::with_capacity_and_hasher(entries.len(), hasher);
+            map.extend(entries);
+            (map, init, teardown)
+        })
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> Drop
+    for ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+{
+    fn drop(&mut self) {
+        let Some(teardown) = &self.teardown.0 else {
+            return;
+        };
+        for (key, component) in self.map.iter_mut() {
+            teardown.teardown(key, &mut component.component);
+        }
+    }
 }
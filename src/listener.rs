@@ -0,0 +1,131 @@
+use crate::{ChangeKind, ComponentMap, KeyedStorage, Teardown, WithArgs};
+use alloc::boxed::Box;
+use core::fmt;
+
+/// Hooks called synchronously as entries of a [`ComponentMap`] change.
+///
+/// Every method defaults to doing nothing, so implementors only override the
+/// transitions they care about.
+pub trait LifecycleListener<Key, Args, Comp> {
+    /// A component was built for a new key, or for new args under an existing one.
+    fn on_init(&mut self, _key: &Key, _entry: &WithArgs<Args, Comp>) {}
+
+    /// A component was rebuilt from its current args.
+    fn on_reinit(&mut self, _key: &Key, _entry: &WithArgs<Args, Comp>) {}
+
+    /// The entry was removed from the map; its teardown may already have run.
+    fn on_remove(&mut self, _key: &Key, _entry: &WithArgs<Args, Comp>) {}
+
+    /// Init of `key` from `args` failed with `error`, leaving its entry, if
+    /// any, untouched.
+    fn on_failure(&mut self, _key: &Key, _args: &Args, _error: &dyn fmt::Debug) {}
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Registers `listener` for every subsequent mutation of the map.
+    pub fn add_listener(
+        &mut self,
+        mut listener: impl LifecycleListener<Key, Args, Comp> + Send + Sync + 'static,
+    ) {
+        self.events
+            .push(Box::new(move |key: &Key, kind, entry, failure| {
+                match (kind, entry) {
+                    (ChangeKind::Failed, _) => {
+                        if let Some((args, error)) = failure {
+                            listener.on_failure(key, args, error)
+                        }
+                    }
+                    (ChangeKind::Inserted | ChangeKind::Replaced, Some(entry)) => {
+                        listener.on_init(key, entry)
+                    }
+                    (ChangeKind::Reinitialized, Some(entry)) => listener.on_reinit(key, entry),
+                    (ChangeKind::Removed, Some(entry)) => listener.on_remove(key, entry),
+                    (_, None) => {}
+                }
+                true
+            }));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl LifecycleListener<&'static str, usize, Counter> for Recorder {
+        fn on_init(&mut self, key: &&'static str, entry: &WithArgs<usize, Counter>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("init {key} {}", entry.args));
+        }
+
+        fn on_reinit(&mut self, key: &&'static str, entry: &WithArgs<usize, Counter>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("reinit {key} {}", entry.component.0));
+        }
+
+        fn on_remove(&mut self, key: &&'static str, entry: &WithArgs<usize, Counter>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("remove {key} {}", entry.args));
+        }
+
+        fn on_failure(&mut self, key: &&'static str, args: &usize, error: &dyn fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("failure {key} {args} {error:?}"));
+        }
+    }
+
+    #[test]
+    fn test_listener_sees_every_transition() {
+        let init = |_key: &&str, args: &usize| {
+            if *args == 0 {
+                Err(TestError)
+            } else {
+                Ok(Counter(*args))
+            }
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1)], init).unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        manager.add_listener(Recorder(log.clone()));
+
+        manager
+            .try_update([("key2", 2), ("key3", 0)])
+            .for_each(drop);
+        manager.set_args(&"key1", 5);
+        manager.try_reinit([&"key1"]).for_each(drop);
+        manager.set_args(&"key1", 0);
+        manager.try_reinit([&"key1"]).for_each(drop);
+        manager.remove(&"key2");
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "init key2 2",
+                "failure key3 0 TestError",
+                "reinit key1 5",
+                "failure key1 0 TestError",
+                "remove key2 2"
+            ]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(feature = "std")]
+use crate::AsyncTeardown;
+use crate::{ComponentMap, KeyedStorage, Lookup, Teardown, WithArgs};
+use core::{
+    borrow::Borrow,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// Recency is a global logical clock rather than `Instant`, so ties are
+// impossible and touching an entry is a single relaxed store.
+static CLOCK: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn tick() -> u64 {
+    CLOCK.fetch_add(1, Ordering::Relaxed)
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Tears down least-recently-used entries until the map fits
+    /// [`ComponentMapConfig::capacity`](crate::ComponentMapConfig::capacity).
+    pub(crate) fn enforce_capacity(&mut self)
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        if let Some(capacity) = self.config.capacity {
+            self.evict_until(capacity);
+        }
+    }
+
+    /// Makes room for `key` before it is inserted, so the new entry is never
+    /// the one evicted.
+    pub(crate) fn reserve_slot<Q>(&mut self, key: &Q)
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        if let Some(capacity) = self.config.capacity
+            && !self.map.contains_key(key)
+        {
+            self.evict_until(capacity.saturating_sub(1));
+        }
+    }
+
+    /// Like [`enforce_capacity`](Self::enforce_capacity), but finalises the
+    /// evicted entries with the async teardown.
+    #[cfg(feature = "std")]
+    pub(crate) async fn enforce_capacity_async(&mut self)
+    where
+        Key: Eq + core::hash::Hash,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        if let Some(capacity) = self.config.capacity {
+            self.evict_until_async(capacity).await;
+        }
+    }
+
+    /// Like [`reserve_slot`](Self::reserve_slot), but finalises the evicted
+    /// entry with the async teardown.
+    #[cfg(feature = "std")]
+    pub(crate) async fn reserve_slot_async<Q>(&mut self, key: &Q)
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        if let Some(capacity) = self.config.capacity
+            && !self.map.contains_key(key)
+        {
+            self.evict_until_async(capacity.saturating_sub(1)).await;
+        }
+    }
+
+    // Each eviction scans the whole map, which is fine for the cache sizes
+    // this is meant for and keeps lookups free of bookkeeping.
+    fn evict_until(&mut self, len: usize)
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        while self.map.len() > len {
+            let Some(oldest) = self.map.values().map(|entry| entry.last_used()).min() else {
+                return;
+            };
+            self.retain(|_, entry| entry.last_used() != oldest)
+                .for_each(drop);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    async fn evict_until_async(&mut self, len: usize)
+    where
+        Key: Eq + core::hash::Hash,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        while self.map.len() > len {
+            let Some(oldest) = self.map.values().map(|entry| entry.last_used()).min() else {
+                return;
+            };
+            self.retain_async(|_, entry| entry.last_used() != oldest)
+                .await
+                .for_each(drop);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::{ComponentMap, ComponentMapConfig};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_capacity_evicts_least_recently_used() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("a", 1), ("b", 2)], init)
+            .with_teardown(move |key: &&str, _: &mut Counter| {
+                closed_clone.lock().unwrap().push(*key)
+            })
+            .with_config(ComponentMapConfig::default().capacity(3));
+
+        manager.update([("c", 3)]).for_each(drop);
+        assert_eq!(manager.get(&"a"), Some(&Counter(1)));
+        manager.update([("d", 4)]).for_each(drop);
+        assert!(!manager.contains_key(&"b"));
+
+        manager.get_or_init("e", 5);
+        assert!(!manager.contains_key(&"c"));
+        // Replacing an existing key does not evict
+        manager.update([("a", 10)]).for_each(drop);
+
+        assert_eq!(manager.len(), 3);
+        assert_eq!(*closed.lock().unwrap(), vec!["b", "c", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_async_ops_evict_through_async_teardown() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = async |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init_async([("a", 1), ("b", 2)], init)
+            .await
+            .with_async_teardown(async move |key: &&str, _: &mut Counter| {
+                tokio::task::yield_now().await;
+                closed_clone.lock().unwrap().push(*key)
+            })
+            .with_config(ComponentMapConfig::default().capacity(2));
+
+        manager.update_async([("c", 3)]).await.for_each(drop);
+        assert!(!manager.contains_key(&"a"));
+        manager.get_or_init_async("d", 4).await;
+        assert!(!manager.contains_key(&"b"));
+
+        assert_eq!(manager.len(), 2);
+        assert_eq!(*closed.lock().unwrap(), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_try_update_async_evicts_through_async_teardown() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = async |_key: &&str, args: &usize| Ok::<_, ()>(Counter(*args));
+        let mut manager = ComponentMap::try_init_async([("a", 1), ("b", 2)], init)
+            .await
+            .unwrap()
+            .with_async_teardown(async move |key: &&str, _: &mut Counter| {
+                tokio::task::yield_now().await;
+                closed_clone.lock().unwrap().push(*key)
+            })
+            .with_config(ComponentMapConfig::default().capacity(2));
+        manager.get(&"a");
+
+        manager.try_update_async([("c", 3)]).await.for_each(drop);
+        assert!(!manager.contains_key(&"b"));
+        manager.get_or_try_init_async("d", 4).await.unwrap();
+        assert!(!manager.contains_key(&"a"));
+
+        assert_eq!(*closed.lock().unwrap(), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_with_config_shrinks_to_capacity() {
+        let init = |key: &usize, _args: &()| Counter(*key);
+        let manager = ComponentMap::init((0..10).map(|key| (key, ())), init);
+        manager.get(&3);
+
+        let manager = manager.with_config(ComponentMapConfig::default().capacity(1));
+
+        assert_eq!(manager.len(), 1);
+        assert_eq!(manager.get(&3), Some(&Counter(3)));
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{ComponentMap, KeyedStorage, Teardown, WithArgs};
+
+/// Resolves key collisions in [`ComponentMap::merge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    KeepSelf,
+    KeepOther,
+    ReinitFromOther,
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Merges `other` into `self`, keeping the init and teardown functions of `self`.
+    ///
+    /// Components discarded on collision are passed to the teardown hook of the map they came from.
+    pub fn merge<FnInitOther, FnDropOther>(
+        mut self,
+        other: ComponentMap<Key, Args, Comp, FnInitOther, FnDropOther>,
+        policy: MergePolicy,
+    ) -> Self
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Comp,
+        FnDropOther: Teardown<Key, Comp>,
+    {
+        let (other_map, _, other_teardown) = other.into_raw_parts();
+
+        for (key, mut theirs) in other_map {
+            let theirs = match policy {
+                _ if !self.map.contains_key(&key) => theirs,
+                MergePolicy::KeepSelf => {
+                    other_teardown.teardown(&key, &mut theirs.component);
+                    continue;
+                }
+                MergePolicy::KeepOther => theirs,
+                MergePolicy::ReinitFromOther => {
+                    let component = self
+                        .events
+                        .instrument
+                        .build(&key, &theirs.args, || (self.init)(&key, &theirs.args));
+                    other_teardown.teardown(&key, &mut theirs.component);
+                    WithArgs::new(component, theirs.args)
+                }
+            };
+            self.insert_entry(key, theirs);
+        }
+
+        self.enforce_capacity();
+        self
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Args {
+        value: usize,
+    }
+
+    #[test]
+    fn test_merge_keep_self() {
+        let ours = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("shared", Args { value: 2 })],
+            |_key: &&str, args: &Args| Counter(args.value),
+        );
+        let theirs = ComponentMap::init(
+            [("key2", Args { value: 3 }), ("shared", Args { value: 4 })],
+            |_key: &&str, args: &Args| Counter(args.value * 100),
+        );
+
+        let merged = ours.merge(theirs, MergePolicy::KeepSelf);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get(&"key1"), Some(&Counter(1)));
+        assert_eq!(merged.get(&"key2"), Some(&Counter(300)));
+        assert_eq!(merged.get(&"shared"), Some(&Counter(2)));
+        assert_eq!(merged.get_args(&"shared"), Some(&Args { value: 2 }));
+    }
+
+    #[test]
+    fn test_merge_keep_other() {
+        let ours = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("shared", Args { value: 2 })],
+            |_key: &&str, args: &Args| Counter(args.value),
+        );
+        let theirs = ComponentMap::init(
+            [("key2", Args { value: 3 }), ("shared", Args { value: 4 })],
+            |_key: &&str, args: &Args| Counter(args.value * 100),
+        );
+
+        let merged = ours.merge(theirs, MergePolicy::KeepOther);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get(&"shared"), Some(&Counter(400)));
+        assert_eq!(merged.get_args(&"shared"), Some(&Args { value: 4 }));
+    }
+
+    #[test]
+    fn test_merge_reinit_from_other() {
+        let ours = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("shared", Args { value: 2 })],
+            |_key: &&str, args: &Args| Counter(args.value),
+        );
+        let theirs = ComponentMap::init(
+            [("key2", Args { value: 3 }), ("shared", Args { value: 4 })],
+            |_key: &&str, args: &Args| Counter(args.value * 100),
+        );
+
+        let merged = ours.merge(theirs, MergePolicy::ReinitFromOther);
+
+        // Rebuilt with the init function of self from the args of other
+        assert_eq!(merged.get(&"shared"), Some(&Counter(4)));
+        assert_eq!(merged.get_args(&"shared"), Some(&Args { value: 4 }));
+        // Non-colliding entries of other are moved across as-is
+        assert_eq!(merged.get(&"key2"), Some(&Counter(300)));
+    }
+
+    #[test]
+    fn test_merge_tears_down_discarded_components() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let ours_closed = closed.clone();
+        let theirs_closed = closed.clone();
+
+        let ours = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("shared", Args { value: 2 })],
+            |_key: &&str, args: &Args| Counter(args.value),
+        );
+        let theirs = ComponentMap::init(
+            [("key2", Args { value: 3 }), ("shared", Args { value: 4 })],
+            |_key: &&str, args: &Args| Counter(args.value * 100),
+        );
+        let ours = ours.with_teardown(move |_: &&str, component: &mut Counter| {
+            ours_closed.lock().unwrap().push(("ours", component.0));
+        });
+        let theirs = theirs.with_teardown(move |_: &&str, component: &mut Counter| {
+            theirs_closed.lock().unwrap().push(("theirs", component.0));
+        });
+
+        let merged = ours.merge(theirs, MergePolicy::ReinitFromOther);
+
+        assert_eq!(*closed.lock().unwrap(), vec![("theirs", 400), ("ours", 2)]);
+        assert_eq!(merged.len(), 3);
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(feature = "std")]
+use crate::AsyncTeardown;
+use crate::{ChangeKind, ComponentMap, Keyed, Teardown, WithArgs};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{borrow::Borrow, ops::RangeBounds};
+
+// A `BTreeMap` store keeps the entries sorted, so iteration, the `reinit_all*`
+// operations within each priority tier, and the events they emit all follow key
+// order without sorting on every call. Ranges are found in O(log n).
+
+impl<Key, Args, Comp, FnInit, FnDrop>
+    ComponentMap<Key, Args, Comp, FnInit, FnDrop, BTreeMap<Key, WithArgs<Args, Comp>>>
+where
+    FnDrop: Teardown<Key, Comp>,
+{
+    /// Reinitialises every entry whose key falls within `range`, one at a
+    /// time in key order, so events are emitted in that order too; `..`
+    /// reinitialises the whole map. Returns the previous components.
+    pub fn reinit_range<Q>(&mut self, range: impl RangeBounds<Q>) -> Vec<Keyed<Key, Comp>>
+    where
+        Key: Clone + Ord + core::hash::Hash + Borrow<Q>,
+        Q: Ord + ?Sized,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        self.map
+            .range_mut(range)
+            .map(|(key, component)| {
+                let next = self
+                    .events
+                    .instrument
+                    .build(key, &component.args, || (self.init)(key, &component.args));
+                let mut prev = component.replace_component(next);
+                self.teardown.teardown(key, &mut prev);
+                self.events
+                    .emit(key, ChangeKind::Reinitialized, Some(&*component));
+                Keyed::new(key.clone(), prev)
+            })
+            .collect()
+    }
+
+    /// Async counterpart of [`reinit_range`](Self::reinit_range).
+    #[cfg(feature = "std")]
+    pub async fn reinit_range_async<Q>(
+        &mut self,
+        range: impl RangeBounds<Q>,
+    ) -> Vec<Keyed<Key, Comp>>
+    where
+        Key: Clone + Ord + core::hash::Hash + Borrow<Q>,
+        Q: Ord + ?Sized,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let keys: Vec<_> = self.map.range(range).map(|(key, _)| key.clone()).collect();
+        let mut prev_components = Vec::new();
+        for key in keys {
+            let prev = self.reinit_async::<Key>([&key]).await.next();
+            if let Some(prev) = prev.and_then(|prev| prev.value) {
+                prev_components.push(Keyed::new(key, prev));
+            }
+        }
+        prev_components
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use futures::{FutureExt, StreamExt};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_btree_store_runs_in_key_order() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init(
+            [("delta", 4), ("alpha", 1), ("mike", 13), ("charlie", 3)],
+            init,
+        )
+        .with_storage(BTreeMap::new());
+        let mut events = manager.subscribe(16);
+
+        let keys: Vec<_> = manager.iter().map(|(key, ..)| *key).collect();
+        assert_eq!(keys, vec!["alpha", "charlie", "delta", "mike"]);
+
+        let order: Vec<_> = manager.reinit_all().map(|prev| *prev.key).collect();
+        assert_eq!(order, keys);
+        let emitted: Vec<_> = std::iter::from_fn(|| events.next().now_or_never().flatten())
+            .map(|event| event.key)
+            .collect();
+        assert_eq!(emitted, keys);
+    }
+
+    #[test]
+    fn test_reinit_range_in_key_order() {
+        let inited = Arc::new(Mutex::new(Vec::new()));
+        let inited_clone = inited.clone();
+        let init = move |key: &&str, args: &usize| {
+            inited_clone.lock().unwrap().push(key.to_string());
+            Counter(*args)
+        };
+        let mut manager = ComponentMap::init(
+            [("delta", 4), ("alpha", 1), ("mike", 13), ("charlie", 3)],
+            init,
+        )
+        .with_storage(BTreeMap::new());
+        inited.lock().unwrap().clear();
+
+        let prev = manager.reinit_range("b".."m");
+        let prev: Vec<_> = prev.into_iter().map(|prev| prev.key).collect();
+        assert_eq!(prev, vec!["charlie", "delta"]);
+        assert_eq!(*inited.lock().unwrap(), vec!["charlie", "delta"]);
+
+        assert_eq!(manager.reinit_range::<str>(..).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_reinit_range_async() {
+        let init = async |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init_async([("a", 1), ("b", 2), ("c", 3)], init)
+            .await
+            .with_storage(BTreeMap::new());
+
+        let prev = manager.reinit_range_async("b"..).await;
+        let prev: Vec<_> = prev.into_iter().map(|prev| prev.key).collect();
+        assert_eq!(prev, vec!["b", "c"]);
+    }
+}
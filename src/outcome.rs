@@ -0,0 +1,57 @@
+/// Per-key result of the fallible batch reinit and update operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReinitOutcome<Prev, Error> {
+    /// The key was not in the map, so nothing was reinitialised.
+    Missing,
+    /// The key was new and its component was inserted.
+    Inserted,
+    /// The component was rebuilt; holds the previous one.
+    Replaced(Prev),
+    /// The args matched the stored ones, so the component was kept.
+    Unchanged,
+    /// Init failed and the existing entry, if any, was left untouched.
+    Failed(Error),
+    /// The key was last reinitialised within
+    /// [`ComponentMapConfig::min_reinit_interval`](crate::ComponentMapConfig::min_reinit_interval),
+    /// so init was not run.
+    Throttled,
+    /// Init did not complete within the timeout of the call, and the existing
+    /// entry, if any, was left untouched.
+    TimedOut,
+}
+
+impl<Prev, Error> ReinitOutcome<Prev, Error> {
+    pub(crate) fn from_reinit(result: Option<Result<Prev, Error>>) -> Self {
+        match result {
+            None => ReinitOutcome::Missing,
+            Some(Ok(prev)) => ReinitOutcome::Replaced(prev),
+            Some(Err(error)) => ReinitOutcome::Failed(error),
+        }
+    }
+
+    pub(crate) fn from_update(result: Result<Option<Prev>, Error>) -> Self {
+        match result {
+            Ok(None) => ReinitOutcome::Inserted,
+            Ok(Some(prev)) => ReinitOutcome::Replaced(prev),
+            Err(error) => ReinitOutcome::Failed(error),
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, ReinitOutcome::Failed(_))
+    }
+
+    pub fn replaced(self) -> Option<Prev> {
+        match self {
+            ReinitOutcome::Replaced(prev) => Some(prev),
+            _ => None,
+        }
+    }
+
+    pub fn failed(self) -> Option<Error> {
+        match self {
+            ReinitOutcome::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    ChangeKind, ComponentMap, Keyed, KeyedError, KeyedStorage, NoTeardown, Teardown, WithArgs,
+};
+use rayon::prelude::*;
+
+// Inits run on the global rayon pool, so CPU-heavy sync initialisers spread
+// across all cores. The output map is identical to the serial counterpart.
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    /// Like [`init`](Self::init), building the components in parallel.
+    pub fn par_init(entries: impl IntoIterator<Item = (Key, Args)>, init: FnInit) -> Self
+    where
+        Key: Eq + std::hash::Hash + Send,
+        Args: Send,
+        Comp: Send,
+        FnInit: Fn(&Key, &Args) -> Comp + Sync,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let map = entries
+            .into_par_iter()
+            .map(|(key, args)| {
+                let component = (init)(&key, &args);
+                (key, WithArgs::new(component, args))
+            })
+            .collect();
+
+        Self::new(map, init, NoTeardown)
+    }
+
+    /// Like [`try_init`](Self::try_init), building the components in parallel.
+    ///
+    /// When several keys fail, which one is reported is unspecified.
+    pub fn par_try_init<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+    ) -> Result<Self, KeyedError<Key, Error>>
+    where
+        Key: Eq + std::hash::Hash + Send,
+        Args: Send,
+        Comp: Send,
+        Error: Send,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error> + Sync,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let map = entries
+            .into_par_iter()
+            .map(|(key, args)| match (init)(&key, &args) {
+                Ok(component) => Ok((key, WithArgs::new(component, args))),
+                Err(error) => Err(KeyedError::new(key, error)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(map, init, NoTeardown))
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Like [`reinit_all`](Self::reinit_all), computing every replacement in
+    /// parallel before applying them.
+    pub fn par_reinit_all(&mut self) -> impl Iterator<Item = Keyed<&Key, Comp>>
+    where
+        Key: Sync,
+        Args: Sync,
+        Comp: Send,
+        FnInit: Fn(&Key, &Args) -> Comp + Sync,
+    {
+        let entries: Vec<_> = self
+            .map
+            .iter()
+            .map(|(key, component)| (key, &component.args))
+            .collect();
+        let next_components: Vec<_> = entries
+            .into_par_iter()
+            .map(|(key, args)| {
+                self.events
+                    .instrument
+                    .build(key, args, || (self.init)(key, args))
+            })
+            .collect();
+
+        self.map
+            .iter_mut()
+            .zip(next_components)
+            .map(|((key, component), next)| {
+                let mut prev = component.replace_component(next);
+                self.teardown.teardown(key, &mut prev);
+                self.events
+                    .emit(key, ChangeKind::Reinitialized, Some(&*component));
+                Keyed::new(key, prev)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Like [`try_reinit_all`](Self::try_reinit_all), computing every
+    /// replacement in parallel before applying them.
+    pub fn par_try_reinit_all<Error>(
+        &mut self,
+    ) -> impl Iterator<Item = Keyed<&Key, Result<Comp, Error>>>
+    where
+        Key: Sync,
+        Args: Sync,
+        Comp: Send,
+        Error: Send + std::fmt::Debug,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error> + Sync,
+    {
+        let entries: Vec<_> = self
+            .map
+            .iter()
+            .map(|(key, component)| (key, &component.args))
+            .collect();
+        let results: Vec<_> = entries
+            .into_par_iter()
+            .map(|(key, args)| {
+                self.events
+                    .instrument
+                    .build(key, args, || (self.init)(key, args))
+            })
+            .collect();
+
+        self.map
+            .iter_mut()
+            .zip(results)
+            .map(|((key, component), result)| {
+                let result = result
+                    .map(|next| {
+                        let mut prev = component.replace_component(next);
+                        self.teardown.teardown(key, &mut prev);
+                        prev
+                    })
+                    .inspect_err(|_| component.record_failure());
+                self.events
+                    .emit_result(key, &result, ChangeKind::Reinitialized, &*component);
+                Keyed::new(key, result)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[test]
+    fn test_par_init() {
+        let init = |_key: &usize, args: &usize| Counter(*args * 2);
+
+        let manager = ComponentMap::par_init((0..100).map(|key| (key, key)), init);
+
+        assert_eq!(manager.len(), 100);
+        assert_eq!(manager.get(&42), Some(&Counter(84)));
+    }
+
+    #[test]
+    fn test_par_try_init() {
+        let init = |_key: &usize, args: &usize| {
+            if *args == 7 {
+                Err(TestError)
+            } else {
+                Ok(Counter(*args))
+            }
+        };
+
+        let manager = ComponentMap::par_try_init((0..5).map(|key| (key, key)), init).unwrap();
+        assert_eq!(manager.get(&3), Some(&Counter(3)));
+
+        let error = ComponentMap::par_try_init((0..10).map(|key| (key, key)), init)
+            .err()
+            .unwrap();
+        assert_eq!(error, KeyedError::new(7, TestError));
+    }
+
+    #[test]
+    fn test_par_reinit_all() {
+        let init = |key: &usize, args: &usize| Counter(key + args);
+        let mut manager = ComponentMap::init((0..50).map(|key| (key, 0)), init);
+        manager.set_args(&10, 5);
+
+        let mut prev: Vec<_> = manager
+            .par_reinit_all()
+            .map(|Keyed { key, value }| (*key, value))
+            .collect();
+        prev.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(prev.len(), 50);
+        assert_eq!(prev[10], (10, Counter(10)));
+        assert_eq!(manager.get(&10), Some(&Counter(15)));
+        assert_eq!(manager.get(&20), Some(&Counter(20)));
+    }
+
+    #[test]
+    fn test_par_try_reinit_all() {
+        let init = |_key: &&str, args: &usize| {
+            if *args == 0 {
+                Err(TestError)
+            } else {
+                Ok(Counter(*args))
+            }
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1), ("key2", 2)], init).unwrap();
+        manager.set_args(&"key1", 10);
+        manager.set_args(&"key2", 0);
+
+        let mut results: Vec<_> = manager
+            .par_try_reinit_all()
+            .map(|Keyed { key, value }| (*key, value))
+            .collect();
+        results.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(
+            results,
+            vec![("key1", Ok(Counter(1))), ("key2", Err(TestError))]
+        );
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(2)));
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{AsyncTeardown, ChangeKind, ComponentMap, KeyedStorage, Lookup, Teardown, WithArgs};
+use core::borrow::Borrow;
+
+// A paused key keeps only its args, outside of `map`, so lookups miss it like
+// any removed key, while its tags and dependencies stay in place. If the key
+// is inserted again while paused, that entry wins and the paused args are
+// discarded on resume.
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Tears down the component for `key` while keeping its args, so it can
+    /// later be rebuilt with [`resume`](Self::resume).
+    ///
+    /// Returns `false` if the key is missing.
+    pub fn pause<Q>(&mut self, key: &Q) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        match self.take_entry(key) {
+            Some((key, entry)) => {
+                self.paused.insert(key, entry.args);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Like [`pause`](Self::pause), awaiting the async teardown.
+    pub async fn pause_async<Q>(&mut self, key: &Q) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let Some((key, mut entry)) = self.map.take(key) else {
+            return false;
+        };
+        self.events.emit(&key, ChangeKind::Removed, Some(&entry));
+        self.teardown
+            .teardown_async(&key, &mut entry.component)
+            .await;
+        self.paused.insert(key, entry.args);
+        true
+    }
+
+    pub fn is_paused<Q>(&self, key: &Q) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.paused.contains_key(key) && !self.map.contains_key(key)
+    }
+
+    pub fn paused_keys(&self) -> impl Iterator<Item = &Key>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.paused
+            .keys()
+            .filter(|key| !self.map.contains_key(*key))
+    }
+
+    /// Rebuilds the component for a paused `key` from its retained args.
+    ///
+    /// Returns `None` if the key is not paused.
+    pub fn resume<Q>(&mut self, key: &Q) -> Option<&Comp>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let (key, args) = self.take_paused(key)?;
+        let component = self
+            .events
+            .instrument
+            .build(&key, &args, || (self.init)(&key, &args));
+        Some(self.insert_resumed(key, args, component))
+    }
+
+    /// Like [`resume`](Self::resume) for fallible inits; on failure the key
+    /// stays paused.
+    pub fn try_resume<Q, Error>(&mut self, key: &Q) -> Option<Result<&Comp, Error>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let (key, args) = self.take_paused(key)?;
+        let result = self
+            .events
+            .instrument
+            .build(&key, &args, || (self.init)(&key, &args));
+        Some(match result {
+            Ok(component) => Ok(self.insert_resumed(key, args, component)),
+            Err(error) => Err(self.keep_paused(key, args, error)),
+        })
+    }
+
+    pub async fn resume_async<Q>(&mut self, key: &Q) -> Option<&Comp>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: AsyncFn(&Key, &Args) -> Comp,
+    {
+        let (key, args) = self.take_paused(key)?;
+        let span = self.events.instrument.start(&key, &args);
+        let component = (self.init)(&key, &args).await;
+        span.finish(&key);
+        Some(self.insert_resumed(key, args, component))
+    }
+
+    pub async fn try_resume_async<Q, Error>(&mut self, key: &Q) -> Option<Result<&Comp, Error>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let (key, args) = self.take_paused(key)?;
+        let span = self.events.instrument.start(&key, &args);
+        let result = (self.init)(&key, &args).await;
+        span.finish(&key);
+        Some(match result {
+            Ok(component) => Ok(self.insert_resumed(key, args, component)),
+            Err(error) => Err(self.keep_paused(key, args, error)),
+        })
+    }
+
+    fn take_paused<Q>(&mut self, key: &Q) -> Option<(Key, Args)>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        let (key, args) = self.paused.remove_entry(key)?;
+        (!self.map.contains_key(&key)).then_some((key, args))
+    }
+
+    fn insert_resumed(&mut self, key: Key, args: Args, component: Comp) -> &Comp
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.reserve_slot(&key);
+        let (_, entry) = self.insert_entry(key, WithArgs::new(component, args));
+        &entry.component
+    }
+
+    fn keep_paused<Error>(&mut self, key: Key, args: Args, error: Error) -> Error
+    where
+        Key: Eq + core::hash::Hash,
+        Error: core::fmt::Debug,
+    {
+        self.events.emit_failed(&key, &args, &error);
+        self.paused.insert(key, args);
+        error
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::EntryState;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[test]
+    fn test_pause_and_resume() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1), ("key2", 2)], init).with_teardown(
+            move |key: &&str, _: &mut Counter| closed_clone.lock().unwrap().push(*key),
+        );
+
+        assert!(manager.pause(&"key1"));
+        assert!(!manager.pause(&"key1"));
+        assert_eq!(*closed.lock().unwrap(), vec!["key1"]);
+        assert_eq!(manager.get(&"key1"), None);
+        assert!(manager.is_paused(&"key1"));
+        assert_eq!(manager.state(&"key1"), Some(EntryState::Paused));
+        assert_eq!(manager.paused_keys().collect::<Vec<_>>(), vec![&"key1"]);
+
+        assert_eq!(manager.resume(&"key1"), Some(&Counter(1)));
+        assert_eq!(manager.resume(&"key1"), None);
+        assert!(!manager.is_paused(&"key1"));
+        assert_eq!(manager.state(&"key1"), Some(EntryState::Ready));
+    }
+
+    #[tokio::test]
+    async fn test_failed_resume_stays_paused() {
+        let fail = Arc::new(Mutex::new(true));
+        let fail_clone = fail.clone();
+        let init = move |_key: &&str, args: &usize| {
+            let result = match *fail_clone.lock().unwrap() {
+                true => Err(TestError),
+                false => Ok(Counter(*args)),
+            };
+            async move { result }
+        };
+        *fail.lock().unwrap() = false;
+        let mut manager = ComponentMap::try_init_async([("key1", 1)], init)
+            .await
+            .unwrap();
+        assert!(manager.pause_async(&"key1").await);
+
+        *fail.lock().unwrap() = true;
+        assert_eq!(
+            manager.try_resume_async(&"key1").await,
+            Some(Err(TestError))
+        );
+        assert!(manager.is_paused(&"key1"));
+
+        *fail.lock().unwrap() = false;
+        assert_eq!(
+            manager.try_resume_async(&"key1").await,
+            Some(Ok(&Counter(1)))
+        );
+        assert_eq!(manager.len(), 1);
+    }
+}
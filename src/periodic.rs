@@ -0,0 +1,241 @@
+use crate::{SharedComponentMap, Teardown, retry::retry_async, shared::InPlace};
+use futures::{
+    FutureExt, StreamExt,
+    future::{self, BoxFuture, Either},
+    stream::{self, BoxStream},
+};
+use futures_timer::Delay;
+use std::{collections::HashSet, convert::Infallible, fmt, future::Future, time::Duration};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+// Each pass reinitialises through the shared map's per-key methods, so readers
+// keep being served the previous components while a pass is running and a
+// pass never overlaps a concurrent writer for the same key.
+
+/// Handle to a background task started by
+/// [`spawn_periodic_reinit`](SharedComponentMap::spawn_periodic_reinit),
+/// [`spawn_triggered_reinit`](SharedComponentMap::spawn_triggered_reinit), or
+/// `spawn_config_reload` (`hot-reload` feature).
+///
+/// Dropping the handle stops the task once its current pass, if any, has
+/// finished, so no reinit is cancelled halfway.
+#[must_use = "the task stops when its handle is dropped"]
+#[derive(Debug)]
+pub struct ReinitTask {
+    pub(crate) stop: oneshot::Sender<()>,
+    pub(crate) task: JoinHandle<()>,
+}
+
+impl ReinitTask {
+    /// Stops the task, waiting for a pass in progress to finish.
+    pub async fn stop(self) {
+        drop(self.stop);
+        let _ = self.task.await;
+    }
+}
+
+/// Keys a background pass reinitialises.
+pub(crate) enum Pass<Key> {
+    All,
+    Keys(HashSet<Key>),
+}
+
+/// Reinitialises one key on a handle to the map it owns.
+pub(crate) type ReinitOne<Map, Key> = fn(Map, Key) -> BoxFuture<'static, ()>;
+
+impl<Key, Args, Comp, FnInit, FnDrop> SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Clone + Eq + std::hash::Hash + Send + Sync + 'static,
+    Args: Clone + Send + Sync + 'static,
+    Comp: Clone + Send + Sync + 'static,
+    FnInit: Send + Sync + 'static,
+    FnDrop: Teardown<Key, Comp> + Send + Sync + 'static,
+{
+    /// Spawns a task that reinitialises every component matching `filter`
+    /// each `interval`, at most
+    /// [`ComponentMapConfig::concurrency_limit`](crate::ComponentMapConfig::concurrency_limit)
+    /// at a time. Pass `|_| true` to refresh the whole map.
+    pub fn spawn_periodic_reinit<Fut>(
+        &self,
+        interval: Duration,
+        filter: impl Fn(&Key) -> bool + Send + 'static,
+    ) -> ReinitTask
+    where
+        FnInit: Fn(&Key, &Args) -> Fut + Clone,
+        Fut: Future<Output = Comp> + Send + 'static,
+    {
+        self.spawn_passes(ticks(interval), filter, Self::reinit_one)
+    }
+
+    /// Like [`spawn_periodic_reinit`](Self::spawn_periodic_reinit) for
+    /// fallible inits, retried according to
+    /// [`ComponentMapConfig::retry`](crate::ComponentMapConfig::retry). Keys
+    /// that still fail keep their component and are marked
+    /// [`Failed`](crate::EntryState::Failed) until a later pass succeeds.
+    pub fn spawn_periodic_try_reinit<Fut, Error>(
+        &self,
+        interval: Duration,
+        filter: impl Fn(&Key) -> bool + Send + 'static,
+    ) -> ReinitTask
+    where
+        Error: fmt::Debug + Send + 'static,
+        FnInit: Fn(&Key, &Args) -> Fut + Clone,
+        Fut: Future<Output = Result<Comp, Error>> + Send + 'static,
+    {
+        self.spawn_passes(ticks(interval), filter, Self::try_reinit_one)
+    }
+
+    pub(crate) fn reinit_one<Fut>(shared: Self, key: Key) -> BoxFuture<'static, ()>
+    where
+        FnInit: Fn(&Key, &Args) -> Fut + Clone,
+        Fut: Future<Output = Comp> + Send + 'static,
+    {
+        async move {
+            shared
+                .reinit_with(
+                    key,
+                    |init, key, args| async move { Ok::<_, Infallible>((init)(&key, &args).await) },
+                    InPlace,
+                )
+                .await;
+        }
+        .boxed()
+    }
+
+    pub(crate) fn try_reinit_one<Fut, Error>(shared: Self, key: Key) -> BoxFuture<'static, ()>
+    where
+        Error: fmt::Debug + Send + 'static,
+        FnInit: Fn(&Key, &Args) -> Fut + Clone,
+        Fut: Future<Output = Result<Comp, Error>> + Send + 'static,
+    {
+        async move {
+            let retry = shared.read().config.retry;
+            let outcome = shared
+                .reinit_with(
+                    key.clone(),
+                    move |init, key, args| async move {
+                        retry_async(retry, || (init)(&key, &args)).await
+                    },
+                    InPlace,
+                )
+                .await;
+            // Nobody awaits a background reinit, so keep its error on the entry
+            if let Some(error) = outcome.failed() {
+                shared.write().record_error(&key, &error);
+            }
+        }
+        .boxed()
+    }
+
+    /// Spawns a task running a pass for every item of `passes` until it ends or
+    /// the returned handle is dropped.
+    // Reinits are boxed and driven by `buffer_unordered` rather than
+    // `join_bounded`, whose per-item closure the compiler cannot prove `Send`
+    // inside a spawned generic future.
+    pub(crate) fn spawn_passes(
+        &self,
+        mut passes: BoxStream<'static, Pass<Key>>,
+        filter: impl Fn(&Key) -> bool + Send + 'static,
+        reinit: ReinitOne<Self, Key>,
+    ) -> ReinitTask {
+        let shared = self.clone();
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            while let Either::Left((Some(pass), _)) =
+                future::select(passes.next(), &mut stopped).await
+            {
+                let (keys, limit) = shared.due(pass, &filter);
+                let limit = limit.unwrap_or(keys.len()).max(1);
+                let reinits: Vec<_> = keys
+                    .into_iter()
+                    .map(|key| reinit(shared.clone(), key))
+                    .collect();
+                stream::iter(reinits).buffer_unordered(limit).count().await;
+            }
+        });
+        ReinitTask { stop, task }
+    }
+
+    fn due(&self, pass: Pass<Key>, filter: impl Fn(&Key) -> bool) -> (Vec<Key>, Option<usize>) {
+        let map = self.read();
+        let keys = match pass {
+            Pass::All => map.map.keys().filter(|key| filter(key)).cloned().collect(),
+            Pass::Keys(keys) => keys.into_iter().filter(|key| filter(key)).collect(),
+        };
+        (keys, map.config.concurrency_limit)
+    }
+}
+
+fn ticks<Key: Send + 'static>(interval: Duration) -> BoxStream<'static, Pass<Key>> {
+    stream::unfold((), move |()| async move {
+        Delay::new(interval).await;
+        Some((Pass::All, ()))
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComponentMap, EntryState, SharedComponentMap};
+    use futures_timer::Delay;
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[tokio::test]
+    async fn test_periodic_reinit_refreshes_filtered_keys() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&str, _args: &()| {
+            let call = calls_clone.fetch_add(1, Ordering::Relaxed);
+            async move { Counter(call) }
+        };
+        let map = ComponentMap::init_async([("key1", ()), ("key2", ())], init).await;
+        let shared = SharedComponentMap::from(map);
+
+        let periodic = shared.spawn_periodic_reinit(Duration::from_millis(5), |key| *key == "key1");
+        while calls.load(Ordering::Relaxed) < 4 {
+            Delay::new(Duration::from_millis(5)).await;
+        }
+        periodic.stop().await;
+
+        assert!(shared.get(&"key1").unwrap().0 >= 2);
+        assert!(shared.get(&"key2").unwrap().0 < 2);
+    }
+
+    #[tokio::test]
+    async fn test_periodic_try_reinit_marks_failures() {
+        let init = |_key: &&str, args: &usize| {
+            let args = *args;
+            async move {
+                match args {
+                    0 => Err(TestError),
+                    _ => Ok(Counter(args)),
+                }
+            }
+        };
+        let map = ComponentMap::try_init_async([("key1", 1)], init)
+            .await
+            .unwrap();
+        let shared = SharedComponentMap::from(map);
+        shared.write().set_args(&"key1", 0);
+
+        let periodic = shared.spawn_periodic_try_reinit(Duration::from_millis(5), |_| true);
+        while shared.read().state(&"key1") != Some(EntryState::Failed(Some("TestError"))) {
+            Delay::new(Duration::from_millis(5)).await;
+        }
+        drop(periodic);
+
+        assert_eq!(shared.get(&"key1"), Some(Counter(1)));
+    }
+}
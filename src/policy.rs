@@ -0,0 +1,340 @@
+use crate::{
+    ChangeKind, ComponentMap, Entries, Keyed, KeyedError, KeyedStorage, NoTeardown, Teardown,
+    WithArgs,
+};
+use alloc::vec::Vec;
+
+/// Controls how the `*_with_policy` batch operations react to a failed init.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop at the first failure and return it; remaining entries are not attempted.
+    FailFast,
+    /// Attempt every entry and return all failures if there were any.
+    CollectAll,
+    /// Attempt every entry and skip the failures.
+    BestEffort,
+}
+
+impl ErrorPolicy {
+    /// Records `failure` and returns whether the batch should keep going.
+    fn record<Key, Error>(
+        self,
+        failures: &mut Vec<KeyedError<Key, Error>>,
+        failure: KeyedError<Key, Error>,
+    ) -> bool {
+        match self {
+            ErrorPolicy::FailFast => {
+                failures.push(failure);
+                false
+            }
+            ErrorPolicy::CollectAll => {
+                failures.push(failure);
+                true
+            }
+            ErrorPolicy::BestEffort => true,
+        }
+    }
+}
+
+/// Minimum share of entries that must initialise for
+/// [`try_init_with_threshold`](ComponentMap::try_init_with_threshold) to succeed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    AtLeast(usize),
+    /// Fraction of all entries, between `0.0` and `1.0`.
+    Fraction(f64),
+}
+
+impl Threshold {
+    fn is_met(self, succeeded: usize, total: usize) -> bool {
+        match self {
+            Threshold::AtLeast(count) => succeeded >= count,
+            Threshold::Fraction(fraction) => {
+                total == 0 || succeeded as f64 / total as f64 >= fraction
+            }
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    pub fn try_init_with_policy<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        policy: ErrorPolicy,
+    ) -> Result<Self, Vec<KeyedError<Key, Error>>>
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+    {
+        let mut map = Entries::new();
+        let mut failures = Vec::new();
+
+        for (key, args) in entries {
+            match (init)(&key, &args) {
+                Ok(component) => {
+                    map.insert(key, WithArgs::new(component, args));
+                }
+                Err(error) => {
+                    if !policy.record(&mut failures, KeyedError::new(key, error)) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(Self::new(map, init, NoTeardown))
+        } else {
+            Err(failures)
+        }
+    }
+
+    /// Keeps the successfully initialised entries as long as `threshold` is
+    /// met, returning the map together with the failures; otherwise returns
+    /// every failure.
+    #[allow(clippy::type_complexity)]
+    pub fn try_init_with_threshold<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        threshold: Threshold,
+    ) -> Result<(Self, Vec<KeyedError<Key, Error>>), Vec<KeyedError<Key, Error>>>
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+    {
+        let (map, failures) = Self::try_init_partial(entries, init);
+
+        if threshold.is_met(map.len(), map.len() + failures.len()) {
+            Ok((map, failures))
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Reinitialises every component according to `policy`.
+    ///
+    /// Components replaced before a failure stay replaced; on success the
+    /// previous components are returned.
+    #[allow(clippy::type_complexity)]
+    pub fn try_reinit_all_with_policy<Error>(
+        &mut self,
+        policy: ErrorPolicy,
+    ) -> Result<Vec<Keyed<&Key, Comp>>, Vec<KeyedError<&Key, Error>>>
+    where
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let mut replaced = Vec::new();
+        let mut failures = Vec::new();
+
+        for (key, component) in self.map.iter_mut() {
+            let result = self
+                .events
+                .instrument
+                .build(key, &component.args, || (self.init)(key, &component.args));
+            match result {
+                Ok(next) => {
+                    let mut prev = component.replace_component(next);
+                    self.teardown.teardown(key, &mut prev);
+                    self.events
+                        .emit(key, ChangeKind::Reinitialized, Some(&*component));
+                    replaced.push(Keyed::new(key, prev));
+                }
+                Err(error) => {
+                    self.events.emit_failed(key, &component.args, &error);
+                    if !policy.record(&mut failures, KeyedError::new(key, error)) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(replaced)
+        } else {
+            Err(failures)
+        }
+    }
+
+    /// Applies `updates` according to `policy`.
+    ///
+    /// Updates applied before a failure stay applied; on success the previous
+    /// entries are returned.
+    #[allow(clippy::type_complexity)]
+    pub fn try_update_with_policy<Error>(
+        &mut self,
+        updates: impl IntoIterator<Item = (Key, Args)>,
+        policy: ErrorPolicy,
+    ) -> Result<Vec<Keyed<Key, Option<WithArgs<Args, Comp>>>>, Vec<KeyedError<Key, Error>>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let mut updated = Vec::new();
+        let mut failures = Vec::new();
+
+        for (key, args) in updates {
+            let result = self
+                .events
+                .instrument
+                .build(&key, &args, || (self.init)(&key, &args));
+            match result {
+                Ok(component) => {
+                    let prev = self
+                        .map
+                        .insert(key.clone(), WithArgs::new(component, args))
+                        .map(|mut prev| {
+                            self.teardown.teardown(&key, &mut prev.component);
+                            prev
+                        });
+                    self.events
+                        .emit(&key, ChangeKind::upsert(prev.is_some()), self.map.get(&key));
+                    self.enforce_capacity();
+                    updated.push(Keyed::new(key, prev));
+                }
+                Err(error) => {
+                    self.events.emit_failed(&key, &args, &error);
+                    if !policy.record(&mut failures, KeyedError::new(key, error)) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(updated)
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError(usize);
+
+    fn entries() -> [(&'static str, usize); 3] {
+        [("key1", 1), ("key2", 0), ("key3", 0)]
+    }
+
+    #[test]
+    fn test_try_init_with_policy() {
+        let init = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError(*args)),
+            value => Ok(Counter(value)),
+        };
+
+        let fail_fast = ComponentMap::try_init_with_policy(entries(), init, ErrorPolicy::FailFast);
+        assert_eq!(fail_fast.err().map(|failures| failures.len()), Some(1));
+
+        let collect_all =
+            ComponentMap::try_init_with_policy(entries(), init, ErrorPolicy::CollectAll);
+        let mut failed: Vec<_> = collect_all
+            .err()
+            .unwrap()
+            .into_iter()
+            .map(|failure| failure.key)
+            .collect();
+        failed.sort();
+        assert_eq!(failed, vec!["key2", "key3"]);
+
+        let best_effort =
+            ComponentMap::try_init_with_policy(entries(), init, ErrorPolicy::BestEffort).unwrap();
+        assert_eq!(best_effort.len(), 1);
+        assert_eq!(best_effort.get(&"key1"), Some(&Counter(1)));
+    }
+
+    #[test]
+    fn test_try_reinit_all_with_policy() {
+        let init = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError(*args)),
+            value => Ok(Counter(value)),
+        };
+
+        let mut manager = ComponentMap::try_init([("key1", 1), ("key2", 2)], init).unwrap();
+        manager.set_args(&"key2", 0);
+
+        let failures = manager
+            .try_reinit_all_with_policy(ErrorPolicy::CollectAll)
+            .err()
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(*failures[0].key, "key2");
+
+        let replaced = manager
+            .try_reinit_all_with_policy(ErrorPolicy::BestEffort)
+            .unwrap();
+        assert_eq!(replaced.len(), 1);
+        assert_eq!(*replaced[0].key, "key1");
+        assert_eq!(manager.get(&"key2"), Some(&Counter(2)));
+    }
+
+    #[test]
+    fn test_try_update_with_policy() {
+        let init = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError(*args)),
+            value => Ok(Counter(value)),
+        };
+
+        let mut manager = ComponentMap::try_init([("key1", 1)], init).unwrap();
+
+        let failures = manager
+            .try_update_with_policy([("key2", 0), ("key1", 10)], ErrorPolicy::FailFast)
+            .err()
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].key, "key2");
+        // Entries after the failure were not attempted
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+
+        let updated = manager
+            .try_update_with_policy([("key2", 0), ("key1", 10)], ErrorPolicy::BestEffort)
+            .unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].key, "key1");
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert!(!manager.contains_key(&"key2"));
+    }
+
+    #[test]
+    fn test_try_init_with_threshold() {
+        let init = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError(*args)),
+            value => Ok(Counter(value)),
+        };
+
+        let (manager, failures) =
+            ComponentMap::try_init_with_threshold(entries(), init, Threshold::AtLeast(1)).unwrap();
+        assert_eq!(manager.len(), 1);
+        assert_eq!(failures.len(), 2);
+
+        let failures =
+            ComponentMap::try_init_with_threshold(entries(), init, Threshold::Fraction(0.5))
+                .err()
+                .unwrap();
+        assert_eq!(failures.len(), 2);
+
+        let (manager, failures) = ComponentMap::try_init_with_threshold(
+            [("key1", 1), ("key2", 2), ("key3", 0)],
+            init,
+            Threshold::Fraction(0.5),
+        )
+        .unwrap();
+        assert_eq!(manager.len(), 2);
+        assert_eq!(failures[0].key, "key3");
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{ComponentMap, KeyedStorage, Lookup, Teardown, WithArgs, dependency::Dependencies};
+use alloc::{boxed::Box, vec::Vec};
+use core::{borrow::Borrow, cmp::Reverse, fmt, ops::Deref};
+
+type PriorityFn<Key, Args> = Box<dyn Fn(&Key, &Args) -> i32 + Send + Sync>;
+
+/// The function registered with [`prioritize_by`](ComponentMap::prioritize_by),
+/// consulted for every entry without a priority of its own.
+pub(crate) struct Prioritizer<Key, Args>(Option<PriorityFn<Key, Args>>);
+
+impl<Key, Args> Prioritizer<Key, Args> {
+    pub(crate) fn priority<Comp>(&self, key: &Key, entry: &WithArgs<Args, Comp>) -> i32 {
+        match (entry.priority, &self.0) {
+            (Some(priority), _) => priority,
+            (None, Some(priority_by)) => priority_by(key, &entry.args),
+            (None, None) => 0,
+        }
+    }
+}
+
+impl<Key, Args> Default for Prioritizer<Key, Args> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<Key, Args> fmt::Debug for Prioritizer<Key, Args> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.0 {
+            Some(_) => "Some",
+            None => "None",
+        })
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Sets the priority of `key` for the `reinit_all*` operations, which
+    /// handle higher priorities first; entries default to 0. The priority is
+    /// dropped when the entry is replaced with new args, falling back to the
+    /// [`prioritize_by`](Self::prioritize_by) function, if any.
+    ///
+    /// Returns `false` if the key is missing.
+    pub fn set_priority<Q>(&mut self, key: &Q, priority: i32) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        match self.map.get_mut(key) {
+            Some(component) => {
+                component.priority = Some(priority);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Derives the priority of every entry from its key and args, including
+    /// entries inserted or updated later, replacing any previous function and
+    /// the priorities set with [`set_priority`](Self::set_priority) so far.
+    pub fn prioritize_by(&mut self, priority: impl Fn(&Key, &Args) -> i32 + Send + Sync + 'static) {
+        for component in self.map.values_mut() {
+            component.priority = None;
+        }
+        self.priorities = Prioritizer(Some(Box::new(priority)));
+    }
+
+    /// The priority in effect for `key`, or `None` if the key is missing.
+    pub fn priority<Q>(&self, key: &Q) -> Option<i32>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        let (key, entry) = self.map.get_key_value(key)?;
+        Some(self.priorities.priority(key, entry))
+    }
+}
+
+/// Orders entries after their dependencies, and otherwise from the highest
+/// priority to the lowest, keeping the map's order among equals.
+pub(crate) fn prioritized<'a, Key, E, Args, Comp>(
+    entries: impl Iterator<Item = (&'a Key, E)>,
+    dependencies: &Dependencies<Key>,
+    priorities: &Prioritizer<Key, Args>,
+) -> Vec<(&'a Key, E)>
+where
+    Key: Eq + core::hash::Hash,
+    E: Deref<Target = WithArgs<Args, Comp>>,
+{
+    let depths = dependencies.depths();
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_by_key(|(key, component)| {
+        let depth = depths.get(key).copied().unwrap_or_default();
+        (depth, Reverse(priorities.priority(key, component)))
+    });
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_reinit_all_follows_priority() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("low", 1), ("critical", 100), ("normal", 10)], init);
+        manager.prioritize_by(|_, args| *args as i32);
+        assert!(manager.set_priority(&"low", -1));
+        assert!(!manager.set_priority(&"missing", 5));
+
+        let order: Vec<_> = manager.reinit_all().map(|prev| *prev.key).collect();
+        assert_eq!(order, vec!["critical", "normal", "low"]);
+        assert_eq!(manager.priority(&"low"), Some(-1));
+    }
+
+    #[test]
+    fn test_prioritize_by_applies_to_later_inserts_and_updates() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("low", 1)], init);
+        manager.prioritize_by(|_, args| *args as i32);
+
+        manager
+            .update([("critical", 100), ("normal", 10)])
+            .for_each(drop);
+        assert!(manager.set_priority(&"low", 50));
+        assert_eq!(manager.priority(&"critical"), Some(100));
+
+        let order: Vec<_> = manager.reinit_all().map(|prev| *prev.key).collect();
+        assert_eq!(order, vec!["critical", "low", "normal"]);
+
+        // Updating drops the explicit priority in favour of the function
+        manager.update([("low", 2)]).for_each(drop);
+        assert_eq!(manager.priority(&"low"), Some(2));
+        assert_eq!(manager.priority(&"missing"), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn test_reinit_all_stream_yields_tiers_in_order() {
+        use futures::StreamExt;
+
+        let init = async |_key: &&str, args: &usize| {
+            // Lower priorities finish their init first
+            for _ in 0..*args {
+                tokio::task::yield_now().await;
+            }
+            Counter(*args)
+        };
+        let mut manager =
+            ComponentMap::init_async([("low", 1), ("high", 5), ("high2", 3)], init).await;
+        manager.set_priority(&"high", 1);
+        manager.set_priority(&"high2", 1);
+
+        let order: Vec<_> = manager
+            .reinit_all_stream()
+            .map(|prev| prev.key)
+            .collect()
+            .await;
+        assert_eq!(order, vec!["high2", "high", "low"]);
+    }
+}
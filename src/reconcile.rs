@@ -0,0 +1,403 @@
+use crate::{
+    ComponentMap, KeyedError, KeyedStorage, Teardown, WithArgs, events::Instrumentation,
+    storage::HashMap,
+};
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+/// What [`sync_with`](ComponentMap::sync_with) or
+/// [`apply_changeset`](ComponentMap::apply_changeset) changed, by key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncReport<Key, Error = Infallible> {
+    pub inserted: Vec<Key>,
+    /// Keys rebuilt because their args changed.
+    pub updated: Vec<Key>,
+    /// Keys no longer desired, torn down and removed.
+    pub removed: Vec<Key>,
+    pub unchanged: Vec<Key>,
+    /// Keys whose init failed; existing entries were left untouched.
+    pub failed: Vec<KeyedError<Key, Error>>,
+}
+
+impl<Key, Error> SyncReport<Key, Error> {
+    fn new() -> Self {
+        Self {
+            inserted: Vec::new(),
+            updated: Vec::new(),
+            removed: Vec::new(),
+            unchanged: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    /// Whether the map already matched the desired state.
+    pub fn is_noop(&self) -> bool {
+        self.inserted.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.failed.is_empty()
+    }
+}
+
+/// Delta between a [`ComponentMap`] and a desired state, computed by
+/// [`diff`](ComponentMap::diff) without touching any component.
+///
+/// Entries can be dropped from any list before the set is applied to veto
+/// those changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeSet<Key, Args> {
+    pub additions: Vec<(Key, Args)>,
+    /// Existing keys whose args differ from the desired ones.
+    pub modifications: Vec<(Key, Args)>,
+    pub removals: Vec<Key>,
+    pub unchanged: Vec<Key>,
+}
+
+impl<Key, Args> ChangeSet<Key, Args> {
+    pub fn is_empty(&self) -> bool {
+        self.additions.is_empty() && self.modifications.is_empty() && self.removals.is_empty()
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Compares the map against the complete `desired` state.
+    ///
+    /// Dirty entries count as modified even when their args match, since their
+    /// component was built from older args. Paused keys are compared by their
+    /// retained args and removed like any other key once no longer desired.
+    pub fn diff(&self, desired: impl IntoIterator<Item = (Key, Args)>) -> ChangeSet<Key, Args>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        Args: PartialEq,
+    {
+        let desired: HashMap<_, _> = desired.into_iter().collect();
+        let mut changes = ChangeSet {
+            additions: Vec::new(),
+            modifications: Vec::new(),
+            removals: self
+                .map
+                .keys()
+                .chain(self.paused_keys())
+                .filter(|key| !desired.contains_key(*key))
+                .cloned()
+                .collect(),
+            unchanged: Vec::new(),
+        };
+
+        for (key, args) in desired {
+            match self.map.get(&key) {
+                None => match self.paused.get(&key) {
+                    Some(paused) if *paused == args => changes.unchanged.push(key),
+                    Some(_) => changes.modifications.push((key, args)),
+                    None => changes.additions.push((key, args)),
+                },
+                Some(entry) if !entry.is_dirty() && entry.args == args => {
+                    changes.unchanged.push(key)
+                }
+                Some(_) => changes.modifications.push((key, args)),
+            }
+        }
+
+        changes
+    }
+
+    /// Applies `changes`: removals go through the teardown, additions and
+    /// modifications are initialised and inserted.
+    ///
+    /// Paused keys stay paused: a modification only replaces the args they
+    /// will be resumed with.
+    pub fn apply_changeset(&mut self, changes: ChangeSet<Key, Args>) -> SyncReport<Key>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        self.apply_with(changes, |init, key, args| Ok(init(key, args)))
+    }
+
+    /// Like [`apply_changeset`](Self::apply_changeset) for fallible inits.
+    /// Failed keys keep their current entry and are reported in
+    /// [`SyncReport::failed`].
+    pub fn try_apply_changeset<Error>(
+        &mut self,
+        changes: ChangeSet<Key, Args>,
+    ) -> SyncReport<Key, Error>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        self.apply_with(changes, |init, key, args| init(key, args))
+    }
+
+    /// Reconciles the map against the complete `desired` state: new keys are
+    /// inserted, keys whose args changed are rebuilt, and keys missing from
+    /// `desired` are removed through the teardown.
+    pub fn sync_with(&mut self, desired: impl IntoIterator<Item = (Key, Args)>) -> SyncReport<Key>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        Args: PartialEq,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let changes = self.diff(desired);
+        self.apply_changeset(changes)
+    }
+
+    /// Like [`sync_with`](Self::sync_with) for fallible inits. Failed keys keep
+    /// their current entry and are reported in [`SyncReport::failed`].
+    pub fn try_sync_with<Error>(
+        &mut self,
+        desired: impl IntoIterator<Item = (Key, Args)>,
+    ) -> SyncReport<Key, Error>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        Args: PartialEq,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let changes = self.diff(desired);
+        self.try_apply_changeset(changes)
+    }
+
+    fn apply_with<Error>(
+        &mut self,
+        changes: ChangeSet<Key, Args>,
+        build: impl Fn(&FnInit, &Key, &Args) -> Result<Comp, Error>,
+    ) -> SyncReport<Key, Error>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        Error: core::fmt::Debug,
+    {
+        let built = changes.build(
+            |key| self.is_paused(key),
+            &self.events.instrument,
+            |key, args| build(&self.init, key, args),
+        );
+        self.apply_built(built, build)
+    }
+
+    /// Applies changes whose components were built beforehand. Keys paused
+    /// since then only take the new args, while keys resumed since then are
+    /// built here with `build`.
+    pub(crate) fn apply_built<Error>(
+        &mut self,
+        built: BuiltChanges<Key, Args, Comp, Error>,
+        build: impl Fn(&FnInit, &Key, &Args) -> Result<Comp, Error>,
+    ) -> SyncReport<Key, Error>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        Error: core::fmt::Debug,
+    {
+        let mut report = SyncReport::new();
+        report.unchanged = built.unchanged;
+
+        // Removing first frees capacity for the keys about to be inserted
+        for key in built.removals {
+            let paused = self.is_paused(&key);
+            if self.remove(&key).is_some() || paused {
+                report.removed.push(key);
+            }
+        }
+
+        for (key, args, result) in built.entries {
+            let result = match (self.is_paused(&key), result) {
+                (true, result) => {
+                    if let Some(Ok(mut discarded)) = result {
+                        self.teardown.teardown(&key, &mut discarded);
+                    }
+                    self.paused.insert(key.clone(), args);
+                    report.updated.push(key);
+                    continue;
+                }
+                (false, Some(result)) => result,
+                (false, None) => self
+                    .events
+                    .instrument
+                    .build(&key, &args, || build(&self.init, &key, &args)),
+            };
+            match result {
+                Ok(component) => match self.insert_component(key.clone(), args, component) {
+                    Some(_) => report.updated.push(key),
+                    None => report.inserted.push(key),
+                },
+                Err(error) => {
+                    self.events.emit_failed(&key, &args, &error);
+                    report.failed.push(KeyedError::new(key, error));
+                }
+            }
+        }
+
+        report
+    }
+}
+
+/// A desired entry and the result of its init, `None` while it is paused.
+type BuiltEntry<Key, Args, Comp, Error> = (Key, Args, Option<Result<Comp, Error>>);
+
+/// A [`ChangeSet`] whose components were built before it is applied, so the
+/// init can run without holding a lock on the map.
+pub(crate) struct BuiltChanges<Key, Args, Comp, Error> {
+    removals: Vec<Key>,
+    unchanged: Vec<Key>,
+    entries: Vec<BuiltEntry<Key, Args, Comp, Error>>,
+}
+
+impl<Key, Args> ChangeSet<Key, Args> {
+    /// Builds the additions and modifications that `is_paused` does not
+    /// report, since paused keys only take the new args.
+    pub(crate) fn build<Comp, Error>(
+        self,
+        is_paused: impl Fn(&Key) -> bool,
+        instrument: &Instrumentation<Key, Args>,
+        build: impl Fn(&Key, &Args) -> Result<Comp, Error>,
+    ) -> BuiltChanges<Key, Args, Comp, Error> {
+        let entries = self
+            .additions
+            .into_iter()
+            .chain(self.modifications)
+            .map(|(key, args)| {
+                let result = (!is_paused(&key))
+                    .then(|| instrument.build(&key, &args, || build(&key, &args)));
+                (key, args, result)
+            })
+            .collect();
+
+        BuiltChanges {
+            removals: self.removals,
+            unchanged: self.unchanged,
+            entries,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    fn sorted<T: Ord>(mut keys: Vec<T>) -> Vec<T> {
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_sync_with_applies_the_delta() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("keep", 1), ("change", 2), ("drop", 3)], init)
+            .with_teardown(move |key: &&str, _: &mut Counter| {
+                closed_clone.lock().unwrap().push(*key)
+            });
+
+        let report = manager.sync_with([("keep", 1), ("change", 20), ("new", 4)]);
+
+        assert_eq!(report.inserted, vec!["new"]);
+        assert_eq!(report.updated, vec!["change"]);
+        assert_eq!(report.removed, vec!["drop"]);
+        assert_eq!(report.unchanged, vec!["keep"]);
+        assert_eq!(manager.get(&"change"), Some(&Counter(20)));
+        assert_eq!(
+            sorted(closed.lock().unwrap().clone()),
+            vec!["change", "drop"]
+        );
+
+        assert!(
+            manager
+                .sync_with([("keep", 1), ("change", 20), ("new", 4)])
+                .is_noop()
+        );
+    }
+
+    #[test]
+    fn test_sync_with_keeps_paused_keys_paused() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("same", 1), ("change", 2), ("drop", 3)], init);
+        for key in ["same", "change", "drop"] {
+            manager.pause(&key);
+        }
+
+        let changes = manager.diff([("same", 1), ("change", 20)]);
+        assert_eq!(changes.unchanged, vec!["same"]);
+        assert_eq!(changes.modifications, vec![("change", 20)]);
+        assert_eq!(changes.removals, vec!["drop"]);
+
+        let report = manager.apply_changeset(changes);
+        assert_eq!(report.updated, vec!["change"]);
+        assert_eq!(report.removed, vec!["drop"]);
+        assert!(manager.is_empty());
+        assert!(!manager.is_paused(&"drop"));
+        assert_eq!(
+            sorted(manager.paused_keys().copied().collect()),
+            vec!["change", "same"]
+        );
+
+        assert_eq!(manager.resume(&"change"), Some(&Counter(20)));
+        assert!(manager.sync_with([("same", 1), ("change", 20)]).is_noop());
+    }
+
+    #[test]
+    fn test_try_sync_with_reports_failures() {
+        let init = |_key: &&str, args: &usize| {
+            if *args == 0 {
+                Err(TestError)
+            } else {
+                Ok(Counter(*args))
+            }
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1), ("key2", 2)], init).unwrap();
+
+        let report = manager.try_sync_with([("key1", 0), ("key3", 0)]);
+
+        assert_eq!(report.removed, vec!["key2"]);
+        assert_eq!(
+            sorted(
+                report
+                    .failed
+                    .into_iter()
+                    .map(|failure| failure.key)
+                    .collect()
+            ),
+            vec!["key1", "key3"]
+        );
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+        assert!(!manager.contains_key(&"key3"));
+    }
+
+    #[test]
+    fn test_diff_then_apply_vetoed_changeset() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("keep", 1), ("change", 2), ("drop", 3)], init);
+
+        let mut changes = manager.diff([("keep", 1), ("change", 20), ("new", 4)]);
+        assert_eq!(changes.additions, vec![("new", 4)]);
+        assert_eq!(changes.modifications, vec![("change", 20)]);
+        assert_eq!(changes.removals, vec!["drop"]);
+        assert_eq!(changes.unchanged, vec!["keep"]);
+        // Diffing is pure
+        assert_eq!(manager.get(&"change"), Some(&Counter(2)));
+
+        changes.removals.clear();
+        let report = manager.apply_changeset(changes);
+
+        assert_eq!(sorted(report.inserted), vec!["new"]);
+        assert_eq!(report.updated, vec!["change"]);
+        assert!(report.removed.is_empty());
+        assert_eq!(manager.get(&"drop"), Some(&Counter(3)));
+        assert!(
+            manager
+                .diff([("keep", 1), ("change", 20), ("new", 4), ("drop", 3)])
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use crate::{
+    ComponentMap, Entries, KeyedStorage, ReinitTask, SharedComponentMap, SyncReport, Teardown,
+    WithArgs,
+};
+use futures::future::{self, Either};
+use futures_timer::Delay;
+use serde::de::DeserializeOwned;
+use std::{
+    collections::HashSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+// Files are polled rather than watched through OS notifications: config files
+// are small, editors replace them in ways inotify and friends report
+// inconsistently, and polling behaves the same on every platform.
+
+/// Format of a config file holding `key -> args` entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Guesses the format from the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn parse<Key, Args>(self, text: &str) -> Result<Entries<Key, Args>, ReloadError>
+    where
+        Key: Eq + std::hash::Hash + DeserializeOwned,
+        Args: DeserializeOwned,
+    {
+        let parsed = match self {
+            ConfigFormat::Json => serde_json::from_str(text).map_err(|error| error.to_string()),
+            ConfigFormat::Toml => toml::from_str(text).map_err(|error| error.to_string()),
+            ConfigFormat::Yaml => serde_norway::from_str(text).map_err(|error| error.to_string()),
+        };
+        parsed.map_err(ReloadError::Parse)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReloadError {
+    Io(io::Error),
+    /// The file does not hold valid `key -> args` entries in its format.
+    Parse(String),
+    /// The format could not be guessed from the file extension.
+    UnknownFormat(PathBuf),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReloadError::Io(error) => write!(f, "failed to read config: {error}"),
+            ReloadError::Parse(error) => write!(f, "failed to parse config: {error}"),
+            ReloadError::UnknownFormat(path) => {
+                write!(f, "unknown config format for {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReloadError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Polls a config file for changes, yielding its entries only when its
+/// contents differ from the last successful poll.
+#[derive(Debug, Clone)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    format: ConfigFormat,
+    last: Option<String>,
+}
+
+impl ConfigWatcher {
+    /// Watches `path`, guessing its format from the extension.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, ReloadError> {
+        let path = path.into();
+        match ConfigFormat::from_path(&path) {
+            Some(format) => Ok(Self::with_format(path, format)),
+            None => Err(ReloadError::UnknownFormat(path)),
+        }
+    }
+
+    pub fn with_format(path: impl Into<PathBuf>, format: ConfigFormat) -> Self {
+        Self {
+            path: path.into(),
+            format,
+            last: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the file, returning `None` if it is unchanged since the last
+    /// successful poll. A file that fails to parse is retried on every poll.
+    pub fn poll<Key, Args>(&mut self) -> Result<Option<Entries<Key, Args>>, ReloadError>
+    where
+        Key: Eq + std::hash::Hash + DeserializeOwned,
+        Args: DeserializeOwned,
+    {
+        let text = fs::read_to_string(&self.path).map_err(ReloadError::Io)?;
+        self.changed(text)
+    }
+
+    /// Like [`poll`](Self::poll), but reads the file on the blocking thread
+    /// pool instead of the calling task.
+    pub async fn poll_async<Key, Args>(&mut self) -> Result<Option<Entries<Key, Args>>, ReloadError>
+    where
+        Key: Eq + std::hash::Hash + DeserializeOwned,
+        Args: DeserializeOwned,
+    {
+        let path = self.path.clone();
+        let text = tokio::task::spawn_blocking(move || fs::read_to_string(path))
+            .await
+            .map_err(|error| ReloadError::Io(io::Error::other(error)))?
+            .map_err(ReloadError::Io)?;
+        self.changed(text)
+    }
+
+    fn changed<Key, Args>(
+        &mut self,
+        text: String,
+    ) -> Result<Option<Entries<Key, Args>>, ReloadError>
+    where
+        Key: Eq + std::hash::Hash + DeserializeOwned,
+        Args: DeserializeOwned,
+    {
+        if self.last.as_ref() == Some(&text) {
+            return Ok(None);
+        }
+        let entries = self.format.parse(&text)?;
+        self.last = Some(text);
+        Ok(Some(entries))
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Polls `watcher` and, if the file changed, reconciles the map against
+    /// it with [`sync_with`](Self::sync_with), emitting the usual change
+    /// events. Returns `None` if the file is unchanged.
+    pub fn reload_from(
+        &mut self,
+        watcher: &mut ConfigWatcher,
+    ) -> Result<Option<SyncReport<Key>>, ReloadError>
+    where
+        Key: Clone + Eq + std::hash::Hash + DeserializeOwned,
+        Args: PartialEq + DeserializeOwned,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        Ok(watcher.poll()?.map(|desired| self.sync_with(desired)))
+    }
+
+    /// Like [`reload_from`](Self::reload_from) for fallible inits.
+    pub fn try_reload_from<Error>(
+        &mut self,
+        watcher: &mut ConfigWatcher,
+    ) -> Result<Option<SyncReport<Key, Error>>, ReloadError>
+    where
+        Key: Clone + Eq + std::hash::Hash + DeserializeOwned,
+        Args: PartialEq + DeserializeOwned,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: fmt::Debug,
+    {
+        Ok(watcher.poll()?.map(|desired| self.try_sync_with(desired)))
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Clone + Eq + std::hash::Hash + DeserializeOwned + Send + Sync + 'static,
+    Args: PartialEq + DeserializeOwned + Send + Sync + 'static,
+    Comp: Send + Sync + 'static,
+    FnInit: Fn(&Key, &Args) -> Comp + Clone + Send + Sync + 'static,
+    FnDrop: Teardown<Key, Comp> + Send + Sync + 'static,
+{
+    /// Spawns a task polling `watcher` every `interval` and applying each
+    /// change like [`sync_with`](ComponentMap::sync_with). The file is read
+    /// off the runtime's worker threads, and the map is only write-locked
+    /// while the components built for the change are swapped in, not while
+    /// they are built.
+    ///
+    /// `on_reload` receives the report of every applied change and every
+    /// failed poll; a file that cannot be read or parsed leaves the map as it
+    /// was.
+    pub fn spawn_config_reload(
+        &self,
+        mut watcher: ConfigWatcher,
+        interval: Duration,
+        mut on_reload: impl FnMut(Result<SyncReport<Key>, ReloadError>) + Send + 'static,
+    ) -> ReinitTask {
+        let shared = self.clone();
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            loop {
+                match watcher.poll_async().await {
+                    Ok(Some(desired)) => {
+                        let (changes, paused) = {
+                            let map = shared.read();
+                            let paused: HashSet<_> = map.paused_keys().cloned().collect();
+                            (map.diff(desired), paused)
+                        };
+                        let (init, instrument) = shared.init_parts();
+                        let built = changes.build(
+                            |key| paused.contains(key),
+                            &instrument,
+                            |key, args| Ok(init(key, args)),
+                        );
+                        let report = shared
+                            .write()
+                            .apply_built(built, |init, key, args| Ok(init(key, args)));
+                        on_reload(Ok(report));
+                    }
+                    Ok(None) => {}
+                    Err(error) => on_reload(Err(error)),
+                }
+                if let Either::Right(_) = future::select(Delay::new(interval), &mut stopped).await {
+                    break;
+                }
+            }
+        });
+        ReinitTask { stop, task }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChangeKind;
+    use futures::{FutureExt, StreamExt};
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    fn config_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("component-map-{}-{name}", std::process::id()))
+    }
+
+    /// Replaces the file in one step, so a concurrent poll never reads it half-written.
+    fn write_config(path: &Path, contents: &str) {
+        let staged = path.with_extension("staged");
+        fs::write(&staged, contents).unwrap();
+        fs::rename(staged, path).unwrap();
+    }
+
+    #[test]
+    fn test_reload_from_applies_file_changes() {
+        let path = config_path("reload.json");
+        write_config(&path, r#"{"key1": 1, "key2": 2}"#);
+        let mut watcher = ConfigWatcher::new(&path).unwrap();
+
+        let init = |_key: &String, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1".to_string(), 1)], init);
+        let mut events = manager.subscribe(16);
+
+        let report = manager.reload_from(&mut watcher).unwrap().unwrap();
+        assert_eq!(report.inserted, vec!["key2"]);
+        assert_eq!(report.unchanged, vec!["key1"]);
+        assert!(manager.reload_from(&mut watcher).unwrap().is_none());
+
+        write_config(&path, r#"{"key2": 20}"#);
+        let report = manager.reload_from(&mut watcher).unwrap().unwrap();
+        assert_eq!(report.updated, vec!["key2"]);
+        assert_eq!(report.removed, vec!["key1"]);
+        assert_eq!(manager.get("key2"), Some(&Counter(20)));
+        let events: Vec<_> = std::iter::from_fn(|| events.next().now_or_never().flatten())
+            .map(|event| (event.key, event.kind))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("key2".to_string(), ChangeKind::Inserted),
+                ("key1".to_string(), ChangeKind::Removed),
+                ("key2".to_string(), ChangeKind::Replaced),
+            ]
+        );
+
+        write_config(&path, "not json");
+        assert!(matches!(
+            manager.reload_from(&mut watcher),
+            Err(ReloadError::Parse(_))
+        ));
+        assert_eq!(manager.get("key2"), Some(&Counter(20)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_formats() {
+        let toml: Entries<String, usize> = ConfigFormat::Toml.parse("key1 = 1\nkey2 = 2").unwrap();
+        let yaml: Entries<String, usize> = ConfigFormat::Yaml.parse("key1: 1\nkey2: 2").unwrap();
+        assert_eq!(toml, yaml);
+        assert_eq!(toml["key2"], 2);
+
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("components.yml")),
+            Some(ConfigFormat::Yaml)
+        );
+        assert!(matches!(
+            ConfigWatcher::new("components.ini"),
+            Err(ReloadError::UnknownFormat(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_config_reload() {
+        let path = config_path("spawn.toml");
+        write_config(&path, "key1 = 1");
+        let watcher = ConfigWatcher::new(&path).unwrap();
+
+        let init = |_key: &String, args: &usize| Counter(*args);
+        let shared = SharedComponentMap::from(ComponentMap::init(Vec::new(), init));
+        let (reports, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let reload = shared.spawn_config_reload(watcher, Duration::from_millis(5), move |report| {
+            let _ = reports.send(report.map(|report| report.inserted));
+        });
+
+        assert_eq!(received.recv().await.unwrap().unwrap(), vec!["key1"]);
+        write_config(&path, "key1 = 1\nkey2 = 2");
+        assert_eq!(received.recv().await.unwrap().unwrap(), vec!["key2"]);
+        reload.stop().await;
+
+        assert_eq!(shared.get("key2"), Some(Counter(2)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_spawn_config_reload_builds_without_the_write_lock() {
+        type Probe = Box<dyn Fn() -> bool + Send + Sync>;
+
+        let path = config_path("unlocked.json");
+        write_config(&path, r#"{"key1": 1}"#);
+        let watcher = ConfigWatcher::new(&path).unwrap();
+
+        // Records whether the map was write-locked while each component was built
+        let probe = Arc::new(OnceLock::<Probe>::new());
+        let locked = Arc::new(Mutex::new(Vec::new()));
+        let init = {
+            let (probe, locked) = (probe.clone(), locked.clone());
+            move |_key: &String, args: &usize| {
+                if let Some(probe) = probe.get() {
+                    locked.lock().unwrap().push(probe());
+                }
+                Counter(*args)
+            }
+        };
+        let shared = SharedComponentMap::from(ComponentMap::init(Vec::new(), init));
+        let _ = probe.set(Box::new({
+            let shared = shared.clone();
+            move || shared.is_write_locked()
+        }));
+
+        let (reports, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let reload = shared.spawn_config_reload(watcher, Duration::from_millis(5), move |report| {
+            let _ = reports.send(report.map(|report| report.inserted));
+        });
+        assert_eq!(received.recv().await.unwrap().unwrap(), vec!["key1"]);
+        reload.stop().await;
+
+        assert_eq!(*locked.lock().unwrap(), vec![false]);
+        assert_eq!(shared.get("key1"), Some(Counter(1)));
+        fs::remove_file(&path).unwrap();
+    }
+}
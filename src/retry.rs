@@ -0,0 +1,312 @@
+use crate::{
+    ComponentMap, Keyed, KeyedError, KeyedStorage, Lookup, NoTeardown, ReinitOutcome, Teardown,
+    WithArgs,
+};
+use futures_timer::Delay;
+use std::{borrow::Borrow, future::Future, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    Fixed(Duration),
+    /// Multiplies the delay by `multiplier` after every failed attempt, capped at `max`.
+    Exponential {
+        initial: Duration,
+        multiplier: f64,
+        max: Duration,
+    },
+}
+
+/// How often, and how patiently, a failed init is retried.
+///
+/// `max_attempts` counts the first attempt, so `1` disables retrying.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: usize,
+    pub backoff: Backoff,
+    /// Fraction of each delay, between `0.0` and `1.0`, that is randomly shaved
+    /// off so that keys failing together do not retry in lockstep.
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: usize, backoff: Backoff) -> Self {
+        Self {
+            max_attempts,
+            backoff,
+            jitter: 0.0,
+        }
+    }
+
+    pub fn with_jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Delay to wait after the failed attempt number `attempt` (starting at 0), before jitter.
+    pub fn delay(&self, attempt: usize) -> Duration {
+        match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential {
+                initial,
+                multiplier,
+                max,
+            } => i32::try_from(attempt)
+                .ok()
+                .and_then(|attempt| {
+                    Duration::try_from_secs_f64(initial.as_secs_f64() * multiplier.powi(attempt))
+                        .ok()
+                })
+                .map_or(max, |delay| delay.min(max)),
+        }
+    }
+
+    fn jittered_delay(&self, attempt: usize) -> Duration {
+        self.delay(attempt)
+            .mul_f64(1.0 - self.jitter * fastrand::f64())
+    }
+
+    pub(crate) fn run<T, Error>(
+        &self,
+        mut f: impl FnMut() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            match f() {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt + 1 >= self.max_attempts => return Err(error),
+                Err(_) => {
+                    std::thread::sleep(self.jittered_delay(attempt));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    pub(crate) async fn run_async<T, Error, Fut>(
+        &self,
+        mut f: impl FnMut() -> Fut,
+    ) -> Result<T, Error>
+    where
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt + 1 >= self.max_attempts => return Err(error),
+                Err(_) => {
+                    Delay::new(self.jittered_delay(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Runs `f` once, or under `policy` if one is given.
+pub(crate) async fn retry_async<T, Error, Fut>(
+    policy: Option<RetryPolicy>,
+    mut f: impl FnMut() -> Fut,
+) -> Result<T, Error>
+where
+    Fut: Future<Output = Result<T, Error>>,
+{
+    match policy {
+        Some(policy) => policy.run_async(f).await,
+        None => f().await,
+    }
+}
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    /// Like [`try_init`](Self::try_init), retrying each failed init according to `policy`.
+    pub fn try_init_with_retry<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        policy: RetryPolicy,
+    ) -> Result<Self, KeyedError<Key, Error>>
+    where
+        Key: Eq + std::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+    {
+        let map = entries
+            .into_iter()
+            .map(|(key, args)| match policy.run(|| (init)(&key, &args)) {
+                Ok(component) => Ok((key, WithArgs::new(component, args))),
+                Err(error) => Err(KeyedError::new(key, error)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(map, init, NoTeardown))
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Like [`try_reinit`](Self::try_reinit), retrying each failed init according to `policy`.
+    pub fn try_reinit_with_retry<'q, Q, Error>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'q Q>,
+        policy: RetryPolicy,
+    ) -> impl Iterator<Item = Keyed<&'q Q, ReinitOutcome<Comp, Error>>>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: std::fmt::Debug,
+    {
+        keys.into_iter().map(move |key| {
+            if self.is_throttled(key) {
+                return Keyed::new(key, ReinitOutcome::Throttled);
+            }
+            let prev = self.reinit_entry(key, |init, key, entry| {
+                policy.run(|| init(key, &entry.args))
+            });
+            Keyed::new(key, ReinitOutcome::from_reinit(prev))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BatchOptions;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError(usize);
+
+    fn no_delay(max_attempts: usize) -> RetryPolicy {
+        RetryPolicy::new(max_attempts, Backoff::Fixed(Duration::ZERO))
+    }
+
+    #[test]
+    fn test_exponential_delay() {
+        let policy = RetryPolicy::new(
+            5,
+            Backoff::Exponential {
+                initial: Duration::from_millis(10),
+                multiplier: 2.0,
+                max: Duration::from_millis(50),
+            },
+        );
+
+        assert_eq!(policy.delay(0), Duration::from_millis(10));
+        assert_eq!(policy.delay(1), Duration::from_millis(20));
+        assert_eq!(policy.delay(2), Duration::from_millis(40));
+        assert_eq!(policy.delay(3), Duration::from_millis(50));
+        assert_eq!(policy.delay(100), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_try_init_with_retry() {
+        let attempts = Arc::new(Mutex::new(0));
+        let attempts_clone = attempts.clone();
+
+        // Fails twice before succeeding
+        let init = move |_key: &&str, args: &usize| {
+            let mut attempts = attempts_clone.lock().unwrap();
+            *attempts += 1;
+            if *attempts < 3 {
+                Err(TestError(*attempts))
+            } else {
+                Ok(Counter(*args))
+            }
+        };
+
+        let manager = ComponentMap::try_init_with_retry([("key1", 1)], init, no_delay(3)).unwrap();
+
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+        assert_eq!(*attempts.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_try_init_with_retry_exhausted() {
+        let init = |_key: &&str, args: &usize| Err::<Counter, _>(TestError(*args));
+
+        let result = ComponentMap::try_init_with_retry([("key1", 1)], init, no_delay(2));
+
+        assert_eq!(result.err(), Some(KeyedError::new("key1", TestError(1))));
+    }
+
+    #[test]
+    fn test_try_reinit_with_retry() {
+        let attempts = Arc::new(Mutex::new(0));
+        let attempts_clone = attempts.clone();
+
+        // Succeeds on init, then fails once on reinit
+        let init = move |_key: &&str, args: &usize| {
+            let mut attempts = attempts_clone.lock().unwrap();
+            *attempts += 1;
+            if *attempts == 2 {
+                Err(TestError(*attempts))
+            } else {
+                Ok(Counter(*args * *attempts))
+            }
+        };
+
+        let mut manager = ComponentMap::try_init([("key1", 1)], init).unwrap();
+
+        let results: Vec<_> = manager
+            .try_reinit_with_retry(["key1", "nonexistent"], no_delay(2))
+            .collect();
+
+        assert_eq!(results[0].value, ReinitOutcome::Replaced(Counter(1)));
+        assert!(matches!(results[1].value, ReinitOutcome::Missing));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(3)));
+    }
+
+    #[test]
+    fn test_jitter_shortens_delay() {
+        let policy =
+            RetryPolicy::new(3, Backoff::Fixed(Duration::from_millis(100))).with_jitter(0.5);
+
+        for attempt in 0..20 {
+            let delay = policy.jittered_delay(attempt);
+            assert!(delay > Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_try_update_async_with_retry() {
+        let attempts = Arc::new(Mutex::new(0usize));
+        let attempts_clone = attempts.clone();
+
+        // Every other attempt fails
+        let init = move |_key: &&str, args: &usize| {
+            let attempts = attempts_clone.clone();
+            let value = *args;
+            async move {
+                let mut attempts = attempts.lock().unwrap();
+                *attempts += 1;
+                if attempts.is_multiple_of(2) {
+                    Err(TestError(*attempts))
+                } else {
+                    Ok(Counter(value))
+                }
+            }
+        };
+
+        let options = BatchOptions::default().retry(no_delay(2));
+        let mut manager = ComponentMap::try_init_async_with([("key1", 1)], init, options.clone())
+            .await
+            .unwrap();
+
+        let results: Vec<_> = manager
+            .try_update_async_with([("key1", 10)], options)
+            .await
+            .collect();
+
+        assert!(!results[0].value.is_failed());
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert_eq!(*attempts.lock().unwrap(), 3);
+    }
+}
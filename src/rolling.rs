@@ -0,0 +1,81 @@
+use crate::{
+    AsyncTeardown, ComponentMap, Keyed, KeyedStorage, Teardown, WithArgs, priority::prioritized,
+};
+use futures_timer::Delay;
+use std::time::Duration;
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Reinitialises every component in batches of `batch_size`, waiting
+    /// `delay` between one batch and the next, so the whole map is never
+    /// restarted at the same instant. Batches are taken from the highest
+    /// [priority](Self::set_priority) down.
+    ///
+    /// Each batch is reinitialised like [`reinit_async`](Self::reinit_async)
+    /// and the previous components are returned in the order they were replaced.
+    pub async fn reinit_all_rolling(
+        &mut self,
+        delay: Duration,
+        batch_size: usize,
+    ) -> Vec<Keyed<Key, Comp>>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let keys: Vec<_> = prioritized(self.map.iter(), &self.dependencies, &self.priorities)
+            .into_iter()
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut prev_components = Vec::with_capacity(keys.len());
+
+        for (index, batch) in keys.chunks(batch_size.max(1)).enumerate() {
+            if index > 0 {
+                Delay::new(delay).await;
+            }
+            prev_components.extend(self.reinit_async(batch).await.filter_map(
+                |Keyed { key, value }| value.map(|prev| Keyed::new(key.clone(), prev)),
+            ));
+        }
+
+        prev_components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[tokio::test]
+    async fn test_reinit_all_rolling_pauses_between_batches() {
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let started_clone = started.clone();
+        let init = async move |_key: &&str, args: &usize| {
+            started_clone.lock().unwrap().push(Instant::now());
+            Counter(*args)
+        };
+        let mut manager =
+            ComponentMap::init_async([("key1", 1), ("key2", 2), ("key3", 3)], init).await;
+        started.lock().unwrap().clear();
+
+        let prev = manager
+            .reinit_all_rolling(Duration::from_millis(20), 2)
+            .await;
+        assert_eq!(prev.len(), 3);
+
+        let started = started.lock().unwrap();
+        assert_eq!(started.len(), 3);
+        assert!(started[1] - started[0] < Duration::from_millis(20));
+        assert!(started[2] - started[1] >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{ComponentMap, Entries, KeyedError, Storage, Teardown, WithArgs};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use std::fmt;
+
+// Only keys and args are persisted: components are rebuilt by the init passed
+// to the `deserialize_and_init*` constructors, and runtime state such as TTLs,
+// priorities, or paused entries starts afresh.
+
+/// Serializes the map as `key -> args`.
+impl<Key, Args, Comp, FnInit, FnDrop, Store> Serialize
+    for ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    Key: Serialize,
+    Args: Serialize,
+    FnDrop: Teardown<Key, Comp>,
+    Store: Storage<Key, WithArgs<Args, Comp>>,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: Serializer,
+    {
+        serializer.collect_map(self.map.iter().map(|(key, entry)| (key, &entry.args)))
+    }
+}
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    /// Reads `key -> args` as written by the [`Serialize`] impl and builds
+    /// every component with `init`.
+    pub fn deserialize_and_init<'de, D>(deserializer: D, init: FnInit) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        Key: Deserialize<'de> + Eq + std::hash::Hash,
+        Args: Deserialize<'de>,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let entries = Entries::<Key, Args>::deserialize(deserializer)?;
+        Ok(Self::init(entries, init))
+    }
+
+    /// Fallible counterpart of [`deserialize_and_init`](Self::deserialize_and_init);
+    /// a failed init is reported as a custom deserializer error.
+    pub fn try_deserialize_and_init<'de, D, Error>(
+        deserializer: D,
+        init: FnInit,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        Key: Deserialize<'de> + Eq + std::hash::Hash + fmt::Debug,
+        Args: Deserialize<'de>,
+        Error: fmt::Display,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+    {
+        let entries = Entries::<Key, Args>::deserialize(deserializer)?;
+        Self::try_init(entries, init)
+            .map_err(|error: KeyedError<Key, Error>| D::Error::custom(error))
+    }
+
+    /// Async counterpart of [`deserialize_and_init`](Self::deserialize_and_init).
+    pub async fn deserialize_and_init_async<'de, D>(
+        deserializer: D,
+        init: FnInit,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        Key: Deserialize<'de> + Eq + std::hash::Hash,
+        Args: Deserialize<'de>,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+    {
+        let entries = Entries::<Key, Args>::deserialize(deserializer)?;
+        Ok(Self::init_async(entries, init).await)
+    }
+
+    /// Async counterpart of [`try_deserialize_and_init`](Self::try_deserialize_and_init).
+    pub async fn try_deserialize_and_init_async<'de, D, Error>(
+        deserializer: D,
+        init: FnInit,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        Key: Deserialize<'de> + Eq + std::hash::Hash + fmt::Debug,
+        Args: Deserialize<'de>,
+        Error: fmt::Display,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+    {
+        let entries = Entries::<Key, Args>::deserialize(deserializer)?;
+        Self::try_init_async(entries, init)
+            .await
+            .map_err(|error: KeyedError<Key, Error>| D::Error::custom(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_round_trip_rebuilds_components() {
+        let init = |_key: &String, args: &usize| Counter(*args);
+        let manager = ComponentMap::init([("key1".to_string(), 1), ("key2".to_string(), 2)], init);
+
+        let json = serde_json::to_string(&manager).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, serde_json::json!({"key1": 1, "key2": 2}));
+
+        let init = |_key: &String, args: &usize| Counter(*args * 10);
+        let restored = ComponentMap::deserialize_and_init(
+            &mut serde_json::Deserializer::from_str(&json),
+            init,
+        )
+        .unwrap();
+        assert_eq!(restored.get("key1"), Some(&Counter(10)));
+        assert_eq!(restored.get("key2"), Some(&Counter(20)));
+    }
+
+    #[tokio::test]
+    async fn test_try_deserialize_and_init_async_reports_init_failure() {
+        let init = async |key: &String, args: &usize| match *args {
+            0 => Err(format!("{key} has no args")),
+            args => Ok(Counter(args)),
+        };
+        let json = r#"{"key1": 1, "key2": 0}"#;
+
+        let Err(error) = ComponentMap::try_deserialize_and_init_async(
+            &mut serde_json::Deserializer::from_str(json),
+            init,
+        )
+        .await
+        else {
+            panic!("init failure should surface as a deserialize error");
+        };
+        assert!(error.to_string().contains("key2 has no args"));
+
+        let json = r#"{"key1": 1}"#;
+        let manager = ComponentMap::try_deserialize_and_init_async(
+            &mut serde_json::Deserializer::from_str(json),
+            init,
+        )
+        .await
+        .unwrap();
+        assert_eq!(manager.get("key1"), Some(&Counter(1)));
+    }
+}
@@ -0,0 +1,642 @@
+use crate::{
+    ChangeKind, ComponentMap, NoTeardown, ReinitOutcome, Teardown, WithArgs,
+    events::Instrumentation, key_lock::KeyLocks, retry::retry_async,
+};
+use std::{
+    borrow::Borrow,
+    convert::Infallible,
+    future::Future,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// Cloneable handle to a [`ComponentMap`] shared between tasks.
+///
+/// Locks are never held across an `.await`: initialisation runs on cloned
+/// inputs and only the final insert or replace takes the write lock.
+#[allow(clippy::type_complexity)]
+#[derive(Debug)]
+pub struct SharedComponentMap<Key, Args, Comp, FnInit, FnDrop = NoTeardown>
+where
+    FnDrop: Teardown<Key, Comp>,
+{
+    inner: Arc<RwLock<ComponentMap<Key, Args, Comp, FnInit, FnDrop>>>,
+    in_flight: Arc<KeyLocks<Key, Comp>>,
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> Clone for SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    FnDrop: Teardown<Key, Comp>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> From<ComponentMap<Key, Args, Comp, FnInit, FnDrop>>
+    for SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    FnDrop: Teardown<Key, Comp>,
+{
+    fn from(map: ComponentMap<Key, Args, Comp, FnInit, FnDrop>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(map)),
+            in_flight: Arc::default(),
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    FnDrop: Teardown<Key, Comp>,
+{
+    pub fn read(&self) -> RwLockReadGuard<'_, ComponentMap<Key, Args, Comp, FnInit, FnDrop>> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, ComponentMap<Key, Args, Comp, FnInit, FnDrop>> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(all(test, feature = "hot-reload"))]
+    pub(crate) fn is_write_locked(&self) -> bool {
+        matches!(
+            self.inner.try_read(),
+            Err(std::sync::TryLockError::WouldBlock)
+        )
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<Comp>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Comp: Clone,
+    {
+        self.read().get(key).cloned()
+    }
+
+    /// Returns the component for `key`, initialising it from `args` if missing.
+    ///
+    /// Concurrent callers for the same missing key wait on a single in-flight
+    /// initialisation and share its result. If that initialisation fails, the
+    /// error is returned to the caller that ran it and the next waiter retries.
+    pub async fn get_or_try_init_async<Error>(&self, key: Key, args: Args) -> Result<Comp, Error>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        Comp: Clone,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+    {
+        if let Some(component) = self.get(&key) {
+            return Ok(component);
+        }
+
+        let claim = self.in_flight.claim(&key);
+        let mut guard = claim.lock().await;
+
+        // Another caller may have completed the initialisation while we waited
+        if let Some(component) = self.get(&key) {
+            return Ok(component);
+        }
+
+        let (init, instrument) = self.init_parts();
+        let span = instrument.start(&key, &args);
+        let result = (init)(&key, &args).await;
+        span.finish(&key);
+        guard.complete(None);
+
+        let mut map = self.write();
+        let map = &mut *map;
+        match result {
+            Ok(component) => {
+                map.reserve_slot(&key);
+                let entry = map
+                    .map
+                    .entry(key.clone())
+                    .or_insert(WithArgs::new(component, args));
+                map.events.emit(&key, ChangeKind::Inserted, Some(entry));
+                Ok(entry.component.clone())
+            }
+            Err(error) => {
+                map.events.emit_failed(&key, &args, &error);
+                Err(error)
+            }
+        }
+    }
+
+    /// Reinitialises the component for `key` from its current args, returning
+    /// the previous component, or `None` if the key is missing or
+    /// [throttled](crate::ComponentMapConfig::min_reinit_interval).
+    ///
+    /// Writers to the same key are serialised; readers only wait for the final
+    /// swap. The previous component is finalised with the sync teardown.
+    ///
+    /// Callers that ask for a reinit while one of the same key is in flight
+    /// wait for it and receive its result instead of running init again.
+    pub async fn reinit_async<Q>(&self, key: &Q) -> Option<Comp>
+    where
+        Key: Clone + Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Args: Clone,
+        Comp: Clone,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+    {
+        let key = self.read().map.get_key_value(key)?.0.clone();
+        self.reinit_with(
+            key,
+            async |init, key, args| Ok::<_, Infallible>((init)(&key, &args).await),
+            InPlace,
+        )
+        .await
+        .replaced()
+    }
+
+    /// Like [`reinit_async`](Self::reinit_async) for fallible inits, retried
+    /// according to [`ComponentMapConfig::retry`](crate::ComponentMapConfig::retry).
+    ///
+    /// On failure the previous component is kept and the entry is marked
+    /// [`Failed`](crate::EntryState::Failed). Callers that were waiting on the
+    /// failed reinit then retry one at a time.
+    pub async fn try_reinit_async<Q, Error>(&self, key: &Q) -> ReinitOutcome<Comp, Error>
+    where
+        Key: Clone + Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Args: Clone,
+        Comp: Clone,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+    {
+        let Some(key) = self
+            .read()
+            .map
+            .get_key_value(key)
+            .map(|(key, _)| key.clone())
+        else {
+            return ReinitOutcome::Missing;
+        };
+        let retry = self.read().config.retry;
+        self.reinit_with(
+            key,
+            async move |init, key, args| retry_async(retry, || (init)(&key, &args)).await,
+            InPlace,
+        )
+        .await
+    }
+
+    /// Runs `reinit` on the current inputs of `key` with writers to it locked
+    /// out, then swaps in the result and hands the previous component to
+    /// `retire`. Taking the init as a plain future keeps callers free to
+    /// require it to be `Send`.
+    pub(crate) async fn reinit_with<Error, Fut, R>(
+        &self,
+        key: Key,
+        reinit: impl FnOnce(FnInit, Key, Args) -> Fut,
+        retire: R,
+    ) -> ReinitOutcome<R::Prev, Error>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        Args: Clone,
+        FnInit: Clone,
+        Fut: Future<Output = Result<Comp, Error>>,
+        Error: std::fmt::Debug,
+        R: Retire<Key, Comp, FnDrop>,
+    {
+        let claim = self.in_flight.claim(&key);
+        let mut guard = claim.lock().await;
+
+        // A reinit of the key completed while we waited on it
+        if let Some(prev) = guard.shared() {
+            return ReinitOutcome::Replaced(R::reuse(prev));
+        }
+
+        if self.read().is_throttled(&key) {
+            return ReinitOutcome::Throttled;
+        }
+
+        let result = match self.begin_reinit(&key) {
+            Some((init, instrument, args)) => {
+                let _reinitializing = ReinitGuard {
+                    shared: self,
+                    key: &key,
+                };
+                let span = instrument.start(&key, &args);
+                let result = reinit(init, key.clone(), args).await;
+                span.finish(&key);
+                let mut map = self.write();
+                let map = &mut *map;
+                let mut left = None;
+                let result = map.map.get_mut(&key).map(|component| {
+                    let result = result
+                        .map(|next| {
+                            let prev = component.replace_component(next);
+                            let (prev, shared) = retire.retire(&map.teardown, &key, prev);
+                            left = shared;
+                            prev
+                        })
+                        .inspect_err(|_| component.record_failure());
+                    map.events
+                        .emit_result(&key, &result, ChangeKind::Reinitialized, component);
+                    result
+                });
+                guard.complete(left);
+                result
+            }
+            None => None,
+        };
+
+        ReinitOutcome::from_reinit(result)
+    }
+
+    /// Initialises a component from `args` and inserts it under `key`,
+    /// returning the entry it replaced.
+    ///
+    /// Writers to the same key are serialised; readers only wait for the final
+    /// swap. The previous component is finalised with the sync teardown.
+    pub async fn update_async(&self, key: Key, args: Args) -> Option<WithArgs<Args, Comp>>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+    {
+        let claim = self.in_flight.claim(&key);
+        let mut guard = claim.lock().await;
+
+        let (init, instrument) = self.init_parts();
+        let span = instrument.start(&key, &args);
+        let component = (init)(&key, &args).await;
+        span.finish(&key);
+        let next = WithArgs::new(component, args);
+
+        let mut map = self.write();
+        let map = &mut *map;
+        let prev = map.map.insert(key.clone(), next).map(|mut prev| {
+            map.teardown.teardown(&key, &mut prev.component);
+            prev
+        });
+        map.events
+            .emit(&key, ChangeKind::upsert(prev.is_some()), map.map.get(&key));
+        map.enforce_capacity();
+        // Reinits queued behind this update must not report an earlier one
+        guard.complete(None);
+        prev
+    }
+
+    /// The init function and instrument, to build a component without
+    /// holding the lock.
+    pub(crate) fn init_parts(&self) -> (FnInit, Instrumentation<Key, Args>)
+    where
+        FnInit: Clone,
+    {
+        let map = self.read();
+        ((*map.init).clone(), map.events.instrument.clone())
+    }
+
+    /// Marks `key` as reinitialising and returns what its init needs, so the
+    /// lock can be released while it runs.
+    fn begin_reinit(&self, key: &Key) -> Option<(FnInit, Instrumentation<Key, Args>, Args)>
+    where
+        Key: Eq + std::hash::Hash,
+        Args: Clone,
+        FnInit: Clone,
+    {
+        let mut map = self.write();
+        let entry = map.map.get_mut(key)?;
+        entry.reinitializing = true;
+        let args = entry.args.clone();
+        Some(((*map.init).clone(), map.events.instrument.clone(), args))
+    }
+}
+
+/// What a reinit does with the component it replaced, and what its caller and
+/// the callers queued behind it get back.
+pub(crate) trait Retire<Key, Comp, FnDrop> {
+    type Prev;
+
+    /// Finalises `prev`, returning what the caller gets along with the
+    /// component left for queued callers, if any.
+    fn retire(self, teardown: &FnDrop, key: &Key, prev: Comp) -> (Self::Prev, Option<Comp>);
+
+    /// What a queued caller gets when a reinit completed while it waited.
+    fn reuse(prev: &Comp) -> Self::Prev;
+}
+
+/// Finalises the replaced component with the sync teardown before handing it
+/// back to every caller.
+pub(crate) struct InPlace;
+
+impl<Key, Comp, FnDrop> Retire<Key, Comp, FnDrop> for InPlace
+where
+    Comp: Clone,
+    FnDrop: Teardown<Key, Comp>,
+{
+    type Prev = Comp;
+
+    fn retire(self, teardown: &FnDrop, key: &Key, mut prev: Comp) -> (Comp, Option<Comp>) {
+        teardown.teardown(key, &mut prev);
+        (prev.clone(), Some(prev))
+    }
+
+    fn reuse(prev: &Comp) -> Comp {
+        prev.clone()
+    }
+}
+
+/// Clears the mark set by `begin_reinit` when a reinit ends, including when
+/// its future is dropped before the init completes.
+struct ReinitGuard<'a, Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Eq + std::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    shared: &'a SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>,
+    key: &'a Key,
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> Drop for ReinitGuard<'_, Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Eq + std::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    fn drop(&mut self) {
+        if let Some(entry) = self.shared.write().map.get_mut(self.key) {
+            entry.reinitializing = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EntryState;
+    use futures::FutureExt;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct FailArgs {
+        value: usize,
+        should_fail: bool,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError(String);
+
+    #[tokio::test]
+    async fn test_get_or_try_init_async_deduplicates_in_flight() {
+        let call_count = Arc::new(Mutex::new(0));
+        let call_count_clone = call_count.clone();
+
+        let init = move |_key: &&str, args: &FailArgs| {
+            let call_count = call_count_clone.clone();
+            let value = args.value;
+            async move {
+                *call_count.lock().unwrap() += 1;
+                tokio::task::yield_now().await;
+                Ok::<_, TestError>(Counter(value))
+            }
+        };
+
+        let shared =
+            SharedComponentMap::from(ComponentMap::try_init_async([], init).await.unwrap());
+
+        let args = FailArgs {
+            value: 1,
+            should_fail: false,
+        };
+        let (first, second, third) = tokio::join!(
+            shared.get_or_try_init_async("key1", args.clone()),
+            shared.get_or_try_init_async("key1", args.clone()),
+            shared.get_or_try_init_async("key1", args.clone()),
+        );
+
+        assert_eq!(first, Ok(Counter(1)));
+        assert_eq!(second, Ok(Counter(1)));
+        assert_eq!(third, Ok(Counter(1)));
+        assert_eq!(*call_count.lock().unwrap(), 1);
+        assert!(shared.in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_or_try_init_async_existing_key() {
+        let init = |_key: &&str, args: &FailArgs| {
+            let value = args.value;
+            async move { Ok::<_, TestError>(Counter(value)) }
+        };
+
+        let shared = SharedComponentMap::from(
+            ComponentMap::try_init_async(
+                [(
+                    "key1",
+                    FailArgs {
+                        value: 1,
+                        should_fail: false,
+                    },
+                )],
+                init,
+            )
+            .await
+            .unwrap(),
+        );
+
+        let result = shared
+            .get_or_try_init_async(
+                "key1",
+                FailArgs {
+                    value: 100,
+                    should_fail: false,
+                },
+            )
+            .await;
+
+        assert_eq!(result, Ok(Counter(1)));
+        assert_eq!(shared.get("key1"), Some(Counter(1)));
+    }
+
+    #[tokio::test]
+    async fn test_get_or_try_init_async_failure() {
+        let init = |_key: &&str, args: &FailArgs| {
+            let value = args.value;
+            let should_fail = args.should_fail;
+            async move {
+                if should_fail {
+                    Err(TestError("Failed".to_string()))
+                } else {
+                    Ok(Counter(value))
+                }
+            }
+        };
+
+        let shared =
+            SharedComponentMap::from(ComponentMap::try_init_async([], init).await.unwrap());
+
+        let result = shared
+            .get_or_try_init_async(
+                "key1",
+                FailArgs {
+                    value: 1,
+                    should_fail: true,
+                },
+            )
+            .await;
+
+        assert_eq!(result, Err(TestError("Failed".to_string())));
+        assert!(shared.get("key1").is_none());
+        assert!(shared.in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reinit_and_update_async() {
+        let init = |_key: &&str, args: &usize| {
+            let value = *args;
+            async move {
+                tokio::task::yield_now().await;
+                Counter(value)
+            }
+        };
+        let shared = SharedComponentMap::from(ComponentMap::init_async([("key1", 1)], init).await);
+        shared.write().set_args(&"key1", 2);
+
+        let (prev, (reader, reinitializing)) = tokio::join!(shared.reinit_async(&"key1"), async {
+            let reinitializing = shared.read().state(&"key1") == Some(EntryState::Reinitializing);
+            (shared.get(&"key1"), reinitializing)
+        });
+        assert_eq!(prev, Some(Counter(1)));
+        assert_eq!(reader, Some(Counter(1)));
+        assert!(reinitializing);
+        assert_eq!(shared.read().state(&"key1"), Some(EntryState::Ready));
+        assert_eq!(shared.get(&"key1"), Some(Counter(2)));
+        assert_eq!(shared.reinit_async(&"key2").await, None);
+
+        let prev = shared.update_async("key1", 3).await;
+        assert_eq!(prev.map(|prev| prev.component), Some(Counter(2)));
+        assert!(shared.update_async("key2", 4).await.is_none());
+        assert_eq!(shared.get(&"key2"), Some(Counter(4)));
+        assert!(shared.in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_reinit_async_clears_reinitializing() {
+        let init = |_key: &&str, args: &usize| {
+            let value = *args;
+            async move {
+                tokio::task::yield_now().await;
+                Counter(value)
+            }
+        };
+        let shared = SharedComponentMap::from(ComponentMap::init_async([("key1", 1)], init).await);
+        shared.write().set_args(&"key1", 2);
+
+        assert!(shared.reinit_async(&"key1").now_or_never().is_none());
+        assert_eq!(shared.read().state(&"key1"), Some(EntryState::Stale));
+        assert_eq!(shared.get(&"key1"), Some(Counter(1)));
+
+        assert_eq!(shared.reinit_async(&"key1").await, Some(Counter(1)));
+        assert_eq!(shared.read().state(&"key1"), Some(EntryState::Ready));
+    }
+
+    #[tokio::test]
+    async fn test_update_async_serialises_writers() {
+        let init = |_key: &&str, args: &usize| {
+            let value = *args;
+            async move {
+                // Later writers finish their init first
+                for _ in 0..(10 - value) {
+                    tokio::task::yield_now().await;
+                }
+                Counter(value)
+            }
+        };
+        let shared = SharedComponentMap::from(ComponentMap::init_async([], init).await);
+
+        tokio::join!(
+            shared.update_async("key1", 1),
+            shared.update_async("key1", 2),
+        );
+
+        assert_eq!(shared.get(&"key1"), Some(Counter(2)));
+    }
+
+    #[tokio::test]
+    async fn test_writers_arriving_behind_queued_ones_wait_their_turn() {
+        // (running inits, most running at once, calls)
+        let inits = Arc::new(Mutex::new((0, 0, 0)));
+        let inits_clone = inits.clone();
+        let init = move |_key: &&str, args: &usize| {
+            let inits = inits_clone.clone();
+            let value = *args;
+            async move {
+                {
+                    let mut inits = inits.lock().unwrap();
+                    inits.0 += 1;
+                    inits.1 = inits.1.max(inits.0);
+                    inits.2 += 1;
+                }
+                for _ in 0..5 {
+                    tokio::task::yield_now().await;
+                }
+                inits.lock().unwrap().0 -= 1;
+                Counter(value)
+            }
+        };
+        let shared = SharedComponentMap::from(ComponentMap::init_async([], init).await);
+
+        tokio::join!(
+            shared.update_async("key1", 1),
+            shared.update_async("key1", 2),
+            async {
+                // Arrives once the first writer is done and the second is running
+                while shared.get(&"key1").is_none() {
+                    tokio::task::yield_now().await;
+                }
+                shared.update_async("key1", 3).await
+            },
+            async {
+                while shared.get(&"key1").is_none() {
+                    tokio::task::yield_now().await;
+                }
+                shared.reinit_async(&"key1").await
+            },
+        );
+
+        let (running, most_running, calls) = *inits.lock().unwrap();
+        assert_eq!(running, 0);
+        assert_eq!(most_running, 1);
+        assert_eq!(calls, 4);
+        assert!(shared.in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reinits_share_one_init() {
+        let calls = Arc::new(Mutex::new(0));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&str, args: &usize| {
+            *calls_clone.lock().unwrap() += 1;
+            let value = *args;
+            async move {
+                tokio::task::yield_now().await;
+                Counter(value)
+            }
+        };
+        let shared = SharedComponentMap::from(ComponentMap::init_async([("key1", 1)], init).await);
+        *calls.lock().unwrap() = 0;
+        shared.write().set_args(&"key1", 2);
+
+        let (first, second, third) = tokio::join!(
+            shared.reinit_async(&"key1"),
+            shared.reinit_async(&"key1"),
+            shared.reinit_async(&"key1"),
+        );
+        assert_eq!(first, Some(Counter(1)));
+        assert_eq!(second, Some(Counter(1)));
+        assert_eq!(third, Some(Counter(1)));
+        assert_eq!(*calls.lock().unwrap(), 1);
+
+        // Reinits requested after the shared one has finished run again
+        assert_eq!(shared.reinit_async(&"key1").await, Some(Counter(2)));
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert!(shared.in_flight.is_empty());
+    }
+}
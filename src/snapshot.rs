@@ -0,0 +1,187 @@
+use crate::{ComponentMap, EntryState, KeyedStorage, NoTeardown, Teardown, WithArgs};
+use serde::{Deserialize, Serialize, de::DeserializeOwned, de::Error as _};
+use std::io;
+
+// Snapshots are JSON objects tagged with a format version, so a service can
+// refuse a snapshot written by an incompatible release instead of misreading
+// it. Bump the version whenever `SnapshotEntry` changes shape.
+const VERSION: u32 = 1;
+
+/// Lifecycle state of an entry when its snapshot was taken, mirroring
+/// [`EntryState`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotState {
+    Ready,
+    Stale,
+    Reinitializing,
+    Failed(Option<String>),
+    Paused,
+}
+
+impl From<EntryState<'_>> for SnapshotState {
+    fn from(state: EntryState<'_>) -> Self {
+        match state {
+            EntryState::Ready => SnapshotState::Ready,
+            EntryState::Stale => SnapshotState::Stale,
+            EntryState::Reinitializing => SnapshotState::Reinitializing,
+            EntryState::Failed(error) => SnapshotState::Failed(error.map(str::to_owned)),
+            EntryState::Paused => SnapshotState::Paused,
+        }
+    }
+}
+
+/// One entry of a snapshot written by
+/// [`save_snapshot`](ComponentMap::save_snapshot).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry<Key, Args> {
+    pub key: Key,
+    pub args: Args,
+    /// Generation at save time, `None` for paused entries. Generations are
+    /// only unique within a process, so restored entries get fresh ones.
+    pub generation: Option<u64>,
+    pub state: SnapshotState,
+    pub priority: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile<Entries> {
+    version: u32,
+    entries: Entries,
+}
+
+/// Reads the entries of a snapshot written by
+/// [`save_snapshot`](ComponentMap::save_snapshot) without building any
+/// components, e.g. to inspect the states a crashed service left behind.
+pub fn read_snapshot<Key, Args>(
+    reader: impl io::Read,
+) -> serde_json::Result<Vec<SnapshotEntry<Key, Args>>>
+where
+    Key: DeserializeOwned,
+    Args: DeserializeOwned,
+{
+    let file: SnapshotFile<Vec<SnapshotEntry<Key, Args>>> = serde_json::from_reader(reader)?;
+    if file.version != VERSION {
+        return Err(serde_json::Error::custom(format!(
+            "unsupported snapshot version {}, expected {VERSION}",
+            file.version
+        )));
+    }
+    Ok(file.entries)
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Writes the key and args of every entry, paused ones included, along
+    /// with its generation, state, and priority.
+    pub fn save_snapshot(&self, writer: impl io::Write) -> serde_json::Result<()>
+    where
+        Key: Eq + std::hash::Hash + Serialize,
+        Args: Serialize,
+    {
+        let entries: Vec<_> = self
+            .map
+            .iter()
+            .map(|(key, entry)| SnapshotEntry {
+                key,
+                args: &entry.args,
+                generation: Some(entry.generation()),
+                state: entry.state(&self.config).into(),
+                priority: self.priorities.priority(key, entry),
+            })
+            .chain(self.paused_keys().map(|key| SnapshotEntry {
+                key,
+                args: &self.paused[key],
+                generation: None,
+                state: SnapshotState::Paused,
+                priority: 0,
+            }))
+            .collect();
+
+        serde_json::to_writer(
+            writer,
+            &SnapshotFile {
+                version: VERSION,
+                entries,
+            },
+        )
+    }
+}
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    /// Rebuilds a map from a snapshot written by
+    /// [`save_snapshot`](Self::save_snapshot), initialising every entry with
+    /// `init` and restoring priorities. Paused entries stay paused, and every
+    /// other entry starts out ready whatever its saved state.
+    pub fn restore_snapshot(reader: impl io::Read, init: FnInit) -> serde_json::Result<Self>
+    where
+        Key: Eq + std::hash::Hash + DeserializeOwned,
+        Args: DeserializeOwned,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let mut manager = Self::new(Default::default(), init, NoTeardown);
+        for entry in read_snapshot(reader)? {
+            if entry.state == SnapshotState::Paused {
+                manager.paused.insert(entry.key, entry.args);
+                continue;
+            }
+            let component = (manager.init)(&entry.key, &entry.args);
+            let mut restored = WithArgs::new(component, entry.args);
+            restored.priority = Some(entry.priority);
+            manager.map.insert(entry.key, restored);
+        }
+        Ok(manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_snapshot_restores_topology() {
+        let init = |_key: &String, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init(
+            [
+                ("key1".to_string(), 1),
+                ("key2".to_string(), 2),
+                ("key3".to_string(), 3),
+            ],
+            init,
+        );
+        manager.set_priority("key1", 5);
+        manager.pause("key3");
+
+        let mut snapshot = Vec::new();
+        manager.save_snapshot(&mut snapshot).unwrap();
+
+        let mut entries = read_snapshot::<String, usize>(snapshot.as_slice()).unwrap();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(entries[0].generation, manager.generation("key1"));
+        assert_eq!(entries[0].state, SnapshotState::Ready);
+        assert_eq!(entries[2].state, SnapshotState::Paused);
+
+        let restored = ComponentMap::restore_snapshot(snapshot.as_slice(), init).unwrap();
+        assert_eq!(restored.get("key1"), Some(&Counter(1)));
+        assert_eq!(restored.get("key2"), Some(&Counter(2)));
+        assert_eq!(restored.priority("key1"), Some(5));
+        assert!(restored.is_paused("key3"));
+    }
+
+    #[test]
+    fn test_restore_rejects_unknown_version() {
+        let init = |_key: &String, args: &usize| Counter(*args);
+        let snapshot = br#"{"version": 99, "entries": []}"#;
+
+        let Err(error) = ComponentMap::restore_snapshot(snapshot.as_slice(), init) else {
+            panic!("snapshot with an unknown version should be rejected");
+        };
+        assert!(error.to_string().contains("version 99"));
+    }
+}
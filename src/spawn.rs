@@ -0,0 +1,172 @@
+use crate::{
+    AsyncTeardown, ChangeKind, ComponentMap, Entries, Keyed, KeyedError, KeyedStorage, NoTeardown,
+    Teardown, WithArgs, teardown::teardown_all,
+};
+use futures::future::join_all;
+use std::future::Future;
+use tokio::task::{JoinError, JoinHandle};
+
+// Each init is called and awaited on its own tokio task, so a panicking init
+// surfaces as a `JoinError` for its key and CPU-heavy inits spread across
+// worker threads. The task owns clones of the init, key, and args, so the init
+// must return a `'static` future that does not borrow them.
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    /// Like [`init_async`](Self::init_async), spawning every init on its own task.
+    ///
+    /// Entries whose task panicked are left out of the map and reported.
+    pub async fn init_spawned<Fut>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+    ) -> (Self, Vec<KeyedError<Key, JoinError>>)
+    where
+        Key: Clone + Eq + std::hash::Hash + Send + 'static,
+        Args: Clone + Send + 'static,
+        FnInit: Fn(&Key, &Args) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Comp> + Send + 'static,
+        Comp: Send + 'static,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let handles = entries
+            .iter()
+            .map(|(key, args)| spawn_init(&init, key, args));
+
+        let results = join_all(handles).await;
+
+        let mut map = Entries::new();
+        let mut failures = Vec::new();
+        for ((key, args), result) in entries.into_iter().zip(results) {
+            match result {
+                Ok(component) => {
+                    map.insert(key, WithArgs::new(component, args));
+                }
+                Err(error) => failures.push(KeyedError::new(key, error)),
+            }
+        }
+
+        (Self::new(map, init, NoTeardown), failures)
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Like [`reinit_all_async`](Self::reinit_all_async), spawning every init on its own task.
+    ///
+    /// Keys whose task panicked keep their current component.
+    pub async fn reinit_all_spawned<Fut>(
+        &mut self,
+    ) -> impl Iterator<Item = Keyed<&Key, Result<Comp, JoinError>>>
+    where
+        Key: Clone + Send + 'static,
+        Args: Clone + Send + 'static,
+        FnInit: Fn(&Key, &Args) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Comp> + Send + 'static,
+        Comp: Send + 'static,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let handles = self
+            .map
+            .iter()
+            .map(|(key, component)| async {
+                let span = self.events.instrument.start(key, &component.args);
+                let result = spawn_init(&*self.init, key, &component.args).await;
+                span.finish(key);
+                result
+            })
+            .collect::<Vec<_>>();
+
+        let results = join_all(handles).await;
+
+        let mut prev_components = self
+            .map
+            .iter_mut()
+            .zip(results)
+            .map(|((key, component), result)| {
+                let result = result
+                    .map(|next| component.replace_component(next))
+                    .inspect_err(|_| component.record_failure());
+                self.events
+                    .emit_result(key, &result, ChangeKind::Reinitialized, &*component);
+                Keyed::new(key, result)
+            })
+            .collect::<Vec<_>>();
+
+        teardown_all(
+            &*self.teardown,
+            prev_components
+                .iter_mut()
+                .filter_map(|Keyed { key, value }| value.as_mut().ok().map(|prev| (&**key, prev))),
+        )
+        .await;
+
+        prev_components.into_iter()
+    }
+}
+
+fn spawn_init<Key, Args, Comp, FnInit, Fut>(
+    init: &FnInit,
+    key: &Key,
+    args: &Args,
+) -> JoinHandle<Comp>
+where
+    Key: Clone + Send + 'static,
+    Args: Clone + Send + 'static,
+    FnInit: Fn(&Key, &Args) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Comp> + Send + 'static,
+    Comp: Send + 'static,
+{
+    let (init, key, args) = (init.clone(), key.clone(), args.clone());
+    tokio::spawn(async move { init(&key, &args).await })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    // Args of 0 panic inside the spawned future, and args of 1 before it
+    fn init(_key: &&str, args: &usize) -> impl Future<Output = Counter> + Send + use<> {
+        let value = *args;
+        assert_ne!(value, 1, "init panicked before returning its future");
+        async move {
+            assert_ne!(value, 0, "init panicked");
+            Counter(value)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_init_spawned_isolates_panics() {
+        let (manager, failures) =
+            ComponentMap::init_spawned([("key1", 2), ("key2", 0), ("key3", 1)], init).await;
+
+        assert_eq!(manager.len(), 1);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(2)));
+        let mut failed: Vec<_> = failures.iter().map(|failure| failure.key).collect();
+        failed.sort();
+        assert_eq!(failed, vec!["key2", "key3"]);
+        assert!(failures.iter().all(|failure| failure.error.is_panic()));
+    }
+
+    #[tokio::test]
+    async fn test_reinit_all_spawned() {
+        let (mut manager, _) = ComponentMap::init_spawned([("key1", 2), ("key2", 3)], init).await;
+        manager.set_args(&"key1", 10);
+        manager.set_args(&"key2", 0);
+
+        let mut results: Vec<_> = manager
+            .reinit_all_spawned()
+            .await
+            .map(|Keyed { key, value }| (*key, value.ok()))
+            .collect();
+        results.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(results, vec![("key1", Some(Counter(2))), ("key2", None)]);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(3)));
+    }
+}
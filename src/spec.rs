@@ -0,0 +1,277 @@
+use crate::{ComponentMap, KeyedError, KeyedStorage, Teardown, WithArgs};
+use std::time::Duration;
+
+/// Declarative description of one entry: its key and args, plus the tags,
+/// priority, and TTL it should start with.
+///
+/// With the `serde` feature, specs deserialize from config such as
+/// `{"key": "feed", "args": ..., "tags": ["market"], "priority": 1, "ttl": 30}`,
+/// where every field but `key` and `args` is optional and `ttl` is in seconds.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentSpec<Key, Args> {
+    pub key: Key,
+    pub args: Args,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub priority: i32,
+    #[cfg_attr(feature = "serde", serde(default, with = "seconds"))]
+    pub ttl: Option<Duration>,
+}
+
+impl<Key, Args> ComponentSpec<Key, Args> {
+    pub fn new(key: Key, args: Args) -> Self {
+        Self {
+            key,
+            args,
+            tags: Vec::new(),
+            priority: 0,
+            ttl: None,
+        }
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Splits the spec into the entry to initialise and the metadata to apply
+    /// once it exists.
+    fn into_entry(self) -> ((Key, Args), Meta<Key>)
+    where
+        Key: Clone,
+    {
+        let meta = ComponentSpec {
+            key: self.key.clone(),
+            args: (),
+            tags: self.tags,
+            priority: self.priority,
+            ttl: self.ttl,
+        };
+        ((self.key, self.args), meta)
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Like [`export_args`](Self::export_args), also capturing the tags,
+    /// priority, and TTL of every entry, so the result round-trips through
+    /// [`from_specs`](ComponentMap::from_specs).
+    pub fn export_specs(&self) -> Vec<ComponentSpec<Key, Args>>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        Args: Clone,
+    {
+        self.map
+            .iter()
+            .map(|(key, entry)| {
+                let mut tags: Vec<_> = self.tags(key).map(str::to_owned).collect();
+                tags.sort_unstable();
+                ComponentSpec {
+                    key: key.clone(),
+                    args: entry.args.clone(),
+                    tags,
+                    priority: self.priorities.priority(key, entry),
+                    ttl: entry.ttl(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The parts of a spec applied after its entry is initialised.
+type Meta<Key> = ComponentSpec<Key, ()>;
+
+fn split<Key: Clone, Args>(
+    specs: impl IntoIterator<Item = ComponentSpec<Key, Args>>,
+) -> (Vec<(Key, Args)>, Vec<Meta<Key>>) {
+    specs.into_iter().map(ComponentSpec::into_entry).unzip()
+}
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    /// Like [`init`](Self::init), then applies the tags, priority, and TTL of
+    /// each spec.
+    pub fn from_specs(
+        specs: impl IntoIterator<Item = ComponentSpec<Key, Args>>,
+        init: FnInit,
+    ) -> Self
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let (entries, metas) = split(specs);
+        let mut manager = Self::init(entries, init);
+        manager.apply_specs(metas);
+        manager
+    }
+
+    /// Fallible counterpart of [`from_specs`](Self::from_specs).
+    pub fn try_from_specs<Error>(
+        specs: impl IntoIterator<Item = ComponentSpec<Key, Args>>,
+        init: FnInit,
+    ) -> Result<Self, KeyedError<Key, Error>>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+    {
+        let (entries, metas) = split(specs);
+        let mut manager = Self::try_init(entries, init)?;
+        manager.apply_specs(metas);
+        Ok(manager)
+    }
+
+    /// Async counterpart of [`from_specs`](Self::from_specs).
+    pub async fn from_specs_async(
+        specs: impl IntoIterator<Item = ComponentSpec<Key, Args>>,
+        init: FnInit,
+    ) -> Self
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+    {
+        let (entries, metas) = split(specs);
+        let mut manager = Self::init_async(entries, init).await;
+        manager.apply_specs(metas);
+        manager
+    }
+
+    /// Async counterpart of [`try_from_specs`](Self::try_from_specs).
+    pub async fn try_from_specs_async<Error>(
+        specs: impl IntoIterator<Item = ComponentSpec<Key, Args>>,
+        init: FnInit,
+    ) -> Result<Self, KeyedError<Key, Error>>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+    {
+        let (entries, metas) = split(specs);
+        let mut manager = Self::try_init_async(entries, init).await?;
+        manager.apply_specs(metas);
+        Ok(manager)
+    }
+
+    fn apply_specs(&mut self, metas: Vec<Meta<Key>>)
+    where
+        Key: Clone + Eq + std::hash::Hash,
+    {
+        for meta in metas {
+            self.set_priority(&meta.key, meta.priority);
+            self.set_ttl(&meta.key, meta.ttl);
+            for tag in meta.tags {
+                self.add_tag(&meta.key, tag);
+            }
+        }
+    }
+}
+
+/// (De)serializes an optional TTL as fractional seconds, which reads better in
+/// config than serde's `{secs, nanos}` default.
+#[cfg(feature = "serde")]
+mod seconds {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        ttl: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        ttl.map(|ttl| ttl.as_secs_f64()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<f64>::deserialize(deserializer)?
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_from_specs_applies_metadata() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let manager = ComponentMap::from_specs(
+            [
+                ComponentSpec::new("feed", 1)
+                    .with_tag("market")
+                    .with_priority(2)
+                    .with_ttl(Duration::from_secs(30)),
+                ComponentSpec::new("cache", 2),
+            ],
+            init,
+        );
+
+        assert_eq!(manager.get(&"feed"), Some(&Counter(1)));
+        assert_eq!(manager.priority(&"feed"), Some(2));
+        assert_eq!(manager.map[&"feed"].ttl(), Some(Duration::from_secs(30)));
+        assert_eq!(manager.tags(&"feed").collect::<Vec<_>>(), vec!["market"]);
+        assert_eq!(manager.map[&"cache"].ttl(), None);
+
+        let mut specs = manager.export_specs();
+        specs.sort_by_key(|spec| spec.key);
+        assert_eq!(specs[1].tags, vec!["market"]);
+        assert_eq!(specs[1].priority, 2);
+        assert_eq!(specs[1].ttl, Some(Duration::from_secs(30)));
+    }
+
+    #[tokio::test]
+    async fn test_try_from_specs_async_reports_failure() {
+        let init = async |_key: &&str, args: &usize| match *args {
+            0 => Err("no args"),
+            args => Ok(Counter(args)),
+        };
+
+        let Err(error) = ComponentMap::try_from_specs_async(
+            [
+                ComponentSpec::new("feed", 1),
+                ComponentSpec::new("cache", 0),
+            ],
+            init,
+        )
+        .await
+        else {
+            panic!("init failure should abort construction");
+        };
+        assert_eq!(error.key, "cache");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_specs_deserialize_from_config() {
+        let specs: Vec<ComponentSpec<String, usize>> = serde_json::from_str(
+            r#"[{"key": "feed", "args": 1, "tags": ["market"], "ttl": 1.5}, {"key": "cache", "args": 2}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            specs,
+            vec![
+                ComponentSpec::new("feed".to_string(), 1)
+                    .with_tag("market")
+                    .with_ttl(Duration::from_millis(1500)),
+                ComponentSpec::new("cache".to_string(), 2),
+            ]
+        );
+    }
+}
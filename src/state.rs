@@ -0,0 +1,146 @@
+use crate::{ComponentMap, ComponentMapConfig, KeyedStorage, Lookup, Teardown, WithArgs};
+use alloc::format;
+use core::{borrow::Borrow, fmt};
+
+/// Lifecycle state of an entry, from [`state`](ComponentMap::state).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryState<'a> {
+    /// The component was built from the current args and is within its TTL.
+    Ready,
+    /// The component was built from args since replaced via `set_args`, or
+    /// has outlived its TTL.
+    Stale,
+    /// A reinit is in flight; the previous component is served until it lands.
+    Reinitializing,
+    /// The last reinit failed; the previous component is still served. Holds
+    /// the error attached with [`record_error`](ComponentMap::record_error),
+    /// if any.
+    Failed(Option<&'a str>),
+    /// The component was torn down by `pause`; only its args are kept.
+    Paused,
+}
+
+impl EntryState<'_> {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, EntryState::Ready)
+    }
+}
+
+impl<Args, Comp> WithArgs<Args, Comp> {
+    pub(crate) fn state(&self, config: &ComponentMapConfig) -> EntryState<'_> {
+        if self.reinitializing {
+            EntryState::Reinitializing
+        } else if self.is_failed() {
+            EntryState::Failed(self.last_error())
+        } else if self.is_dirty() || self.is_stale(config) {
+            EntryState::Stale
+        } else {
+            EntryState::Ready
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    pub fn state<Q>(&self, key: &Q) -> Option<EntryState<'_>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        match self.map.get(key) {
+            Some(entry) => Some(entry.state(&self.config)),
+            None => self.paused.contains_key(key).then_some(EntryState::Paused),
+        }
+    }
+
+    /// Iterates over the state of every entry, so degraded components can be
+    /// found without checking each key.
+    pub fn states(&self) -> impl Iterator<Item = (&Key, EntryState<'_>)>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.map
+            .iter()
+            .map(|(key, entry)| (key, entry.state(&self.config)))
+            .chain(self.paused_keys().map(|key| (key, EntryState::Paused)))
+    }
+
+    /// Attaches the debug rendering of `error` to the failed entry for `key`,
+    /// to be reported by [`EntryState::Failed`].
+    ///
+    /// Reinits only mark the entry failed, so they do not require the error to
+    /// be `Debug`. Returns `false` if the key is missing or its last reinit
+    /// did not fail.
+    pub fn record_error<Q>(&mut self, key: &Q, error: &impl fmt::Debug) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        match self.map.get_mut(key) {
+            Some(entry) if entry.is_failed() => {
+                entry.last_error = Some(format!("{error:?}"));
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keyed;
+    use alloc::{vec, vec::Vec};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[test]
+    fn test_state_tracks_failed_reinit() {
+        let init = |_key: &&str, args: &usize| match args {
+            0 => Err(TestError),
+            _ => Ok(Counter(*args)),
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1), ("key2", 2)], init).unwrap();
+        assert_eq!(manager.state(&"key1"), Some(EntryState::Ready));
+
+        manager.set_args(&"key1", 0);
+        assert_eq!(manager.state(&"key1"), Some(EntryState::Stale));
+
+        let errors: Vec<_> = manager
+            .try_reinit([&"key1"])
+            .filter_map(|Keyed { key, value }| Some((*key, value.failed()?)))
+            .collect();
+        assert_eq!(manager.state(&"key1"), Some(EntryState::Failed(None)));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+
+        for (key, error) in errors {
+            assert!(manager.record_error(&key, &error));
+        }
+        assert!(!manager.record_error(&"key2", &TestError));
+        assert_eq!(
+            manager.state(&"key1"),
+            Some(EntryState::Failed(Some("TestError")))
+        );
+
+        let degraded: Vec<_> = manager
+            .states()
+            .filter(|(_, state)| !state.is_ready())
+            .map(|(key, _)| *key)
+            .collect();
+        assert_eq!(degraded, vec!["key1"]);
+
+        manager.set_args(&"key1", 3);
+        manager.try_reinit([&"key1"]).for_each(drop);
+        assert_eq!(manager.state(&"key1"), Some(EntryState::Ready));
+        assert_eq!(manager.state(&"missing"), None);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{ChangeKind, ComponentMap, KeyedStorage, Teardown, WithArgs};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    ops::Add,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+// Counters are fed by an observer on the map's change events, so every
+// operation that reports its changes is counted without instrumenting each
+// one. Counting is opt-in because keeping a table of keys requires `Key: Clone`.
+
+pub(crate) type StatsTable<Key> = Arc<Mutex<HashMap<Key, ComponentStats>>>;
+
+/// How often a key's component was built, rebuilt, or failed to build, from
+/// [`stats`](ComponentMap::stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComponentStats {
+    /// Components built from new args: the first insert and every
+    /// replacement with new args.
+    pub inits: u64,
+    /// Components rebuilt from the args the key already had.
+    pub reinits: u64,
+    /// Inits and reinits that returned an error.
+    pub failures: u64,
+}
+
+impl Add for ComponentStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            inits: self.inits + other.inits,
+            reinits: self.reinits + other.reinits,
+            failures: self.failures + other.failures,
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Starts counting inits, reinits, and failures per key. Entries already
+    /// in the map count as initialised once.
+    ///
+    /// Counts outlive the entries they describe, so a key removed and added
+    /// again keeps its history.
+    pub fn with_stats(mut self) -> Self
+    where
+        Key: Clone + Eq + std::hash::Hash + Send + 'static,
+    {
+        if self.events.stats.is_some() {
+            return self;
+        }
+
+        let initial = ComponentStats {
+            inits: 1,
+            ..ComponentStats::default()
+        };
+        let table: StatsTable<Key> = Arc::new(Mutex::new(
+            self.map.keys().map(|key| (key.clone(), initial)).collect(),
+        ));
+        self.events.stats = Some(table.clone());
+        self.events.push(Box::new(move |key: &Key, kind, _, _| {
+            let mut table = table.lock().unwrap_or_else(PoisonError::into_inner);
+            let stats = table.entry(key.clone()).or_default();
+            match kind {
+                ChangeKind::Inserted | ChangeKind::Replaced => stats.inits += 1,
+                ChangeKind::Reinitialized => stats.reinits += 1,
+                ChangeKind::Failed => stats.failures += 1,
+                ChangeKind::Removed => {}
+            }
+            true
+        }));
+        self
+    }
+
+    /// Counters for `key`, or `None` if stats are not enabled with
+    /// [`with_stats`](Self::with_stats) or the key was never seen.
+    pub fn stats<Q>(&self, key: &Q) -> Option<ComponentStats>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+    {
+        let table = self.events.stats.as_ref()?;
+        let table = table.lock().unwrap_or_else(PoisonError::into_inner);
+        table.get(key).copied()
+    }
+
+    /// Counters summed across every key, or `None` if stats are not enabled.
+    pub fn stats_all(&self) -> Option<ComponentStats> {
+        let table = self.events.stats.as_ref()?;
+        let table = table.lock().unwrap_or_else(PoisonError::into_inner);
+        Some(
+            table
+                .values()
+                .copied()
+                .fold(ComponentStats::default(), Add::add),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[test]
+    fn test_stats_count_inits_reinits_and_failures() {
+        let init = |_key: &&str, args: &usize| match *args {
+            0 => Err(TestError),
+            args => Ok(Counter(args)),
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1), ("key2", 2)], init)
+            .unwrap()
+            .with_stats();
+
+        manager.try_reinit([&"key1"]).for_each(drop);
+        manager.try_update([("key2", 20)]).for_each(drop);
+        manager.set_args(&"key1", 0);
+        manager.try_reinit([&"key1"]).for_each(drop);
+
+        assert_eq!(
+            manager.stats(&"key1"),
+            Some(ComponentStats {
+                inits: 1,
+                reinits: 1,
+                failures: 1,
+            })
+        );
+        assert_eq!(manager.stats(&"key2").unwrap().inits, 2);
+        assert_eq!(
+            manager.stats_all(),
+            Some(ComponentStats {
+                inits: 3,
+                reinits: 1,
+                failures: 1,
+            })
+        );
+
+        manager.remove(&"key2");
+        assert_eq!(manager.stats(&"key2").unwrap().inits, 2);
+        assert_eq!(manager.stats(&"missing"), None);
+    }
+
+    #[test]
+    fn test_stats_disabled_by_default() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let manager = ComponentMap::init([("key1", 1)], init);
+
+        assert_eq!(manager.stats(&"key1"), None);
+        assert_eq!(manager.stats_all(), None);
+    }
+}
@@ -0,0 +1,415 @@
+use alloc::collections::BTreeMap;
+use core::{borrow::Borrow, hash::BuildHasher};
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::{HashMap, HashSet, hash_map};
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap, HashSet, hash_map};
+
+// A `BTreeMap` store keeps the entries in key order and an `IndexMap` store
+// (`indexmap` feature) in insertion order, which then carries over to
+// iteration and every operation visiting the whole map. Removals go through
+// `Lookup::take` so they don't disturb that order.
+//
+// Backends differ in what they need from the key (hashing, ordering), so the
+// operations are split by those needs: `Storage` asks nothing of the key and
+// is all a map needs to be dropped, while `Lookup` and `KeyedStorage` carry
+// whatever bounds the backend puts on its impls.
+
+/// The default storage behind [`ComponentMap::map`](crate::ComponentMap::map),
+/// `hashbrown`'s `HashMap` when the `std` feature is off.
+#[cfg(not(feature = "std"))]
+pub use hashbrown::HashMap as Entries;
+/// The default storage behind [`ComponentMap::map`](crate::ComponentMap::map),
+/// `hashbrown`'s `HashMap` when the `std` feature is off.
+#[cfg(feature = "std")]
+pub use std::collections::HashMap as Entries;
+
+/// A map backend for the entries of a [`ComponentMap`](crate::ComponentMap),
+/// selected through its `Store` parameter.
+///
+/// Only covers operations visiting the whole map; see [`Lookup`] and
+/// [`KeyedStorage`] for those going through a key.
+pub trait Storage<Key, Value>: IntoIterator<Item = (Key, Value)> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a Key, &'a Value)>
+    where
+        Key: 'a,
+        Value: 'a;
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a Key, &'a mut Value)>
+    where
+        Key: 'a,
+        Value: 'a;
+
+    fn keys<'a>(&'a self) -> impl Iterator<Item = &'a Key>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        self.iter().map(|(key, _)| key)
+    }
+
+    fn values<'a>(&'a self) -> impl Iterator<Item = &'a Value>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        self.iter().map(|(_, value)| value)
+    }
+
+    fn values_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut Value>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        self.iter_mut().map(|(_, value)| value)
+    }
+
+    /// Removes and yields every entry, in order.
+    fn take_all(&mut self) -> impl Iterator<Item = (Key, Value)>;
+}
+
+/// Lookups of a [`Storage`] through `Q`, a borrowed form of its key.
+pub trait Lookup<Key, Value, Q: ?Sized = Key>: Storage<Key, Value> {
+    fn get(&self, key: &Q) -> Option<&Value>;
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut Value>;
+
+    fn get_key_value(&self, key: &Q) -> Option<(&Key, &Value)>;
+
+    fn contains_key(&self, key: &Q) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes the entry at `key`, keeping the order of the others.
+    fn take(&mut self, key: &Q) -> Option<(Key, Value)>;
+}
+
+/// Insertions and removals of a [`Storage`] that go through owned keys.
+pub trait KeyedStorage<Key, Value>: Lookup<Key, Value> {
+    fn insert(&mut self, key: Key, value: Value) -> Option<Value>;
+
+    /// Inserts `value` at `key` and hands `inspect` the stored key and value
+    /// along with the value they replaced, if any.
+    fn upsert<R>(
+        &mut self,
+        key: Key,
+        value: Value,
+        inspect: impl FnOnce(&Key, &Value, Option<Value>) -> R,
+    ) -> (R, &mut Value);
+
+    /// Removes and yields, in order, every entry for which `predicate` returns
+    /// `true`.
+    fn take_if(
+        &mut self,
+        predicate: impl FnMut(&Key, &mut Value) -> bool,
+    ) -> impl Iterator<Item = (Key, Value)>;
+}
+
+impl<Key, Value, S> Storage<Key, Value> for HashMap<Key, Value, S> {
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a Key, &'a Value)>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        HashMap::iter(self)
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a Key, &'a mut Value)>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        HashMap::iter_mut(self)
+    }
+
+    fn take_all(&mut self) -> impl Iterator<Item = (Key, Value)> {
+        self.drain()
+    }
+}
+
+impl<Key, Value, S, Q> Lookup<Key, Value, Q> for HashMap<Key, Value, S>
+where
+    Key: Eq + core::hash::Hash + Borrow<Q>,
+    Q: Eq + core::hash::Hash + ?Sized,
+    S: BuildHasher,
+{
+    fn get(&self, key: &Q) -> Option<&Value> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut Value> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn get_key_value(&self, key: &Q) -> Option<(&Key, &Value)> {
+        HashMap::get_key_value(self, key)
+    }
+
+    fn take(&mut self, key: &Q) -> Option<(Key, Value)> {
+        self.remove_entry(key)
+    }
+}
+
+impl<Key, Value, S> KeyedStorage<Key, Value> for HashMap<Key, Value, S>
+where
+    Key: Eq + core::hash::Hash,
+    S: BuildHasher,
+{
+    fn insert(&mut self, key: Key, value: Value) -> Option<Value> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn upsert<R>(
+        &mut self,
+        key: Key,
+        value: Value,
+        inspect: impl FnOnce(&Key, &Value, Option<Value>) -> R,
+    ) -> (R, &mut Value) {
+        use hash_map::Entry;
+
+        let (prev, entry) = match self.entry(key) {
+            Entry::Occupied(mut entry) => (Some(entry.insert(value)), entry),
+            Entry::Vacant(entry) => (None, entry.insert_entry(value)),
+        };
+        let output = inspect(entry.key(), entry.get(), prev);
+        (output, entry.into_mut())
+    }
+
+    fn take_if(
+        &mut self,
+        predicate: impl FnMut(&Key, &mut Value) -> bool,
+    ) -> impl Iterator<Item = (Key, Value)> {
+        self.extract_if(predicate)
+    }
+}
+
+impl<Key, Value> Storage<Key, Value> for BTreeMap<Key, Value> {
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a Key, &'a Value)>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        BTreeMap::iter(self)
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a Key, &'a mut Value)>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        BTreeMap::iter_mut(self)
+    }
+
+    fn take_all(&mut self) -> impl Iterator<Item = (Key, Value)> {
+        core::mem::take(self).into_iter()
+    }
+}
+
+impl<Key, Value, Q> Lookup<Key, Value, Q> for BTreeMap<Key, Value>
+where
+    Key: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    fn get(&self, key: &Q) -> Option<&Value> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut Value> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn get_key_value(&self, key: &Q) -> Option<(&Key, &Value)> {
+        BTreeMap::get_key_value(self, key)
+    }
+
+    fn take(&mut self, key: &Q) -> Option<(Key, Value)> {
+        self.remove_entry(key)
+    }
+}
+
+impl<Key, Value> KeyedStorage<Key, Value> for BTreeMap<Key, Value>
+where
+    Key: Ord,
+{
+    fn insert(&mut self, key: Key, value: Value) -> Option<Value> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn upsert<R>(
+        &mut self,
+        key: Key,
+        value: Value,
+        inspect: impl FnOnce(&Key, &Value, Option<Value>) -> R,
+    ) -> (R, &mut Value) {
+        use alloc::collections::btree_map::Entry;
+
+        let (prev, entry) = match self.entry(key) {
+            Entry::Occupied(mut entry) => (Some(entry.insert(value)), entry),
+            Entry::Vacant(entry) => (None, entry.insert_entry(value)),
+        };
+        let output = inspect(entry.key(), entry.get(), prev);
+        (output, entry.into_mut())
+    }
+
+    fn take_if(
+        &mut self,
+        predicate: impl FnMut(&Key, &mut Value) -> bool,
+    ) -> impl Iterator<Item = (Key, Value)> {
+        self.extract_if(.., predicate)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<Key, Value, S> Storage<Key, Value> for indexmap::IndexMap<Key, Value, S> {
+    fn len(&self) -> usize {
+        indexmap::IndexMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a Key, &'a Value)>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        indexmap::IndexMap::iter(self)
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a Key, &'a mut Value)>
+    where
+        Key: 'a,
+        Value: 'a,
+    {
+        indexmap::IndexMap::iter_mut(self)
+    }
+
+    fn take_all(&mut self) -> impl Iterator<Item = (Key, Value)> {
+        self.drain(..)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<Key, Value, S, Q> Lookup<Key, Value, Q> for indexmap::IndexMap<Key, Value, S>
+where
+    Key: Eq + core::hash::Hash + Borrow<Q>,
+    Q: Eq + core::hash::Hash + ?Sized,
+    S: BuildHasher,
+{
+    fn get(&self, key: &Q) -> Option<&Value> {
+        indexmap::IndexMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut Value> {
+        indexmap::IndexMap::get_mut(self, key)
+    }
+
+    fn get_key_value(&self, key: &Q) -> Option<(&Key, &Value)> {
+        indexmap::IndexMap::get_key_value(self, key)
+    }
+
+    fn take(&mut self, key: &Q) -> Option<(Key, Value)> {
+        self.shift_remove_entry(key)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<Key, Value, S> KeyedStorage<Key, Value> for indexmap::IndexMap<Key, Value, S>
+where
+    Key: Eq + core::hash::Hash,
+    S: BuildHasher,
+{
+    fn insert(&mut self, key: Key, value: Value) -> Option<Value> {
+        indexmap::IndexMap::insert(self, key, value)
+    }
+
+    fn upsert<R>(
+        &mut self,
+        key: Key,
+        value: Value,
+        inspect: impl FnOnce(&Key, &Value, Option<Value>) -> R,
+    ) -> (R, &mut Value) {
+        use indexmap::map::Entry;
+
+        let (prev, entry) = match self.entry(key) {
+            Entry::Occupied(mut entry) => (Some(entry.insert(value)), entry),
+            Entry::Vacant(entry) => (None, entry.insert_entry(value)),
+        };
+        let output = inspect(entry.key(), entry.get(), prev);
+        (output, entry.into_mut())
+    }
+
+    fn take_if(
+        &mut self,
+        predicate: impl FnMut(&Key, &mut Value) -> bool,
+    ) -> impl Iterator<Item = (Key, Value)> {
+        self.extract_if(.., predicate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    fn exercise(mut storage: impl KeyedStorage<&'static str, usize>) {
+        assert_eq!(storage.insert("a", 1), None);
+        assert_eq!(storage.insert("b", 2), None);
+        assert_eq!(storage.insert("c", 3), None);
+
+        *storage.get_mut(&"b").unwrap() *= 10;
+        assert_eq!(storage.get(&"b"), Some(&20));
+        assert_eq!(storage.take(&"a"), Some(("a", 1)));
+        assert_eq!(storage.get_mut(&"a"), None);
+
+        let (replaced, value) = storage.upsert("c", 4, |key, value, prev| (*key, *value, prev));
+        assert_eq!(replaced, ("c", 4, Some(3)));
+        *value += 1;
+        assert_eq!(storage.get(&"c"), Some(&5));
+
+        let taken: Vec<_> = storage.take_if(|_, value| *value > 10).collect();
+        assert_eq!(taken, vec![("b", 20)]);
+        assert_eq!(storage.take_all().collect::<Vec<_>>(), vec![("c", 5)]);
+        assert!(storage.is_empty());
+    }
+
+    #[test]
+    fn test_storage_backends() {
+        exercise(HashMap::new());
+        exercise(BTreeMap::new());
+        #[cfg(feature = "indexmap")]
+        exercise(indexmap::IndexMap::new());
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn test_insertion_order_is_preserved() {
+        let init = |_key: &&str, args: &usize| *args;
+        let mut manager =
+            crate::ComponentMap::new(indexmap::IndexMap::new(), init, crate::NoTeardown);
+        manager.extend([("zulu", 1), ("alpha", 2), ("mike", 3)]);
+        manager.update([("bravo", 4)]).for_each(drop);
+
+        let keys = |manager: &crate::ComponentMap<_, _, _, _, _, _>| {
+            manager.iter().map(|(key, ..)| *key).collect::<Vec<_>>()
+        };
+        assert_eq!(keys(&manager), vec!["zulu", "alpha", "mike", "bravo"]);
+
+        let order: Vec<_> = manager.reinit_all().map(|prev| *prev.key).collect();
+        assert_eq!(order, vec!["zulu", "alpha", "mike", "bravo"]);
+
+        manager.remove(&"alpha");
+        assert_eq!(keys(&manager), vec!["zulu", "mike", "bravo"]);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    AsyncTeardown, BatchOptions, ChangeKind, ComponentMap, Health, KeyedError, KeyedStorage,
+    ReinitOutcome, RetryPolicy, Teardown, WithArgs,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Which components a [`Supervisor`] restarts when one of them fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartStrategy {
+    /// Restart only the failed component.
+    OneForOne,
+    /// Restart every component, for maps whose components depend on each other.
+    OneForAll,
+}
+
+/// Collects keys whose init failed or whose health check reports them
+/// unhealthy, and restarts them on [`supervise_async`](ComponentMap::supervise_async).
+///
+/// Created by [`ComponentMap::supervisor`]; restarts are retried according to
+/// `restart`, after which the key is reported as given up.
+#[derive(Debug, Clone)]
+pub struct Supervisor<Key> {
+    pub strategy: RestartStrategy,
+    pub restart: RetryPolicy,
+    failed: Arc<Mutex<HashSet<Key>>>,
+}
+
+impl<Key> Supervisor<Key>
+where
+    Key: Eq + std::hash::Hash,
+{
+    fn take_failed(&self) -> HashSet<Key> {
+        std::mem::take(&mut *self.failed.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Outcome of one [`supervise_async`](ComponentMap::supervise_async) pass.
+#[derive(Debug)]
+pub struct SupervisionReport<Key, Error> {
+    pub restarted: Vec<Key>,
+    /// Keys still failing after every restart attempt; their previous
+    /// component, if any, is left in place.
+    pub gave_up: Vec<KeyedError<Key, Error>>,
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Attaches a supervisor that records every subsequent init failure.
+    pub fn supervisor(&mut self, strategy: RestartStrategy, restart: RetryPolicy) -> Supervisor<Key>
+    where
+        Key: Clone + Eq + std::hash::Hash + Send + 'static,
+    {
+        let failed = Arc::new(Mutex::new(HashSet::new()));
+        let sink = Arc::downgrade(&failed);
+        self.events.push(Box::new(move |key: &Key, kind, _, _| {
+            let Some(failed) = sink.upgrade() else {
+                return false;
+            };
+            if kind == ChangeKind::Failed {
+                failed
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(key.clone());
+            }
+            true
+        }));
+
+        Supervisor {
+            strategy,
+            restart,
+            failed,
+        }
+    }
+
+    /// Restarts the components `supervisor` has seen fail since the last pass,
+    /// plus any the registered health check reports as unhealthy.
+    pub async fn supervise_async<Error>(
+        &mut self,
+        supervisor: &Supervisor<Key>,
+    ) -> SupervisionReport<Key, Error>
+    where
+        Key: Clone + Eq + std::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+        Error: std::fmt::Debug,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let mut targets = supervisor.take_failed();
+        if let Some(report) = self.health_report_async().await {
+            targets.extend(
+                report
+                    .entries
+                    .into_iter()
+                    .filter(|entry| matches!(entry.value, Health::Unhealthy(_)))
+                    .map(|entry| entry.key.clone()),
+            );
+        }
+        targets.retain(|key| self.map.contains_key(key));
+
+        let targets: Vec<_> = match supervisor.strategy {
+            RestartStrategy::OneForAll if !targets.is_empty() => self.map.keys().cloned().collect(),
+            _ => targets.into_iter().collect(),
+        };
+
+        let mut report = SupervisionReport {
+            restarted: Vec::new(),
+            gave_up: Vec::new(),
+        };
+        let options = BatchOptions::default().retry(supervisor.restart);
+        let mut deferred = Vec::new();
+        for outcome in self.try_reinit_async_with(targets.iter(), options).await {
+            match outcome.value {
+                ReinitOutcome::Failed(error) => report
+                    .gave_up
+                    .push(KeyedError::new(outcome.key.clone(), error)),
+                ReinitOutcome::Missing => {}
+                ReinitOutcome::Throttled | ReinitOutcome::TimedOut => {
+                    deferred.push(outcome.key.clone())
+                }
+                _ => report.restarted.push(outcome.key.clone()),
+            }
+        }
+
+        // Failures recorded during this pass have already been handled, while
+        // throttled and timed out keys are left for the next one
+        supervisor.take_failed();
+        supervisor
+            .failed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(deferred);
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Backoff;
+    use std::{collections::HashMap, time::Duration};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    type Failures = Arc<Mutex<HashMap<&'static str, usize>>>;
+
+    /// Init that fails as many times as `failures` holds for the key.
+    fn flaky(
+        failures: Failures,
+    ) -> impl AsyncFn(&&'static str, &usize) -> Result<Counter, TestError> + Clone {
+        async move |key: &&'static str, args: &usize| match failures.lock().unwrap().get_mut(key) {
+            Some(remaining) if *remaining > 0 => {
+                *remaining -= 1;
+                Err(TestError)
+            }
+            _ => Ok(Counter(*args)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_one_for_one_restarts_failed_keys() {
+        let failures = Failures::default();
+        let mut manager =
+            ComponentMap::try_init_async([("key1", 1), ("key2", 2)], flaky(failures.clone()))
+                .await
+                .unwrap();
+        let policy = RetryPolicy::new(3, Backoff::Fixed(Duration::ZERO));
+        let supervisor = manager.supervisor(RestartStrategy::OneForOne, policy);
+
+        failures.lock().unwrap().insert("key1", 1);
+        manager.try_reinit_async([&"key1"]).await.for_each(drop);
+
+        // The restart fails once more, then succeeds on retry
+        failures.lock().unwrap().insert("key1", 1);
+        let report = manager.supervise_async(&supervisor).await;
+        assert_eq!(report.restarted, vec!["key1"]);
+        assert!(report.gave_up.is_empty());
+
+        let report = manager.supervise_async(&supervisor).await;
+        assert!(report.restarted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_and_one_for_all() {
+        let failures = Failures::default();
+        let mut manager =
+            ComponentMap::try_init_async([("key1", 1), ("key2", 2)], flaky(failures.clone()))
+                .await
+                .unwrap();
+        let policy = RetryPolicy::new(2, Backoff::Fixed(Duration::ZERO));
+        let supervisor = manager.supervisor(RestartStrategy::OneForAll, policy);
+
+        failures.lock().unwrap().insert("key1", 3);
+        manager.try_reinit_async([&"key1"]).await.for_each(drop);
+
+        let report = manager.supervise_async(&supervisor).await;
+
+        // key1 exhausts both attempts; key2 is restarted alongside it
+        assert_eq!(report.restarted, vec!["key2"]);
+        assert_eq!(report.gave_up, vec![KeyedError::new("key1", TestError)]);
+        assert!(
+            manager
+                .supervise_async(&supervisor)
+                .await
+                .gave_up
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{ComponentMap, Keyed, Teardown, WithArgs, events::Instrumentation};
+use arc_swap::ArcSwap;
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Immutable view of a [`SwapComponentMap`] at one point in time.
+pub type Snapshot<Key, Args, Comp> = HashMap<Key, Arc<WithArgs<Args, Comp>>>;
+
+/// Read-mostly counterpart of [`ComponentMap`] where readers never lock.
+///
+/// Readers load an `Arc` of the current [`Snapshot`]; writers build a new
+/// snapshot, sharing untouched entries, and atomically swap it in. Replaced
+/// components stay alive until the last snapshot referencing them is dropped,
+/// so no teardown is run.
+///
+/// Builds and removals are reported to the instrument the source map was
+/// given with [`with_instrument`](ComponentMap::with_instrument); its other
+/// listeners are not carried over.
+#[derive(Debug)]
+pub struct SwapComponentMap<Key, Args, Comp, FnInit> {
+    current: ArcSwap<Snapshot<Key, Args, Comp>>,
+    init: FnInit,
+    instrument: Instrumentation<Key, Args>,
+    writer: Mutex<()>,
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> From<ComponentMap<Key, Args, Comp, FnInit, FnDrop>>
+    for SwapComponentMap<Key, Args, Comp, FnInit>
+where
+    Key: Eq + std::hash::Hash,
+    FnDrop: Teardown<Key, Comp>,
+{
+    fn from(map: ComponentMap<Key, Args, Comp, FnInit, FnDrop>) -> Self {
+        let instrument = map.events.instrument.clone();
+        let (map, init) = map.into_parts();
+        let snapshot = map
+            .into_iter()
+            .map(|(key, component)| (key, Arc::new(component)))
+            .collect();
+
+        Self {
+            current: ArcSwap::from_pointee(snapshot),
+            init,
+            instrument,
+            writer: Mutex::new(()),
+        }
+    }
+}
+
+impl<Key, Args, Comp, FnInit> SwapComponentMap<Key, Args, Comp, FnInit>
+where
+    Key: Eq + std::hash::Hash,
+{
+    pub fn snapshot(&self) -> Arc<Snapshot<Key, Args, Comp>> {
+        self.current.load_full()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<Arc<WithArgs<Args, Comp>>>
+    where
+        Key: Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+    {
+        self.current.load().get(key).cloned()
+    }
+
+    /// Builds components for `updates` and publishes them in a single swap,
+    /// returning the entries they replaced.
+    #[allow(clippy::type_complexity)]
+    pub fn update(
+        &self,
+        updates: impl IntoIterator<Item = (Key, Args)>,
+    ) -> Vec<Keyed<Key, Option<Arc<WithArgs<Args, Comp>>>>>
+    where
+        Key: Clone,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut next = Snapshot::clone(&self.current.load());
+
+        let prev = updates
+            .into_iter()
+            .map(|(key, args)| {
+                let component = self
+                    .instrument
+                    .build(&key, &args, || (self.init)(&key, &args));
+                let component = WithArgs::new(component, args);
+                let prev = next.insert(key.clone(), Arc::new(component));
+                Keyed::new(key, prev)
+            })
+            .collect();
+
+        self.current.store(Arc::new(next));
+        prev
+    }
+
+    /// Rebuilds every component from its current args and publishes the result
+    /// in a single swap; readers see either all old or all new components.
+    pub fn reinit_all(&self)
+    where
+        Key: Clone,
+        FnInit: Fn(&Key, &Args) -> Comp,
+        Args: Clone,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let next = self
+            .current
+            .load()
+            .iter()
+            .map(|(key, component)| {
+                let args = component.args.clone();
+                let component = self
+                    .instrument
+                    .build(key, &args, || (self.init)(key, &args));
+                let component = WithArgs::new(component, args);
+                (key.clone(), Arc::new(component))
+            })
+            .collect();
+
+        self.current.store(Arc::new(next));
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<Arc<WithArgs<Args, Comp>>>
+    where
+        Key: Clone + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut next = Snapshot::clone(&self.current.load());
+        let (key, prev) = next.remove_entry(key)?;
+
+        self.current.store(Arc::new(next));
+        if let Some(instrument) = &self.instrument.instrument {
+            instrument.on_remove(&key);
+        }
+        Some(prev)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_snapshots_are_isolated_from_writes() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let swap = SwapComponentMap::from(ComponentMap::init([("key1", 1), ("key2", 2)], init));
+
+        let before = swap.snapshot();
+        let prev = swap.update([("key1", 10)]);
+        let after = swap.snapshot();
+
+        assert_eq!(prev[0].value.as_ref().unwrap().component, Counter(1));
+        assert_eq!(before[&"key1"].component, Counter(1));
+        assert_eq!(after[&"key1"].component, Counter(10));
+        // Untouched entries are shared between snapshots
+        assert!(Arc::ptr_eq(&before[&"key2"], &after[&"key2"]));
+
+        assert!(swap.remove(&"key2").is_some());
+        assert!(swap.get(&"key2").is_none());
+        assert_eq!(after.len(), 2);
+    }
+
+    #[test]
+    fn test_reinit_all_swaps_atomically() {
+        let init = |key: &usize, args: &usize| Counter(key + args);
+        let swap = SwapComponentMap::from(ComponentMap::init((0..10).map(|key| (key, 1)), init));
+
+        let before = swap.snapshot();
+        swap.reinit_all();
+
+        assert!(before.values().all(|entry| entry.args == 1));
+        assert!(
+            swap.snapshot()
+                .iter()
+                .all(|(key, entry)| !Arc::ptr_eq(entry, &before[key]))
+        );
+        assert_eq!(swap.get(&3).unwrap().component, Counter(4));
+    }
+}
@@ -1,76 +1,350 @@
-use crate::{ComponentMap, Keyed, WithArgs};
+use crate::{
+    ChangeKind, ComponentMap, Entries, Keyed, KeyedError, KeyedStorage, Lookup, NoTeardown,
+    ReinitOutcome, Teardown, WithArgs, priority::prioritized,
+};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
 
 impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
     pub fn try_init<Error>(
         entries: impl IntoIterator<Item = (Key, Args)>,
         init: FnInit,
-    ) -> Result<Self, Error>
+    ) -> Result<Self, KeyedError<Key, Error>>
     where
-        Key: Eq + std::hash::Hash,
+        Key: Eq + core::hash::Hash,
         FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
     {
         let map = entries
             .into_iter()
-            .map(|(key, args)| {
-                let component = (init)(&key, &args)?;
-                Ok((key, WithArgs { component, args }))
+            .map(|(key, args)| match (init)(&key, &args) {
+                Ok(component) => Ok((key, WithArgs::new(component, args))),
+                Err(error) => Err(KeyedError::new(key, error)),
             })
             .collect::<Result<_, _>>()?;
 
-        Ok(Self { map: map, init })
+        Ok(Self::new(map, init, NoTeardown))
     }
 
+    /// Like [`try_init`](Self::try_init), but keeps every component that
+    /// initialised successfully and reports the failed keys instead of aborting.
+    pub fn try_init_partial<Error>(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+    ) -> (Self, Vec<KeyedError<Key, Error>>)
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+    {
+        let mut map = Entries::new();
+        let mut failures = Vec::new();
+
+        for (key, args) in entries {
+            match (init)(&key, &args) {
+                Ok(component) => {
+                    map.insert(key, WithArgs::new(component, args));
+                }
+                Err(error) => failures.push(KeyedError::new(key, error)),
+            }
+        }
+
+        (Self::new(map, init, NoTeardown), failures)
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
     pub fn try_reinit_all<Error>(
         &mut self,
     ) -> impl Iterator<Item = Keyed<&Key, Result<Comp, Error>>>
     where
+        Key: Eq + core::hash::Hash,
         FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
     {
-        self.map.iter_mut().map(|(key, component)| {
-            let result = (self.init)(key, &component.args)
-                .map(|next| std::mem::replace(&mut component.component, next));
+        prioritized(self.map.iter_mut(), &self.dependencies, &self.priorities)
+            .into_iter()
+            .map(|(key, component)| {
+                let result = self
+                    .events
+                    .instrument
+                    .build(key, &component.args, || (self.init)(key, &component.args))
+                    .map(|next| {
+                        let mut prev = component.replace_component(next);
+                        self.teardown.teardown(key, &mut prev);
+                        prev
+                    })
+                    .inspect_err(|_| component.record_failure());
+                self.events
+                    .emit_result(key, &result, ChangeKind::Reinitialized, &*component);
+
+                Keyed::new(key, result)
+            })
+    }
 
-            Keyed::new(key, result)
-        })
+    /// Reinitialises every component only if all of them initialise, so the
+    /// map is never left with a mix of old and new components. On failure the
+    /// freshly built components are torn down and every error is returned.
+    #[allow(clippy::type_complexity)]
+    pub fn try_reinit_all_atomic<Error>(
+        &mut self,
+    ) -> Result<Vec<Keyed<&Key, Comp>>, Vec<KeyedError<&Key, Error>>>
+    where
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let results: Vec<_> = self
+            .map
+            .iter()
+            .map(|(key, component)| {
+                self.events
+                    .instrument
+                    .build(key, &component.args, || (self.init)(key, &component.args))
+            })
+            .collect();
+
+        if results.iter().any(Result::is_err) {
+            let mut failures = Vec::new();
+            for ((key, component), result) in self.map.iter_mut().zip(results) {
+                match result {
+                    Ok(mut discarded) => self.teardown.teardown(key, &mut discarded),
+                    Err(error) => {
+                        component.record_failure();
+                        self.events.emit_failed(key, &component.args, &error);
+                        failures.push(KeyedError::new(key, error))
+                    }
+                }
+            }
+            return Err(failures);
+        }
+
+        // The map is unchanged since `results` was built, so iteration order matches
+        Ok(self
+            .map
+            .iter_mut()
+            .zip(results.into_iter().flatten())
+            .map(|((key, component), next)| {
+                let mut prev = component.replace_component(next);
+                self.teardown.teardown(key, &mut prev);
+                self.events
+                    .emit(key, ChangeKind::Reinitialized, Some(&*component));
+                Keyed::new(key, prev)
+            })
+            .collect())
     }
 
-    pub fn try_reinit<Error>(
+    pub fn try_reinit<'q, Q, Error>(
         &mut self,
-        keys: impl IntoIterator<Item = Key>,
-    ) -> impl Iterator<Item = Keyed<Key, Option<Result<Comp, Error>>>>
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, ReinitOutcome<Comp, Error>>>
     where
-        Key: Eq + std::hash::Hash,
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
         FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
     {
         keys.into_iter().map(|key| {
-            let prev = self.map.get_mut(&key).map(|component| {
-                (self.init)(&key, &component.args)
-                    .map(|next| std::mem::replace(&mut component.component, next))
-            });
-
-            Keyed::new(key, prev)
+            #[cfg(feature = "std")]
+            if self.is_throttled(key) {
+                return Keyed::new(key, ReinitOutcome::Throttled);
+            }
+            let prev = self.reinit_entry(key, |init, key, entry| init(key, &entry.args));
+            Keyed::new(key, ReinitOutcome::from_reinit(prev))
         })
     }
 
+    /// Replaces the component at `key` with the one `next` builds from the
+    /// current entry, tearing down the previous one. The entry stays in the
+    /// map throughout, so a panicking `next` leaves it untouched.
+    pub(crate) fn reinit_entry<Q, Error>(
+        &mut self,
+        key: &Q,
+        next: impl FnOnce(&FnInit, &Key, &WithArgs<Args, Comp>) -> Result<Comp, Error>,
+    ) -> Option<Result<Comp, Error>>
+    where
+        Q: ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        Error: core::fmt::Debug,
+    {
+        let (owned_key, entry) = self.map.get_key_value(key)?;
+        let result = self.events.instrument.build(owned_key, &entry.args, || {
+            next(&self.init, owned_key, entry)
+        });
+
+        let entry = self.map.get_mut(key)?;
+        let result = match result {
+            Ok(next) => Ok(entry.replace_component(next)),
+            Err(error) => {
+                entry.record_failure();
+                Err(error)
+            }
+        };
+
+        let (key, entry) = self.map.get_key_value(key)?;
+        let result = result.map(|mut prev| {
+            self.teardown.teardown(key, &mut prev);
+            prev
+        });
+        self.events
+            .emit_result(key, &result, ChangeKind::Reinitialized, entry);
+        Some(result)
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn try_update<Error>(
         &mut self,
         updates: impl IntoIterator<Item = (Key, Args)>,
-    ) -> impl Iterator<Item = Keyed<Key, Option<Result<WithArgs<Args, Comp>, Error>>>>
+    ) -> impl Iterator<Item = Keyed<Key, ReinitOutcome<WithArgs<Args, Comp>, Error>>>
     where
-        Key: Clone + Eq + std::hash::Hash,
+        Key: Clone + Eq + core::hash::Hash,
         FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
     {
-        updates.into_iter().map(move |(key, args)| {
-            let result = (self.init)(&key, &args)
-                .map(|component| self.map.insert(key.clone(), WithArgs { component, args }));
+        updates
+            .into_iter()
+            .map(move |(key, args)| self.try_update_entry(key, args))
+    }
 
-            Keyed::new(key, result.transpose())
+    /// Like [`try_update`](Self::try_update), but keys whose args equal the
+    /// stored ones are left untouched and reported as
+    /// [`Unchanged`](ReinitOutcome::Unchanged). Entries whose args were
+    /// replaced via `set_args` are always rebuilt.
+    #[allow(clippy::type_complexity)]
+    pub fn try_update_if_changed<Error>(
+        &mut self,
+        updates: impl IntoIterator<Item = (Key, Args)>,
+    ) -> impl Iterator<Item = Keyed<Key, ReinitOutcome<WithArgs<Args, Comp>, Error>>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        Args: PartialEq,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        updates.into_iter().map(move |(key, args)| {
+            let unchanged = self
+                .map
+                .get(&key)
+                .is_some_and(|entry| !entry.is_dirty() && entry.args == args);
+            if unchanged {
+                Keyed::new(key, ReinitOutcome::Unchanged)
+            } else {
+                self.try_update_entry(key, args)
+            }
         })
     }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn try_update_entry<Error>(
+        &mut self,
+        key: Key,
+        args: Args,
+    ) -> Keyed<Key, ReinitOutcome<WithArgs<Args, Comp>, Error>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let result = self
+            .events
+            .instrument
+            .build(&key, &args, || (self.init)(&key, &args))
+            .inspect_err(|error| self.events.emit_failed(&key, &args, error))
+            .map(|component| {
+                self.map
+                    .insert(key.clone(), WithArgs::new(component, args))
+                    .map(|mut prev| {
+                        self.teardown.teardown(&key, &mut prev.component);
+                        prev
+                    })
+            });
+        if let Ok(prev) = &result {
+            self.events
+                .emit(&key, ChangeKind::upsert(prev.is_some()), self.map.get(&key));
+        }
+        self.enforce_capacity();
+
+        Keyed::new(key, ReinitOutcome::from_update(result))
+    }
+
+    /// Initialises every update before touching the map, so either all of
+    /// them are applied or none are. On failure the components that did
+    /// initialise are torn down and every error is returned.
+    #[allow(clippy::type_complexity)]
+    pub fn try_update_atomic<Error>(
+        &mut self,
+        updates: impl IntoIterator<Item = (Key, Args)>,
+    ) -> Result<Vec<Keyed<Key, Option<WithArgs<Args, Comp>>>>, Vec<KeyedError<Key, Error>>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        let mut ready = Vec::new();
+        let mut failures = Vec::new();
+
+        for (key, args) in updates {
+            let result = self
+                .events
+                .instrument
+                .build(&key, &args, || (self.init)(&key, &args));
+            match result {
+                Ok(component) => ready.push((key, WithArgs::new(component, args))),
+                Err(error) => {
+                    self.events.emit_failed(&key, &args, &error);
+                    failures.push(KeyedError::new(key, error));
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            for (key, mut discarded) in ready {
+                self.teardown.teardown(&key, &mut discarded.component);
+            }
+            return Err(failures);
+        }
+
+        Ok(ready
+            .into_iter()
+            .map(|(key, component)| {
+                let prev = self.map.insert(key.clone(), component).map(|mut prev| {
+                    self.teardown.teardown(&key, &mut prev.component);
+                    prev
+                });
+                self.events
+                    .emit(&key, ChangeKind::upsert(prev.is_some()), self.map.get(&key));
+                self.enforce_capacity();
+                Keyed::new(key, prev)
+            })
+            .collect())
+    }
+
+    pub fn get_or_try_init<Error>(&mut self, key: Key, args: Args) -> Result<&mut Comp, Error>
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Result<Comp, Error>,
+        Error: core::fmt::Debug,
+    {
+        self.reserve_slot(&key);
+        if self.map.contains_key(&key) {
+            let entry = self.map.get_mut(&key).expect("checked above");
+            entry.touch();
+            return Ok(&mut entry.component);
+        }
+        let component = self
+            .events
+            .instrument
+            .build(&key, &args, || (self.init)(&key, &args))
+            .inspect_err(|error| self.events.emit_failed(&key, &args, error))?;
+        let (_, entry) = self.insert_entry(key, WithArgs::new(component, args));
+        Ok(&mut entry.component)
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
+#[allow(clippy::unnecessary_get_then_check)]
 mod tests {
     use super::*;
     use std::sync::{Arc, Mutex};
@@ -155,7 +429,10 @@ mod tests {
         );
 
         assert!(result.is_err());
-        assert_eq!(result.err().unwrap(), TestError("Failed".to_string()));
+        assert_eq!(
+            result.err().unwrap(),
+            KeyedError::new("key2", TestError("Failed".to_string()))
+        );
     }
 
     #[test]
@@ -168,7 +445,7 @@ mod tests {
             }
         };
 
-        let result: Result<ComponentMap<&str, FailArgs, Counter, _>, TestError> =
+        let result: Result<ComponentMap<&str, FailArgs, Counter, _>, KeyedError<&str, TestError>> =
             ComponentMap::try_init([], init);
 
         assert!(result.is_ok());
@@ -362,7 +639,7 @@ mod tests {
         let results: Vec<_> = manager.try_reinit(["key1"]).collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.as_ref().unwrap().is_ok());
+        assert!(matches!(results[0].value, ReinitOutcome::Replaced(_)));
         assert_eq!(manager.map.get("key1").unwrap().component, Counter(3));
         // key2 should be unchanged from initial
         assert_eq!(manager.map.get("key2").unwrap().component, Counter(6));
@@ -394,7 +671,7 @@ mod tests {
 
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].key, "nonexistent");
-        assert!(results[0].value.is_none());
+        assert!(matches!(results[0].value, ReinitOutcome::Missing));
     }
 
     #[test]
@@ -425,7 +702,7 @@ mod tests {
         let results: Vec<_> = manager.try_reinit(["key1"]).collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.as_ref().unwrap().is_err());
+        assert!(results[0].value.is_failed());
     }
 
     #[test]
@@ -461,7 +738,7 @@ mod tests {
             .collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.is_none());
+        assert!(matches!(results[0].value, ReinitOutcome::Inserted));
         assert_eq!(manager.map.len(), 2);
         assert_eq!(manager.map.get("key2").unwrap().component, Counter(20));
     }
@@ -499,8 +776,9 @@ mod tests {
             .collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.is_some());
-        let prev = results[0].value.as_ref().unwrap().as_ref().unwrap();
+        let ReinitOutcome::Replaced(prev) = &results[0].value else {
+            panic!("expected a replaced entry");
+        };
         assert_eq!(prev.component, Counter(1));
 
         assert_eq!(manager.map.get("key1").unwrap().component, Counter(10));
@@ -539,8 +817,7 @@ mod tests {
             .collect();
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].value.is_some());
-        assert!(results[0].value.as_ref().unwrap().is_err());
+        assert!(results[0].value.is_failed());
 
         // Should not insert on error
         assert_eq!(manager.map.len(), 1);
@@ -603,4 +880,239 @@ mod tests {
         assert!(manager.map.get("key3").is_none());
         assert!(manager.map.get("key4").is_some());
     }
+
+    #[test]
+    fn test_get_or_try_init() {
+        let init = |_key: &&str, args: &FailArgs| -> Result<Counter, TestError> {
+            if args.should_fail {
+                Err(TestError("Failed".to_string()))
+            } else {
+                Ok(Counter(args.value))
+            }
+        };
+
+        let mut manager = ComponentMap::try_init(
+            [(
+                "key1",
+                FailArgs {
+                    value: 1,
+                    should_fail: false,
+                },
+            )],
+            init,
+        )
+        .unwrap();
+
+        let existing = manager.get_or_try_init(
+            "key1",
+            FailArgs {
+                value: 100,
+                should_fail: true,
+            },
+        );
+        assert_eq!(existing, Ok(&mut Counter(1)));
+
+        let inserted = manager.get_or_try_init(
+            "key2",
+            FailArgs {
+                value: 2,
+                should_fail: false,
+            },
+        );
+        assert_eq!(inserted, Ok(&mut Counter(2)));
+
+        let failed = manager.get_or_try_init(
+            "key3",
+            FailArgs {
+                value: 3,
+                should_fail: true,
+            },
+        );
+        assert_eq!(failed, Err(TestError("Failed".to_string())));
+
+        // Should not insert on error
+        assert_eq!(manager.map.len(), 2);
+        assert!(!manager.map.contains_key("key3"));
+    }
+
+    #[test]
+    fn test_try_init_partial() {
+        let init = |_key: &&str, args: &FailArgs| -> Result<Counter, TestError> {
+            if args.should_fail {
+                Err(TestError("Failed".to_string()))
+            } else {
+                Ok(Counter(args.value))
+            }
+        };
+
+        let (manager, failures) = ComponentMap::try_init_partial(
+            [
+                (
+                    "key1",
+                    FailArgs {
+                        value: 1,
+                        should_fail: false,
+                    },
+                ),
+                (
+                    "key2",
+                    FailArgs {
+                        value: 2,
+                        should_fail: true,
+                    },
+                ),
+            ],
+            init,
+        );
+
+        assert_eq!(manager.map.len(), 1);
+        assert_eq!(manager.map.get("key1").unwrap().component, Counter(1));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].key, "key2");
+        assert_eq!(failures[0].error, TestError("Failed".to_string()));
+    }
+
+    #[test]
+    fn test_try_update_atomic() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &FailArgs| -> Result<Counter, TestError> {
+            if args.should_fail {
+                Err(TestError("Failed".to_string()))
+            } else {
+                Ok(Counter(args.value))
+            }
+        };
+
+        let mut manager = ComponentMap::try_init(
+            [(
+                "key1",
+                FailArgs {
+                    value: 1,
+                    should_fail: false,
+                },
+            )],
+            init,
+        )
+        .unwrap()
+        .with_teardown(move |key: &&str, component: &mut Counter| {
+            closed_clone.lock().unwrap().push((*key, component.0));
+        });
+
+        let failures = manager
+            .try_update_atomic([
+                (
+                    "key1",
+                    FailArgs {
+                        value: 10,
+                        should_fail: false,
+                    },
+                ),
+                (
+                    "key2",
+                    FailArgs {
+                        value: 20,
+                        should_fail: true,
+                    },
+                ),
+            ])
+            .err()
+            .unwrap();
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].key, "key2");
+        assert_eq!(manager.map.get("key1").unwrap().component, Counter(1));
+        assert!(!manager.map.contains_key("key2"));
+        // The component built for key1 was discarded
+        assert_eq!(*closed.lock().unwrap(), vec![("key1", 10)]);
+
+        let updated = manager
+            .try_update_atomic([(
+                "key2",
+                FailArgs {
+                    value: 20,
+                    should_fail: false,
+                },
+            )])
+            .unwrap();
+
+        assert_eq!(updated.len(), 1);
+        assert!(updated[0].value.is_none());
+        assert_eq!(manager.map.get("key2").unwrap().component, Counter(20));
+    }
+
+    #[test]
+    fn test_try_reinit_all_atomic() {
+        let init = |_key: &&str, args: &FailArgs| -> Result<Counter, TestError> {
+            if args.should_fail {
+                Err(TestError("Failed".to_string()))
+            } else {
+                Ok(Counter(args.value))
+            }
+        };
+
+        let mut manager = ComponentMap::try_init(
+            [
+                (
+                    "key1",
+                    FailArgs {
+                        value: 1,
+                        should_fail: false,
+                    },
+                ),
+                (
+                    "key2",
+                    FailArgs {
+                        value: 2,
+                        should_fail: false,
+                    },
+                ),
+            ],
+            init,
+        )
+        .unwrap();
+
+        manager.map.get_mut("key1").unwrap().args.value = 10;
+        manager.map.get_mut("key2").unwrap().args.should_fail = true;
+
+        let failures = manager.try_reinit_all_atomic().err().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(*failures[0].key, "key2");
+
+        // key1 initialised successfully but was rolled back with the rest
+        assert_eq!(manager.map.get("key1").unwrap().component, Counter(1));
+        assert_eq!(manager.map.get("key2").unwrap().component, Counter(2));
+
+        manager.map.get_mut("key2").unwrap().args.should_fail = false;
+        let replaced = manager.try_reinit_all_atomic().unwrap();
+        assert_eq!(replaced.len(), 2);
+
+        assert_eq!(manager.map.get("key1").unwrap().component, Counter(10));
+        assert_eq!(manager.map.get("key2").unwrap().component, Counter(2));
+    }
+
+    #[test]
+    fn test_try_update_if_changed_skips_equal_args() {
+        let calls = Arc::new(Mutex::new(0));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&str, args: &usize| -> Result<Counter, TestError> {
+            *calls_clone.lock().unwrap() += 1;
+            Ok(Counter(*args))
+        };
+
+        let mut manager = ComponentMap::try_init([("key1", 1), ("key2", 2)], init).unwrap();
+        manager.set_args(&"key2", 2);
+        *calls.lock().unwrap() = 0;
+
+        let results: Vec<_> = manager
+            .try_update_if_changed([("key1", 1), ("key2", 2), ("key3", 3)])
+            .collect();
+
+        assert!(matches!(results[0].value, ReinitOutcome::Unchanged));
+        // key2 is dirty, so it is rebuilt despite the equal args
+        assert!(matches!(results[1].value, ReinitOutcome::Replaced(_)));
+        assert!(matches!(results[2].value, ReinitOutcome::Inserted));
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
 }
@@ -1,46 +1,122 @@
-use crate::{ComponentMap, Keyed, WithArgs};
+use crate::{
+    ChangeKind, ComponentMap, Entries, Keyed, KeyedStorage, Lookup, NoTeardown, Teardown, WithArgs,
+    priority::prioritized,
+};
+use core::{borrow::Borrow, convert::Infallible};
 
 impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
     pub fn init(entries: impl IntoIterator<Item = (Key, Args)>, init: FnInit) -> Self
     where
-        Key: Eq + std::hash::Hash,
+        Key: Eq + core::hash::Hash,
         FnInit: Fn(&Key, &Args) -> Comp,
     {
         let map = entries
             .into_iter()
             .map(|(key, args)| {
                 let component = (init)(&key, &args);
-                (key, WithArgs { component, args })
+                (key, WithArgs::new(component, args))
             })
             .collect();
 
-        Self { map: map, init }
+        Self::new(map, init, NoTeardown)
     }
 
+    /// Creates an empty map with room for `capacity` entries.
+    pub fn with_capacity(init: FnInit, capacity: usize) -> Self {
+        Self::new(Entries::with_capacity(capacity), init, NoTeardown)
+    }
+
+    /// Like [`init`](Self::init), but allocates room for `capacity` entries up
+    /// front, so large maps are not rehashed as they grow.
+    pub fn init_with_capacity(
+        entries: impl IntoIterator<Item = (Key, Args)>,
+        init: FnInit,
+        capacity: usize,
+    ) -> Self
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let mut map = Entries::with_capacity(capacity);
+        for (key, args) in entries {
+            let component = (init)(&key, &args);
+            map.insert(key, WithArgs::new(component, args));
+        }
+
+        Self::new(map, init, NoTeardown)
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Reinitialises every component, from the highest
+    /// [priority](Self::set_priority) to the lowest.
     pub fn reinit_all(&mut self) -> impl Iterator<Item = Keyed<&Key, Comp>>
     where
+        Key: Eq + core::hash::Hash,
         FnInit: Fn(&Key, &Args) -> Comp,
     {
-        self.map.iter_mut().map(|(key, component)| {
-            let next = (self.init)(key, &component.args);
-            let prev = std::mem::replace(&mut component.component, next);
-            Keyed::new(key, prev)
-        })
+        prioritized(self.map.iter_mut(), &self.dependencies, &self.priorities)
+            .into_iter()
+            .map(|(key, component)| {
+                let next = self
+                    .events
+                    .instrument
+                    .build(key, &component.args, || (self.init)(key, &component.args));
+                let mut prev = component.replace_component(next);
+                self.teardown.teardown(key, &mut prev);
+                self.events
+                    .emit(key, ChangeKind::Reinitialized, Some(&*component));
+                Keyed::new(key, prev)
+            })
+    }
+
+    /// Reinitialises only the entries whose args were replaced via `set_args`.
+    pub fn reinit_dirty(&mut self) -> impl Iterator<Item = Keyed<&Key, Comp>>
+    where
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        self.map
+            .iter_mut()
+            .filter(|(_, component)| component.is_dirty())
+            .map(|(key, component)| {
+                let next = self
+                    .events
+                    .instrument
+                    .build(key, &component.args, || (self.init)(key, &component.args));
+                let mut prev = component.replace_component(next);
+                self.teardown.teardown(key, &mut prev);
+                self.events
+                    .emit(key, ChangeKind::Reinitialized, Some(&*component));
+                Keyed::new(key, prev)
+            })
     }
 
-    pub fn reinit(
+    /// Rebuilds the components of `keys` from their current args, yielding
+    /// the previous component, or `None` for keys not in the map.
+    ///
+    /// Always runs, even within the configured `min_reinit_interval`, though
+    /// it counts as an attempt; use [`try_reinit`](Self::try_reinit) to have
+    /// keys reinitialised too soon reported as throttled instead.
+    pub fn reinit<'q, Q>(
         &mut self,
-        keys: impl IntoIterator<Item = Key>,
-    ) -> impl Iterator<Item = Keyed<Key, Option<Comp>>>
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> impl Iterator<Item = Keyed<&'q Q, Option<Comp>>>
     where
-        Key: Eq + std::hash::Hash,
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
         FnInit: Fn(&Key, &Args) -> Comp,
     {
         keys.into_iter().map(|key| {
-            let prev = self.map.get_mut(&key).map(|component| {
-                let next = (self.init)(&key, &component.args);
-                std::mem::replace(&mut component.component, next)
-            });
+            let prev = self
+                .reinit_entry(key, |init, key, entry| {
+                    Ok::<_, Infallible>(init(key, &entry.args))
+                })
+                .map(|Ok(prev)| prev);
 
             Keyed::new(key, prev)
         })
@@ -51,24 +127,104 @@ impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
         updates: impl IntoIterator<Item = (Key, Args)>,
     ) -> impl Iterator<Item = Keyed<Key, Option<WithArgs<Args, Comp>>>>
     where
-        Key: Clone + Eq + std::hash::Hash,
+        Key: Clone + Eq + core::hash::Hash,
         FnInit: Fn(&Key, &Args) -> Comp,
     {
         updates.into_iter().map(move |(key, args)| {
-            let prev = self.map.insert(
-                key.clone(),
-                WithArgs {
-                    component: (self.init)(&key, &args),
-                    args,
-                },
-            );
+            let component = self
+                .events
+                .instrument
+                .build(&key, &args, || (self.init)(&key, &args));
+            let prev = self
+                .map
+                .insert(key.clone(), WithArgs::new(component, args))
+                .map(|mut prev| {
+                    self.teardown.teardown(&key, &mut prev.component);
+                    prev
+                });
+            self.events
+                .emit(&key, ChangeKind::upsert(prev.is_some()), self.map.get(&key));
+            self.enforce_capacity();
+
+            Keyed::new(key, prev)
+        })
+    }
+
+    /// Like [`reinit`](Self::reinit), but builds each replacement with `rebuild`,
+    /// which also sees the component being replaced.
+    pub fn reinit_from_prev<'q, Q>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'q Q>,
+        rebuild: impl Fn(&Key, &Args, Option<&Comp>) -> Comp,
+    ) -> impl Iterator<Item = Keyed<&'q Q, Option<Comp>>>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized + 'q,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        keys.into_iter().map(move |key| {
+            let prev = self
+                .reinit_entry(key, |_, key, entry| {
+                    Ok::<_, Infallible>(rebuild(key, &entry.args, Some(&entry.component)))
+                })
+                .map(|Ok(prev)| prev);
+
+            Keyed::new(key, prev)
+        })
+    }
+
+    /// Like [`update`](Self::update), but builds each component with `rebuild`,
+    /// which also sees the component being replaced, if any.
+    pub fn update_from_prev(
+        &mut self,
+        updates: impl IntoIterator<Item = (Key, Args)>,
+        rebuild: impl Fn(&Key, &Args, Option<&Comp>) -> Comp,
+    ) -> impl Iterator<Item = Keyed<Key, Option<WithArgs<Args, Comp>>>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+    {
+        updates.into_iter().map(move |(key, args)| {
+            let current = self.map.get(&key).map(|current| &current.component);
+            let component = self
+                .events
+                .instrument
+                .build(&key, &args, || rebuild(&key, &args, current));
+            let prev = self
+                .map
+                .insert(key.clone(), WithArgs::new(component, args))
+                .map(|mut prev| {
+                    self.teardown.teardown(&key, &mut prev.component);
+                    prev
+                });
+            self.events
+                .emit(&key, ChangeKind::upsert(prev.is_some()), self.map.get(&key));
+            self.enforce_capacity();
 
             Keyed::new(key, prev)
         })
     }
+
+    pub fn get_or_init(&mut self, key: Key, args: Args) -> &mut Comp
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        self.reserve_slot(&key);
+        if self.map.contains_key(&key) {
+            let entry = self.map.get_mut(&key).expect("checked above");
+            entry.touch();
+            return &mut entry.component;
+        }
+        let component = self
+            .events
+            .instrument
+            .build(&key, &args, || (self.init)(&key, &args));
+        let (_, entry) = self.insert_entry(key, WithArgs::new(component, args));
+        &mut entry.component
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use std::sync::{Arc, Mutex};
@@ -234,6 +390,38 @@ mod tests {
         assert!(results[0].value.is_none() || results[1].value.is_none());
     }
 
+    #[test]
+    fn test_reinit_borrowed_keys() {
+        let init = |key: &String, args: &Args| Counter(key.len() + args.value);
+
+        let mut manager = ComponentMap::init([("key1".to_string(), Args { value: 1 })], init);
+        manager.map.get_mut("key1").unwrap().args.value = 10;
+
+        let results: Vec<_> = manager.reinit(["key1", "nonexistent"]).collect();
+
+        assert_eq!(results[0].key, "key1");
+        assert_eq!(results[0].value, Some(Counter(5)));
+        assert_eq!(results[1].value, None);
+        assert_eq!(manager.map.get("key1").unwrap().component, Counter(14));
+    }
+
+    #[test]
+    fn test_panicking_reinit_keeps_entry() {
+        let init = |_key: &&str, args: &Args| {
+            assert!(args.value > 0, "cannot build from zero");
+            Counter(args.value)
+        };
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+        manager.set_args(&"key1", Args { value: 0 });
+
+        let reinit = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            manager.reinit(["key1"]).for_each(drop);
+        }));
+
+        assert!(reinit.is_err());
+        assert_eq!(manager.get("key1"), Some(&Counter(1)));
+    }
+
     #[test]
     fn test_update_existing_key() {
         let init = |_key: &&str, args: &Args| Counter(args.value);
@@ -314,4 +502,164 @@ mod tests {
         let result = (fn_init)(&"test", &Args { value: 10 });
         assert_eq!(result, Counter(50));
     }
+
+    #[test]
+    fn test_get_or_init() {
+        let call_count = Arc::new(Mutex::new(0));
+        let call_count_clone = call_count.clone();
+
+        let init = move |_key: &&str, args: &Args| {
+            *call_count_clone.lock().unwrap() += 1;
+            Counter(args.value)
+        };
+
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        // Existing key returns the stored component and ignores the provided args
+        assert_eq!(
+            *manager.get_or_init("key1", Args { value: 100 }),
+            Counter(1)
+        );
+        assert_eq!(manager.map.get("key1").unwrap().args.value, 1);
+
+        // Missing key is initialised and inserted
+        assert_eq!(*manager.get_or_init("key2", Args { value: 2 }), Counter(2));
+        assert_eq!(manager.map.len(), 2);
+        assert_eq!(manager.map.get("key2").unwrap().args.value, 2);
+
+        assert_eq!(*call_count.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_reinit_dirty() {
+        let call_count = Arc::new(Mutex::new(0));
+        let call_count_clone = call_count.clone();
+
+        let init = move |_key: &&str, args: &Args| {
+            *call_count_clone.lock().unwrap() += 1;
+            Counter(args.value)
+        };
+
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        );
+
+        manager.set_args(&"key2", Args { value: 20 });
+
+        let results: Vec<_> = manager.reinit_dirty().collect();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(*results[0].key, "key2");
+        assert_eq!(results[0].value, Counter(2));
+
+        assert_eq!(manager.map.get("key1").unwrap().component, Counter(1));
+        assert_eq!(manager.map.get("key2").unwrap().component, Counter(20));
+        assert_eq!(manager.is_dirty(&"key2"), Some(false));
+        assert_eq!(*call_count.lock().unwrap(), 3);
+
+        // Nothing left to rebuild
+        assert_eq!(manager.reinit_dirty().count(), 0);
+    }
+
+    #[test]
+    fn test_with_init_then_reinit_all() {
+        let staging = |_key: &&str, args: &Args| Counter(args.value);
+        let production = |_key: &&str, args: &Args| Counter(args.value * 1000);
+
+        let manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            staging,
+        );
+
+        let mut manager = manager.with_init(production);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+
+        let mut results: Vec<_> = manager
+            .reinit_all()
+            .map(|Keyed { key, value }| (*key, value))
+            .collect();
+        results.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(results, vec![("key1", Counter(1)), ("key2", Counter(2))]);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1000)));
+        assert_eq!(manager.get(&"key2"), Some(&Counter(2000)));
+    }
+
+    #[test]
+    fn test_with_hasher_keeps_entries() {
+        type Deterministic = std::hash::BuildHasherDefault<std::hash::DefaultHasher>;
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_hasher(Deterministic::default());
+        manager.update([("key3", Args { value: 3 })]).for_each(drop);
+
+        assert_eq!(manager.len(), 3);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(1)));
+        assert_eq!(manager.reinit_all().count(), 3);
+
+        let child = manager.child();
+        assert_eq!(child.get(&"key3"), Some(&Counter(3)));
+    }
+
+    #[test]
+    fn test_init_receives_key() {
+        let init = |key: &&str, args: &Args| format!("{key}:{}", args.value);
+
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+        assert_eq!(manager.get(&"key1"), Some(&"key1:1".to_string()));
+
+        let _: Vec<_> = manager.update([("key2", Args { value: 2 })]).collect();
+        assert_eq!(manager.get(&"key2"), Some(&"key2:2".to_string()));
+
+        manager.set_args(&"key1", Args { value: 10 });
+        let _: Vec<_> = manager.reinit(["key1"]).collect();
+        assert_eq!(manager.get(&"key1"), Some(&"key1:10".to_string()));
+    }
+
+    #[test]
+    fn test_reinit_and_update_from_prev() {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Socket {
+            subscription: usize,
+            buffer: Vec<usize>,
+        }
+
+        let init = |_key: &&str, args: &Args| Socket {
+            subscription: args.value,
+            buffer: Vec::new(),
+        };
+        // Resubscribes while carrying over the buffered messages
+        let rebuild = |_key: &&str, args: &Args, prev: Option<&Socket>| Socket {
+            subscription: args.value,
+            buffer: prev.map(|prev| prev.buffer.clone()).unwrap_or_default(),
+        };
+
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+        manager.get_mut(&"key1").unwrap().buffer.push(42);
+
+        manager.set_args(&"key1", Args { value: 2 });
+        let results: Vec<_> = manager.reinit_from_prev(["key1"], rebuild).collect();
+        assert_eq!(results[0].value.as_ref().unwrap().subscription, 1);
+        assert_eq!(
+            manager.get(&"key1"),
+            Some(&Socket {
+                subscription: 2,
+                buffer: vec![42]
+            })
+        );
+
+        let _: Vec<_> = manager
+            .update_from_prev(
+                [("key1", Args { value: 3 }), ("key2", Args { value: 4 })],
+                rebuild,
+            )
+            .collect();
+        assert_eq!(manager.get(&"key1").unwrap().buffer, vec![42]);
+        assert_eq!(manager.get(&"key2").unwrap().buffer, Vec::<usize>::new());
+    }
 }
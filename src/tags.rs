@@ -0,0 +1,168 @@
+#[cfg(feature = "std")]
+use crate::AsyncTeardown;
+use crate::{
+    ComponentMap, Keyed, KeyedStorage, Lookup, Teardown, WithArgs,
+    storage::{HashMap, HashSet},
+};
+use alloc::{string::String, vec::Vec};
+use core::borrow::Borrow;
+
+// Groups are kept apart from the entries, so a key stays in its groups when it
+// is updated with new args or paused; removing it drops it from every group.
+
+/// Keys of each tag, keyed by tag.
+pub(crate) type Groups<Key> = HashMap<String, HashSet<Key>>;
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Adds `key` to the group `tag`; returns `false` if the key is missing.
+    pub fn add_tag<Q>(&mut self, key: &Q, tag: impl Into<String>) -> bool
+    where
+        Key: Clone + Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        let Some((key, _)) = self.map.get_key_value(key) else {
+            return false;
+        };
+        self.groups
+            .entry(tag.into())
+            .or_default()
+            .insert(key.clone());
+        true
+    }
+
+    /// Removes `key` from the group `tag`; returns `false` if it was not in it.
+    pub fn remove_tag<Q>(&mut self, key: &Q, tag: &str) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        let Some(keys) = self.groups.get_mut(tag) else {
+            return false;
+        };
+        let removed = keys.remove(key);
+        if keys.is_empty() {
+            self.groups.remove(tag);
+        }
+        removed
+    }
+
+    pub fn tags<'a, Q>(&'a self, key: &'a Q) -> impl Iterator<Item = &'a str>
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+    {
+        self.groups
+            .iter()
+            .filter(move |(_, keys)| keys.contains(key))
+            .map(|(tag, _)| tag.as_str())
+    }
+
+    /// Iterates over the entries tagged `tag`.
+    pub fn iter_group(&self, tag: &str) -> impl Iterator<Item = (&Key, &Comp)>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.groups
+            .get(tag)
+            .into_iter()
+            .flatten()
+            .filter_map(|key| self.map.get_key_value(key))
+            .map(|(key, entry)| (key, &entry.component))
+    }
+
+    /// Reinitialises every entry tagged `tag`, returning the previous components.
+    pub fn reinit_group(&mut self, tag: &str) -> Vec<Keyed<Key, Comp>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        let keys = self.group_keys(tag);
+        self.reinit(&keys)
+            .filter_map(|Keyed { key, value }| value.map(|prev| Keyed::new(key.clone(), prev)))
+            .collect()
+    }
+
+    /// Async counterpart of [`reinit_group`](Self::reinit_group).
+    #[cfg(feature = "std")]
+    pub async fn reinit_group_async(&mut self, tag: &str) -> Vec<Keyed<Key, Comp>>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        let keys = self.group_keys(tag);
+        self.reinit_async(&keys)
+            .await
+            .filter_map(|Keyed { key, value }| value.map(|prev| Keyed::new(key.clone(), prev)))
+            .collect()
+    }
+
+    /// Removes every entry tagged `tag` through the teardown, and the group
+    /// itself.
+    pub fn remove_group(&mut self, tag: &str) -> Vec<Keyed<Key, WithArgs<Args, Comp>>>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        self.groups
+            .remove(tag)
+            .into_iter()
+            .flatten()
+            .filter_map(|key| self.remove_entry(&key))
+            .map(|(key, entry)| Keyed::new(key, entry))
+            .collect()
+    }
+
+    fn group_keys(&self, tag: &str) -> Vec<Key>
+    where
+        Key: Clone + Eq + core::hash::Hash,
+    {
+        self.iter_group(tag).map(|(key, _)| key.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_group_operations() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("eu1", 1), ("eu2", 2), ("us1", 3)], init);
+        assert!(manager.add_tag(&"eu1", "eu"));
+        assert!(manager.add_tag(&"eu2", "eu"));
+        assert!(manager.add_tag(&"us1", "us"));
+        assert!(manager.add_tag(&"eu1", "primary"));
+        assert!(!manager.add_tag(&"missing", "eu"));
+
+        let mut tags: Vec<_> = manager.tags(&"eu1").collect();
+        tags.sort();
+        assert_eq!(tags, vec!["eu", "primary"]);
+
+        let mut group: Vec<_> = manager.iter_group("eu").map(|(key, _)| *key).collect();
+        group.sort();
+        assert_eq!(group, vec!["eu1", "eu2"]);
+
+        manager.set_args(&"eu2", 20);
+        let prev = manager.reinit_group("eu");
+        assert_eq!(prev.len(), 2);
+        assert_eq!(manager.get(&"eu2"), Some(&Counter(20)));
+
+        assert!(manager.remove_tag(&"eu1", "primary"));
+        assert_eq!(manager.iter_group("primary").count(), 0);
+
+        let removed = manager.remove_group("eu");
+        assert_eq!(removed.len(), 2);
+        assert_eq!(manager.len(), 1);
+        assert_eq!(manager.iter_group("eu").count(), 0);
+        assert_eq!(manager.iter_group("us").count(), 1);
+    }
+}
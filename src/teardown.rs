@@ -0,0 +1,323 @@
+#[cfg(feature = "std")]
+use crate::{ComponentMap, Keyed, KeyedStorage, WithArgs, timeout::deadline};
+use core::future::Future;
+use futures::future::join_all;
+#[cfg(feature = "std")]
+use std::{time::Duration, vec::Vec};
+
+/// Finalizer run for every component the map removes, replaces, or drops.
+///
+/// The component is passed by mutable reference so that operations returning
+/// the previous component (e.g. `update`, `remove`) still hand it back to the
+/// caller after it has been finalized.
+pub trait Teardown<Key, Comp> {
+    fn teardown(&self, key: &Key, component: &mut Comp);
+}
+
+/// Async counterpart of [`Teardown`], awaited by the `*_async` operations.
+pub trait AsyncTeardown<Key, Comp> {
+    fn teardown_async(&self, key: &Key, component: &mut Comp) -> impl Future<Output = ()>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoTeardown;
+
+impl<Key, Comp> Teardown<Key, Comp> for NoTeardown {
+    fn teardown(&self, _: &Key, _: &mut Comp) {}
+}
+
+impl<Key, Comp> AsyncTeardown<Key, Comp> for NoTeardown {
+    async fn teardown_async(&self, _: &Key, _: &mut Comp) {}
+}
+
+impl<Key, Comp, F> Teardown<Key, Comp> for F
+where
+    F: Fn(&Key, &mut Comp),
+{
+    fn teardown(&self, key: &Key, component: &mut Comp) {
+        (self)(key, component)
+    }
+}
+
+impl<Key, Comp, F> AsyncTeardown<Key, Comp> for F
+where
+    F: Fn(&Key, &mut Comp),
+{
+    async fn teardown_async(&self, key: &Key, component: &mut Comp) {
+        (self)(key, component)
+    }
+}
+
+/// Wraps an `AsyncFn(&Key, &mut Comp)` finalizer.
+///
+/// Sync paths, including `Drop`, cannot await the finalizer, so components
+/// discarded outside of the `*_async` operations are dropped without it.
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncTeardownFn<F>(pub F);
+
+impl<Key, Comp, F> Teardown<Key, Comp> for AsyncTeardownFn<F> {
+    fn teardown(&self, _: &Key, _: &mut Comp) {}
+}
+
+impl<Key, Comp, F> AsyncTeardown<Key, Comp> for AsyncTeardownFn<F>
+where
+    F: AsyncFn(&Key, &mut Comp),
+{
+    async fn teardown_async(&self, key: &Key, component: &mut Comp) {
+        (self.0)(key, component).await
+    }
+}
+
+/// Per-key result of [`ComponentMap::shutdown_async`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownOutcome {
+    Completed,
+    /// The teardown did not finish within the timeout and was abandoned.
+    TimedOut,
+}
+
+#[cfg(feature = "std")]
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Consumes the map, running the async teardown for every component
+    /// concurrently, each bounded by `timeout` or else the configured
+    /// `shutdown_timeout`.
+    pub async fn shutdown_async(self, timeout: Option<Duration>) -> Vec<Keyed<Key, ShutdownOutcome>>
+    where
+        FnDrop: AsyncTeardown<Key, Comp>,
+        Store: Default,
+    {
+        let timeout = timeout.or(self.config.shutdown_timeout);
+        let (map, _, teardown) = self.into_raw_parts();
+        let mut entries: Vec<_> = map.into_iter().collect();
+
+        let outcomes = join_all(entries.iter_mut().map(|(key, component)| {
+            let teardown = teardown.teardown_async(key, &mut component.component);
+            async move {
+                match timeout {
+                    Some(timeout) => match deadline(teardown, timeout).await {
+                        Some(()) => ShutdownOutcome::Completed,
+                        None => ShutdownOutcome::TimedOut,
+                    },
+                    None => {
+                        teardown.await;
+                        ShutdownOutcome::Completed
+                    }
+                }
+            }
+        }))
+        .await;
+
+        entries
+            .into_iter()
+            .zip(outcomes)
+            .map(|((key, _), outcome)| Keyed::new(key, outcome))
+            .collect()
+    }
+}
+
+pub(crate) async fn teardown_all<'a, Key, Comp, FnDrop>(
+    teardown: &FnDrop,
+    components: impl IntoIterator<Item = (&'a Key, &'a mut Comp)>,
+) where
+    Key: 'a,
+    Comp: 'a,
+    FnDrop: AsyncTeardown<Key, Comp>,
+{
+    join_all(
+        components
+            .into_iter()
+            .map(|(key, component)| teardown.teardown_async(key, component)),
+    )
+    .await;
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Args {
+        value: usize,
+    }
+
+    #[test]
+    fn test_teardown_on_update_reinit_and_remove() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_teardown(move |key: &&str, component: &mut Counter| {
+            closed_clone.lock().unwrap().push((*key, component.0));
+        });
+
+        let _: Vec<_> = manager.update([("key1", Args { value: 10 })]).collect();
+        assert_eq!(*closed.lock().unwrap(), vec![("key1", 1)]);
+
+        let _: Vec<_> = manager.reinit(["key2"]).collect();
+        assert_eq!(*closed.lock().unwrap(), vec![("key1", 1), ("key2", 2)]);
+
+        let removed = manager.remove(&"key1").unwrap();
+        assert_eq!(removed.component, Counter(10));
+        assert_eq!(
+            *closed.lock().unwrap(),
+            vec![("key1", 1), ("key2", 2), ("key1", 10)]
+        );
+    }
+
+    #[test]
+    fn test_teardown_on_drop() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_teardown(move |key: &&str, _: &mut Counter| {
+            closed_clone.lock().unwrap().push(*key);
+        });
+
+        drop(manager);
+
+        let mut closed = closed.lock().unwrap().clone();
+        closed.sort();
+        assert_eq!(closed, vec!["key1", "key2"]);
+    }
+
+    #[test]
+    fn test_teardown_runs_once_after_rebuilding_builders() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init([("key1", Args { value: 1 })], init)
+            .with_teardown(move |key: &&str, _: &mut Counter| {
+                closed_clone.lock().unwrap().push(*key);
+            })
+            .with_init(|_key: &&str, args: &Args| Counter(args.value * 10))
+            .with_hasher(std::hash::RandomState::new());
+        assert!(closed.lock().unwrap().is_empty());
+
+        drop(manager);
+        assert_eq!(*closed.lock().unwrap(), vec!["key1"]);
+    }
+
+    #[test]
+    fn test_teardown_not_run_for_new_keys() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let mut manager = ComponentMap::init([("key1", Args { value: 1 })], init).with_teardown(
+            move |_: &&str, _: &mut Counter| {
+                *closed_clone.lock().unwrap() += 1;
+            },
+        );
+
+        let _: Vec<_> = manager.update([("key2", Args { value: 2 })]).collect();
+        assert!(manager.remove(&"nonexistent").is_none());
+
+        assert_eq!(*closed.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_async_teardown_on_update_async() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| {
+            let value = args.value;
+            async move { Counter(value) }
+        };
+        let mut manager = ComponentMap::init_async([("key1", Args { value: 1 })], init)
+            .await
+            .with_async_teardown(async move |key: &&str, component: &mut Counter| {
+                closed_clone.lock().unwrap().push((*key, component.0));
+            });
+
+        let results: Vec<_> = manager
+            .update_async([("key1", Args { value: 10 }), ("key2", Args { value: 20 })])
+            .await
+            .collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(*closed.lock().unwrap(), vec![("key1", 1)]);
+
+        manager.remove_async(&"key2").await;
+        assert_eq!(*closed.lock().unwrap(), vec![("key1", 1), ("key2", 20)]);
+    }
+
+    #[tokio::test]
+    async fn test_async_teardown_skipped_on_drop() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| {
+            let value = args.value;
+            async move { Counter(value) }
+        };
+        let manager = ComponentMap::init_async([("key1", Args { value: 1 })], init)
+            .await
+            .with_async_teardown(async move |_: &&str, _: &mut Counter| {
+                *closed_clone.lock().unwrap() += 1;
+            });
+
+        drop(manager);
+
+        assert_eq!(*closed.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_async() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| {
+            let value = args.value;
+            async move { Counter(value) }
+        };
+        let manager = ComponentMap::init_async(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 0 })],
+            init,
+        )
+        .await
+        .with_async_teardown(async move |key: &&str, component: &mut Counter| {
+            // Components with a value of 0 never finish closing
+            if component.0 == 0 {
+                futures::future::pending::<()>().await;
+            }
+            closed_clone.lock().unwrap().push(*key);
+        });
+
+        let mut report: Vec<_> = manager
+            .shutdown_async(Some(Duration::from_millis(10)))
+            .await
+            .into_iter()
+            .map(|Keyed { key, value }| (key, value))
+            .collect();
+        report.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(
+            report,
+            vec![
+                ("key1", ShutdownOutcome::Completed),
+                ("key2", ShutdownOutcome::TimedOut)
+            ]
+        );
+        assert_eq!(*closed.lock().unwrap(), vec!["key1"]);
+    }
+}
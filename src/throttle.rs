@@ -0,0 +1,116 @@
+use crate::{ComponentMap, KeyedStorage, Lookup, Teardown, WithArgs};
+use core::borrow::Borrow;
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Whether `key` was last reinitialised, or last failed to, within
+    /// [`ComponentMapConfig::min_reinit_interval`](crate::ComponentMapConfig::min_reinit_interval).
+    ///
+    /// Only checked by the reinits that yield a
+    /// [`ReinitOutcome`](crate::ReinitOutcome) to report it in; `reinit`,
+    /// `reinit_async`, and the batch reinits have no way to, so they always
+    /// run.
+    pub(crate) fn is_throttled<Q>(&self, key: &Q) -> bool
+    where
+        Key: Eq + core::hash::Hash + Borrow<Q>,
+        Q: Eq + core::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        self.config
+            .min_reinit_interval
+            .zip(self.map.get(key).and_then(|entry| entry.attempted_at))
+            .is_some_and(|(interval, attempted_at)| attempted_at.elapsed() < interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComponentMap, ComponentMapConfig, ReinitOutcome, SharedComponentMap};
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    fn throttled() -> ComponentMapConfig {
+        ComponentMapConfig::default().min_reinit_interval(Duration::from_secs(3600))
+    }
+
+    #[test]
+    fn test_reinit_within_interval_is_throttled() {
+        let init = |_key: &&str, args: &usize| match args {
+            0 => Err(TestError),
+            _ => Ok(Counter(*args)),
+        };
+        let mut manager = ComponentMap::try_init([("key1", 1)], init)
+            .unwrap()
+            .with_config(throttled());
+        // The initial build is not a reinit, so the first reinit goes through
+        assert!(matches!(
+            manager.try_reinit([&"key1"]).next().unwrap().value,
+            ReinitOutcome::Replaced(_)
+        ));
+        let outcomes: Vec<_> = manager
+            .try_reinit([&"key1", &"missing"])
+            .map(|outcome| outcome.value)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![ReinitOutcome::Throttled, ReinitOutcome::Missing]
+        );
+
+        manager.config.min_reinit_interval = Some(Duration::ZERO);
+        assert!(matches!(
+            manager.try_reinit([&"key1"]).next().unwrap().value,
+            ReinitOutcome::Replaced(_)
+        ));
+    }
+
+    #[test]
+    fn test_explicit_reinit_runs_within_interval_and_counts_as_attempt() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1)], init).with_config(throttled());
+
+        manager.reinit([&"key1"]).for_each(drop);
+        assert!(manager.is_throttled(&"key1"));
+        let prev = manager.reinit([&"key1"]).next().unwrap().value;
+        assert_eq!(prev, Some(Counter(1)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempts_count_towards_interval() {
+        let init = |_key: &&str, args: &usize| {
+            let args = *args;
+            async move {
+                match args {
+                    0 => Err(TestError),
+                    _ => Ok(Counter(args)),
+                }
+            }
+        };
+        let mut manager = ComponentMap::try_init_async([("key1", 1)], init)
+            .await
+            .unwrap();
+        manager.set_args(&"key1", 0);
+        assert!(
+            manager
+                .try_reinit_async([&"key1"])
+                .await
+                .next()
+                .unwrap()
+                .value
+                .is_failed()
+        );
+
+        let shared = SharedComponentMap::from(manager.with_config(throttled()));
+        assert_eq!(
+            shared.try_reinit_async(&"key1").await,
+            ReinitOutcome::Throttled
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use futures::future::{Either, select};
+use futures_timer::Delay;
+use std::{fmt, future::Future, pin::pin, time::Duration};
+
+/// Error of an init wrapped by [`with_timeout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeoutError<Error> {
+    /// The init did not complete within the deadline.
+    Timeout(Duration),
+    Init(Error),
+}
+
+impl<Error> fmt::Display for TimeoutError<Error>
+where
+    Error: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutError::Timeout(timeout) => write!(f, "init timed out after {timeout:?}"),
+            TimeoutError::Init(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<Error> std::error::Error for TimeoutError<Error>
+where
+    Error: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TimeoutError::Timeout(_) => None,
+            TimeoutError::Init(error) => Some(error),
+        }
+    }
+}
+
+/// Bounds every call of the async fallible `init` by `timeout`.
+///
+/// An init exceeding the deadline is dropped and yields
+/// [`TimeoutError::Timeout`] for its key, so one hung init does not stall the
+/// rest of a batch.
+pub fn with_timeout<Key, Args, Comp, Error>(
+    init: impl AsyncFn(&Key, &Args) -> Result<Comp, Error> + Clone,
+    timeout: Duration,
+) -> impl AsyncFn(&Key, &Args) -> Result<Comp, TimeoutError<Error>> + Clone {
+    async move |key: &Key, args: &Args| match deadline((init)(key, args), timeout).await {
+        Some(result) => result.map_err(TimeoutError::Init),
+        None => Err(TimeoutError::Timeout(timeout)),
+    }
+}
+
+/// Awaits `future`, dropping it and returning `None` if `timeout` elapses first.
+pub(crate) async fn deadline<F>(future: F, timeout: Duration) -> Option<F::Output>
+where
+    F: Future,
+{
+    match select(pin!(future), Delay::new(timeout)).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ComponentMap, ReinitOutcome};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestError;
+
+    #[tokio::test]
+    async fn test_with_timeout() {
+        // Args of 0 hang forever
+        let init = |_key: &&str, args: &usize| {
+            let value = *args;
+            async move {
+                if value == 0 {
+                    futures::future::pending::<()>().await;
+                }
+                Ok::<_, TestError>(Counter(value))
+            }
+        };
+        let timeout = Duration::from_millis(10);
+
+        let mut manager = ComponentMap::try_init_async([("key1", 1)], with_timeout(init, timeout))
+            .await
+            .unwrap();
+
+        let results: Vec<_> = manager
+            .try_update_async([("key1", 10), ("key2", 0)])
+            .await
+            .collect();
+
+        assert!(matches!(results[0].value, ReinitOutcome::Replaced(_)));
+        assert!(matches!(
+            results[1].value,
+            ReinitOutcome::Failed(TimeoutError::Timeout(_))
+        ));
+        assert_eq!(manager.get(&"key1"), Some(&Counter(10)));
+        assert!(!manager.contains_key(&"key2"));
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout_propagates_init_error() {
+        let init = |_key: &&str, _args: &usize| async { Err::<Counter, _>(TestError) };
+
+        let result =
+            ComponentMap::try_init_async([("key1", 1)], with_timeout(init, Duration::from_secs(1)))
+                .await;
+
+        assert_eq!(result.err().unwrap().error, TimeoutError::Init(TestError));
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// Samples kept per key; older ones are dropped so long-running services
+/// summarise recent behaviour in bounded memory.
+const SAMPLES_PER_KEY: usize = 1024;
+
+/// Summary of init durations from [`InitTimings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationSummary {
+    pub count: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl DurationSummary {
+    fn of(mut samples: Vec<Duration>) -> Option<Self> {
+        samples.sort_unstable();
+        let count = samples.len();
+        // Nearest-rank percentile
+        let p95 = *samples.get((count * 95).div_ceil(100).checked_sub(1)?)?;
+        let total: Duration = samples.iter().sum();
+        Some(Self {
+            count,
+            min: samples[0],
+            mean: total / u32::try_from(count).unwrap_or(u32::MAX),
+            p95,
+            max: samples[count - 1],
+        })
+    }
+}
+
+/// Cloneable handle to the init durations recorded by [`with_timing`] or
+/// [`with_timing_async`], keeping the most recent samples of each key.
+pub struct InitTimings<Key> {
+    samples: Arc<Mutex<HashMap<Key, VecDeque<Duration>>>>,
+}
+
+impl<Key> Clone for InitTimings<Key> {
+    fn clone(&self) -> Self {
+        Self {
+            samples: self.samples.clone(),
+        }
+    }
+}
+
+impl<Key> Default for InitTimings<Key> {
+    fn default() -> Self {
+        Self {
+            samples: Arc::default(),
+        }
+    }
+}
+
+impl<Key> fmt::Debug for InitTimings<Key> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InitTimings")
+            .field("keys", &self.lock().len())
+            .finish()
+    }
+}
+
+impl<Key> InitTimings<Key> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Summary of the recorded inits of `key`, or `None` if it has none.
+    pub fn summary<Q>(&self, key: &Q) -> Option<DurationSummary>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+    {
+        let samples = self.lock();
+        DurationSummary::of(samples.get(key)?.iter().copied().collect())
+    }
+
+    /// Summary of the recorded inits of every key, or `None` if there are none.
+    pub fn summary_all(&self) -> Option<DurationSummary> {
+        let samples = self.lock();
+        DurationSummary::of(samples.values().flatten().copied().collect())
+    }
+
+    /// Summary of each key with recorded inits.
+    pub fn summaries(&self) -> Vec<(Key, DurationSummary)>
+    where
+        Key: Clone,
+    {
+        self.lock()
+            .iter()
+            .filter_map(|(key, samples)| {
+                let summary = DurationSummary::of(samples.iter().copied().collect())?;
+                Some((key.clone(), summary))
+            })
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn record(&self, key: &Key, elapsed: Duration)
+    where
+        Key: Clone + Eq + std::hash::Hash,
+    {
+        let mut samples = self.lock();
+        let samples = samples.entry(key.clone()).or_default();
+        if samples.len() == SAMPLES_PER_KEY {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Key, VecDeque<Duration>>> {
+        self.samples.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Records the wall-clock duration of every call of `init` into `timings`,
+/// failed calls included.
+pub fn with_timing<Key, Args, Output>(
+    init: impl Fn(&Key, &Args) -> Output + Clone,
+    timings: &InitTimings<Key>,
+) -> impl Fn(&Key, &Args) -> Output + Clone
+where
+    Key: Clone + Eq + std::hash::Hash,
+{
+    let timings = timings.clone();
+    move |key: &Key, args: &Args| {
+        let started = Instant::now();
+        let output = (init)(key, args);
+        timings.record(key, started.elapsed());
+        output
+    }
+}
+
+/// Like [`with_timing`] for async inits, timing each call until its future
+/// completes.
+pub fn with_timing_async<Key, Args, Output>(
+    init: impl AsyncFn(&Key, &Args) -> Output + Clone,
+    timings: &InitTimings<Key>,
+) -> impl AsyncFn(&Key, &Args) -> Output + Clone
+where
+    Key: Clone + Eq + std::hash::Hash,
+{
+    let timings = timings.clone();
+    async move |key: &Key, args: &Args| {
+        let started = Instant::now();
+        let output = (init)(key, args).await;
+        timings.record(key, started.elapsed());
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ComponentMap;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_summary_percentiles() {
+        let samples = (1..=100).map(Duration::from_millis).collect();
+        let summary = DurationSummary::of(samples).unwrap();
+
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.min, Duration::from_millis(1));
+        assert_eq!(summary.mean, Duration::from_micros(50_500));
+        assert_eq!(summary.p95, Duration::from_millis(95));
+        assert_eq!(summary.max, Duration::from_millis(100));
+        assert_eq!(DurationSummary::of(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_with_timing_records_each_init() {
+        let timings = InitTimings::new();
+        let init = |_key: &&str, args: &u64| {
+            std::thread::sleep(Duration::from_millis(*args));
+            Counter(*args as usize)
+        };
+        let mut manager =
+            ComponentMap::init([("fast", 0), ("slow", 5)], with_timing(init, &timings));
+        manager.reinit([&"slow"]).for_each(drop);
+
+        let slow = timings.summary(&"slow").unwrap();
+        assert_eq!(slow.count, 2);
+        assert!(slow.min >= Duration::from_millis(5));
+        assert_eq!(timings.summary(&"fast").unwrap().count, 1);
+        assert_eq!(timings.summary_all().unwrap().count, 3);
+        assert_eq!(timings.summaries().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_with_timing_async() {
+        let timings = InitTimings::new();
+        let init = async |_key: &&str, args: &usize| Counter(*args);
+
+        ComponentMap::init_async([("key1", 1)], with_timing_async(init, &timings)).await;
+
+        assert_eq!(timings.summary(&"key1").unwrap().count, 1);
+        assert_eq!(timings.summary(&"missing"), None);
+    }
+}
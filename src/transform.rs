@@ -0,0 +1,319 @@
+use crate::{
+    ChangeKind, ComponentMap, Entries, KeyedStorage, NoTeardown, Teardown, WithArgs,
+    storage::HashMap,
+};
+
+impl<Key, Args, Comp, FnInit> ComponentMap<Key, Args, Comp, FnInit> {
+    pub fn from_parts(map: Entries<Key, WithArgs<Args, Comp>>, init: FnInit) -> Self {
+        Self::new(map, init, NoTeardown)
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>> + Default,
+{
+    /// Decomposes the map without running teardown; the teardown hook is dropped.
+    pub fn into_parts(self) -> (Store, FnInit) {
+        let (map, init, _) = self.into_raw_parts();
+        (map, init)
+    }
+
+    /// Hands off the components without running teardown, dropping args and init.
+    pub fn into_components(self) -> HashMap<Key, Comp>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        let (map, _, _) = self.into_raw_parts();
+        map.into_iter()
+            .map(|(key, entry)| (key, entry.component))
+            .collect()
+    }
+
+    /// Converts every component with `f`, producing a map that uses `init` for
+    /// subsequent reinits. The teardown hook only applies to `Comp`, so it is
+    /// dropped without running.
+    pub fn map_components<Comp2, FnInit2>(
+        self,
+        f: impl Fn(&Key, Comp) -> Comp2,
+        init: FnInit2,
+    ) -> ComponentMap<Key, Args, Comp2, FnInit2>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        let config = self.config;
+        let (map, _, _) = self.into_raw_parts();
+        let map = map
+            .into_iter()
+            .map(|(key, entry)| {
+                let entry = entry.map_parts(|component, args| (f(&key, component), args));
+                (key, entry)
+            })
+            .collect();
+
+        ComponentMap::from_parts(map, init).with_config(config)
+    }
+
+    /// Converts every entry's args with `f`, keeping the existing components.
+    /// Converted entries are marked dirty so `reinit_dirty` can rebuild them.
+    pub fn map_args<Args2, FnInit2>(
+        self,
+        f: impl Fn(&Key, Args) -> Args2,
+        init: FnInit2,
+    ) -> ComponentMap<Key, Args2, Comp, FnInit2, FnDrop>
+    where
+        Key: Eq + core::hash::Hash,
+    {
+        let config = self.config;
+        let (map, _, teardown) = self.into_raw_parts();
+        let map = map
+            .into_iter()
+            .map(|(key, entry)| {
+                let mut entry = entry.map_parts(|component, args| (component, f(&key, args)));
+                entry.dirty = true;
+                (key, entry)
+            })
+            .collect();
+
+        ComponentMap::new(map, init, teardown).with_config(config)
+    }
+
+    /// Like [`map_args`](Self::map_args), but reinitialises every component
+    /// from the converted args, tearing down the previous ones.
+    pub fn map_args_reinit<Args2, FnInit2>(
+        self,
+        f: impl Fn(&Key, Args) -> Args2,
+        init: FnInit2,
+    ) -> ComponentMap<Key, Args2, Comp, FnInit2, FnDrop>
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit2: Fn(&Key, &Args2) -> Comp,
+    {
+        let mut migrated = self.map_args(f, init);
+        migrated.reinit_all().for_each(drop);
+        migrated
+    }
+
+    /// Moves every entry matching `predicate` into a new map sharing a clone of
+    /// the init and teardown functions.
+    pub fn split_off(&mut self, mut predicate: impl FnMut(&Key, &Args) -> bool) -> Self
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Clone,
+        FnDrop: Clone,
+        Store: Default,
+    {
+        let mut map = Store::default();
+        for (key, entry) in self
+            .map
+            .take_if(|key, component| predicate(key, &component.args))
+        {
+            map.insert(key, entry);
+        }
+        for (key, entry) in map.iter() {
+            self.events.emit(key, ChangeKind::Removed, Some(entry));
+        }
+
+        Self::new(map, (*self.init).clone(), (*self.teardown).clone()).with_config(self.config)
+    }
+
+    /// Splits the map into `(matching, rest)` according to `predicate`.
+    pub fn partition(self, predicate: impl FnMut(&Key, &Args) -> bool) -> (Self, Self)
+    where
+        Key: Eq + core::hash::Hash,
+        FnInit: Clone,
+        FnDrop: Clone,
+        Store: Default,
+    {
+        let mut rest = self;
+        let matching = rest.split_off(predicate);
+        (matching, rest)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Args {
+        value: usize,
+    }
+
+    #[test]
+    fn test_from_parts_skips_init() {
+        let init = |_key: &&str, args: &Args| Counter(args.value * 2);
+        let map = Entries::from([("key1", WithArgs::new(Counter(100), Args { value: 1 }))]);
+
+        let mut manager = ComponentMap::from_parts(map, init);
+        assert_eq!(manager.get(&"key1"), Some(&Counter(100)));
+
+        // The provided init function is used for subsequent reinits
+        let _: Vec<_> = manager.reinit(["key1"]).collect();
+        assert_eq!(manager.get(&"key1"), Some(&Counter(2)));
+    }
+
+    #[test]
+    fn test_into_parts_round_trip() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init([("key1", Args { value: 1 })], init).with_teardown(
+            move |_: &&str, _: &mut Counter| {
+                *closed_clone.lock().unwrap() += 1;
+            },
+        );
+
+        let (map, init) = manager.into_parts();
+        assert_eq!(*closed.lock().unwrap(), 0);
+        assert_eq!(map.get("key1").unwrap().component, Counter(1));
+
+        let manager = ComponentMap::from_parts(map, init);
+        assert_eq!(manager.len(), 1);
+        assert_eq!((manager.init)(&"key2", &Args { value: 5 }), Counter(5));
+    }
+
+    #[test]
+    fn test_split_off() {
+        let init = |_key: &&str, args: &Args| Counter(args.value * 2);
+        let mut manager = ComponentMap::init(
+            [
+                ("key1", Args { value: 1 }),
+                ("key2", Args { value: 2 }),
+                ("key3", Args { value: 3 }),
+            ],
+            init,
+        );
+
+        let mut odd = manager.split_off(|_, args| args.value % 2 == 1);
+
+        assert_eq!(manager.len(), 1);
+        assert!(manager.contains_key(&"key2"));
+        assert_eq!(odd.len(), 2);
+        assert_eq!(odd.get(&"key3"), Some(&Counter(6)));
+
+        // Both halves keep a working init function
+        let _: Vec<_> = odd.update([("key5", Args { value: 5 })]).collect();
+        assert_eq!(odd.get(&"key5"), Some(&Counter(10)));
+        let _: Vec<_> = manager.update([("key4", Args { value: 4 })]).collect();
+        assert_eq!(manager.get(&"key4"), Some(&Counter(8)));
+    }
+
+    #[test]
+    fn test_partition_by_key() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init(
+            [
+                ("eu/key1", Args { value: 1 }),
+                ("us/key2", Args { value: 2 }),
+                ("eu/key3", Args { value: 3 }),
+            ],
+            init,
+        );
+
+        let (eu, rest) = manager.partition(|key, _| key.starts_with("eu/"));
+
+        assert_eq!(eu.len(), 2);
+        assert!(eu.contains_key(&"eu/key1"));
+        assert!(eu.contains_key(&"eu/key3"));
+        assert_eq!(rest.len(), 1);
+        assert!(rest.contains_key(&"us/key2"));
+    }
+
+    #[test]
+    fn test_map_components() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_teardown(move |_: &&str, _: &mut Counter| {
+            *closed_clone.lock().unwrap() += 1;
+        });
+
+        let mut wrapped = manager.map_components(
+            |key, component| format!("{key}:{}", component.0),
+            |key: &&str, args: &Args| format!("{key}:{}", args.value * 10),
+        );
+
+        assert_eq!(*closed.lock().unwrap(), 0);
+        assert_eq!(wrapped.get(&"key1"), Some(&"key1:1".to_string()));
+        assert_eq!(wrapped.get_args(&"key2"), Some(&Args { value: 2 }));
+
+        let _: Vec<_> = wrapped.reinit(["key2"]).collect();
+        assert_eq!(wrapped.get(&"key2"), Some(&"key2:20".to_string()));
+    }
+
+    #[test]
+    fn test_map_args_keeps_components() {
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init([("key1", Args { value: 1 })], init);
+
+        let mut migrated = manager.map_args(
+            |_key, args| args.value.to_string(),
+            |_key: &&str, args: &String| Counter(args.len()),
+        );
+
+        assert_eq!(migrated.get(&"key1"), Some(&Counter(1)));
+        assert_eq!(migrated.get_args(&"key1"), Some(&"1".to_string()));
+        assert_eq!(migrated.is_dirty(&"key1"), Some(true));
+
+        migrated.set_args(&"key1", "1234".to_string());
+        let _: Vec<_> = migrated.reinit_dirty().collect();
+        assert_eq!(migrated.get(&"key1"), Some(&Counter(4)));
+    }
+
+    #[test]
+    fn test_map_args_reinit() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init([("key1", Args { value: 3 })], init).with_teardown(
+            move |key: &&str, component: &mut Counter| {
+                closed_clone.lock().unwrap().push((*key, component.0));
+            },
+        );
+
+        let migrated = manager.map_args_reinit(
+            |_key, args| args.value * 100,
+            |_key: &&str, args: &usize| Counter(*args),
+        );
+
+        assert_eq!(migrated.get(&"key1"), Some(&Counter(300)));
+        assert_eq!(migrated.is_dirty(&"key1"), Some(false));
+        assert_eq!(*closed.lock().unwrap(), vec![("key1", 3)]);
+    }
+
+    #[test]
+    fn test_into_components() {
+        let closed = Arc::new(Mutex::new(0));
+        let closed_clone = closed.clone();
+
+        let init = |_key: &&str, args: &Args| Counter(args.value);
+        let manager = ComponentMap::init(
+            [("key1", Args { value: 1 }), ("key2", Args { value: 2 })],
+            init,
+        )
+        .with_teardown(move |_: &&str, _: &mut Counter| {
+            *closed_clone.lock().unwrap() += 1;
+        });
+
+        let components = manager.into_components();
+
+        assert_eq!(
+            components,
+            HashMap::from([("key1", Counter(1)), ("key2", Counter(2))])
+        );
+        assert_eq!(*closed.lock().unwrap(), 0);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{ReinitTask, SharedComponentMap, Teardown, periodic::Pass};
+use futures::{
+    StreamExt,
+    stream::{self, BoxStream},
+};
+use std::{collections::HashSet, fmt, future::Future};
+use tokio::sync::mpsc;
+
+enum Request<Key> {
+    Key(Key),
+    All,
+}
+
+/// Cloneable sender of reinit requests to a task started by
+/// [`spawn_triggered_reinit`](SharedComponentMap::spawn_triggered_reinit).
+///
+/// Requests arriving while a pass runs are coalesced into the next one, so a
+/// key requested many times is reinitialised once. Results are reported
+/// through the map's [`subscribe`](crate::ComponentMap::subscribe) stream.
+pub struct ReinitTrigger<Key> {
+    requests: mpsc::UnboundedSender<Request<Key>>,
+}
+
+impl<Key> Clone for ReinitTrigger<Key> {
+    fn clone(&self) -> Self {
+        Self {
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+impl<Key> fmt::Debug for ReinitTrigger<Key> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReinitTrigger")
+            .field("closed", &self.requests.is_closed())
+            .finish()
+    }
+}
+
+impl<Key> ReinitTrigger<Key> {
+    /// Requests a reinit of `key`; returns `false` once the task has stopped.
+    pub fn refresh(&self, key: Key) -> bool {
+        self.requests.send(Request::Key(key)).is_ok()
+    }
+
+    /// Requests a reinit of every key; returns `false` once the task has stopped.
+    pub fn refresh_all(&self) -> bool {
+        self.requests.send(Request::All).is_ok()
+    }
+}
+
+impl<Key, Args, Comp, FnInit, FnDrop> SharedComponentMap<Key, Args, Comp, FnInit, FnDrop>
+where
+    Key: Clone + Eq + std::hash::Hash + Send + Sync + 'static,
+    Args: Clone + Send + Sync + 'static,
+    Comp: Clone + Send + Sync + 'static,
+    FnInit: Send + Sync + 'static,
+    FnDrop: Teardown<Key, Comp> + Send + Sync + 'static,
+{
+    /// Spawns a task reinitialising the keys requested through the returned
+    /// [`ReinitTrigger`], at most
+    /// [`ComponentMapConfig::concurrency_limit`](crate::ComponentMapConfig::concurrency_limit)
+    /// at a time. The task stops once every trigger is dropped.
+    pub fn spawn_triggered_reinit<Fut>(&self) -> (ReinitTrigger<Key>, ReinitTask)
+    where
+        FnInit: Fn(&Key, &Args) -> Fut + Clone,
+        Fut: Future<Output = Comp> + Send + 'static,
+    {
+        let (trigger, passes) = trigger();
+        (
+            trigger,
+            self.spawn_passes(passes, |_| true, Self::reinit_one),
+        )
+    }
+
+    /// Like [`spawn_triggered_reinit`](Self::spawn_triggered_reinit) for
+    /// fallible inits, retried according to
+    /// [`ComponentMapConfig::retry`](crate::ComponentMapConfig::retry).
+    pub fn spawn_triggered_try_reinit<Fut, Error>(&self) -> (ReinitTrigger<Key>, ReinitTask)
+    where
+        Error: fmt::Debug + Send + 'static,
+        FnInit: Fn(&Key, &Args) -> Fut + Clone,
+        Fut: Future<Output = Result<Comp, Error>> + Send + 'static,
+    {
+        let (trigger, passes) = trigger();
+        (
+            trigger,
+            self.spawn_passes(passes, |_| true, Self::try_reinit_one),
+        )
+    }
+}
+
+/// Each pass takes every request queued since the previous one.
+fn trigger<Key>() -> (ReinitTrigger<Key>, BoxStream<'static, Pass<Key>>)
+where
+    Key: Eq + std::hash::Hash + Send + 'static,
+{
+    let (requests, receiver) = mpsc::unbounded_channel();
+    let passes = stream::unfold(receiver, |mut receiver| async move {
+        let mut pass = Pass::Keys(HashSet::new());
+        let mut next = receiver.recv().await;
+        while let Some(request) = next {
+            match (&mut pass, request) {
+                (Pass::All, _) => {}
+                (_, Request::All) => pass = Pass::All,
+                (Pass::Keys(keys), Request::Key(key)) => {
+                    keys.insert(key);
+                }
+            }
+            next = receiver.try_recv().ok();
+        }
+        match &pass {
+            Pass::Keys(keys) if keys.is_empty() => None,
+            _ => Some((pass, receiver)),
+        }
+    });
+    (ReinitTrigger { requests }, passes.boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ChangeKind, ComponentMap, SharedComponentMap};
+    use futures::StreamExt;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[tokio::test]
+    async fn test_triggered_requests_are_coalesced() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&str, args: &usize| {
+            calls_clone.fetch_add(1, Ordering::Relaxed);
+            let value = *args;
+            async move { Counter(value) }
+        };
+        let map = ComponentMap::init_async([("key1", 1), ("key2", 2), ("key3", 3)], init).await;
+        calls.store(0, Ordering::Relaxed);
+        let shared = SharedComponentMap::from(map);
+        let mut events = shared.write().subscribe(16);
+
+        let (trigger, task) = shared.spawn_triggered_reinit();
+        assert!(trigger.refresh("key1"));
+        assert!(trigger.refresh("key1"));
+        assert!(trigger.refresh("key2"));
+
+        let mut received = [events.next().await.unwrap(), events.next().await.unwrap()];
+        received.sort_by_key(|event| event.key);
+        assert_eq!(received[0].key, "key1");
+        assert_eq!(received[1].key, "key2");
+        assert!(
+            received
+                .iter()
+                .all(|event| event.kind == ChangeKind::Reinitialized)
+        );
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        assert!(trigger.refresh_all());
+        for _ in 0..3 {
+            events.next().await;
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), 5);
+
+        drop(trigger);
+        task.stop().await;
+    }
+}
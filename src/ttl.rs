@@ -0,0 +1,114 @@
+use crate::{AsyncTeardown, ComponentMap, KeyedStorage, Lookup, Teardown, WithArgs};
+use std::{borrow::Borrow, time::Duration};
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Sets the max age of the component for `key`, overriding
+    /// [`ComponentMapConfig::ttl`](crate::ComponentMapConfig::ttl); `None`
+    /// falls back to it again. The override is dropped when the entry is
+    /// replaced with new args.
+    ///
+    /// Returns `false` if the key is missing.
+    pub fn set_ttl<Q>(&mut self, key: &Q, ttl: Option<Duration>) -> bool
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+    {
+        match self.map.get_mut(key) {
+            Some(component) => {
+                component.ttl = ttl;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the component for `key`, first reinitialising it from its
+    /// current args if it has outlived its TTL.
+    pub fn get_fresh<Q>(&mut self, key: &Q) -> Option<&Comp>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: Fn(&Key, &Args) -> Comp,
+    {
+        if self.map.get(key)?.is_stale(&self.config) {
+            self.reinit([key]).for_each(drop);
+        }
+        self.get(key)
+    }
+
+    /// Like [`get_fresh`](Self::get_fresh), reinitialising with the async init.
+    pub async fn get_fresh_async<Q>(&mut self, key: &Q) -> Option<&Comp>
+    where
+        Key: Eq + std::hash::Hash + Borrow<Q>,
+        Q: Eq + std::hash::Hash + ?Sized,
+        Store: Lookup<Key, WithArgs<Args, Comp>, Q>,
+        FnInit: AsyncFn(&Key, &Args) -> Comp + Clone,
+        FnDrop: AsyncTeardown<Key, Comp>,
+    {
+        if self.map.get(key)?.is_stale(&self.config) {
+            self.reinit_async([key]).await.for_each(drop);
+        }
+        self.get(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComponentMap, ComponentMapConfig};
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_get_fresh_reinits_expired_entries() {
+        let calls = Arc::new(Mutex::new(0));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&str, _args: &()| {
+            let mut calls = calls_clone.lock().unwrap();
+            *calls += 1;
+            Counter(*calls)
+        };
+
+        let mut manager = ComponentMap::init([("token", ()), ("client", ())], init);
+        assert!(manager.set_ttl(&"token", Some(Duration::ZERO)));
+        assert!(!manager.set_ttl(&"missing", None));
+
+        assert_eq!(manager.get_fresh(&"token"), Some(&Counter(3)));
+        assert_eq!(manager.get_fresh(&"token"), Some(&Counter(4)));
+        // Entries without a TTL never expire
+        assert_eq!(manager.get_fresh(&"client"), Some(&Counter(2)));
+        assert_eq!(manager.get_fresh(&"missing"), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_fresh_async_uses_config_default() {
+        let calls = Arc::new(Mutex::new(0));
+        let calls_clone = calls.clone();
+        let init = move |_key: &&str, _args: &()| {
+            let calls = calls_clone.clone();
+            async move {
+                let mut calls = calls.lock().unwrap();
+                *calls += 1;
+                Counter(*calls)
+            }
+        };
+
+        let mut manager = ComponentMap::init_async([("token", ())], init)
+            .await
+            .with_config(ComponentMapConfig::default().ttl(Duration::from_secs(3600)));
+        assert_eq!(manager.get_fresh_async(&"token").await, Some(&Counter(1)));
+
+        manager.set_ttl(&"token", Some(Duration::ZERO));
+        assert_eq!(manager.get_fresh_async(&"token").await, Some(&Counter(2)));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{ChangeKind, ComponentMap, KeyedStorage, Teardown, WithArgs};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+impl<Key, Args, Comp, FnInit, FnDrop, Store> ComponentMap<Key, Args, Comp, FnInit, FnDrop, Store>
+where
+    FnDrop: Teardown<Key, Comp>,
+    Store: KeyedStorage<Key, WithArgs<Args, Comp>>,
+{
+    /// Returns a receiver holding the current component for `key` that is
+    /// updated whenever the key is reinitialised or replaced, or `None` if the
+    /// key is missing.
+    ///
+    /// The channel closes once the key is removed.
+    pub fn watch(&mut self, key: Key) -> Option<watch::Receiver<Arc<Comp>>>
+    where
+        Key: Eq + std::hash::Hash + Send + Sync + 'static,
+        Comp: Clone + Send + Sync + 'static,
+    {
+        let current = Arc::new(self.map.get(&key)?.component.clone());
+        let (sender, receiver) = watch::channel(current);
+
+        self.events
+            .push(Box::new(move |changed: &Key, kind, entry, _| {
+                if *changed != key {
+                    return !sender.is_closed();
+                }
+                match (kind, entry) {
+                    (ChangeKind::Removed, _) => false,
+                    (ChangeKind::Failed, _) | (_, None) => !sender.is_closed(),
+                    (_, Some(entry)) => sender.send(Arc::new(entry.component.clone())).is_ok(),
+                }
+            }));
+        Some(receiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_watch_publishes_changes_to_its_key() {
+        let init = |_key: &&str, args: &usize| Counter(*args);
+        let mut manager = ComponentMap::init([("key1", 1), ("key2", 2)], init);
+        let mut receiver = manager.watch("key1").unwrap();
+        assert_eq!(**receiver.borrow_and_update(), Counter(1));
+
+        manager.update([("key2", 20)]).for_each(drop);
+        assert!(!receiver.has_changed().unwrap());
+
+        manager.update([("key1", 10)]).for_each(drop);
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(**receiver.borrow_and_update(), Counter(10));
+
+        manager.set_args(&"key1", 11);
+        manager.reinit([&"key1"]).for_each(drop);
+        assert_eq!(**receiver.borrow_and_update(), Counter(11));
+
+        manager.remove(&"key1");
+        assert!(receiver.has_changed().is_err());
+        assert!(manager.watch("key1").is_none());
+    }
+}